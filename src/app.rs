@@ -2,46 +2,191 @@
 //!
 //! Provides a unified `App::run()` that works with both TUI and GUI backends.
 //! The developer provides a callback that receives events and a renderer —
-//! no backend-specific code needed.
+//! no backend-specific code needed. Every loop runs input through a
+//! `DragTracker`, so the callback also sees drag events.
 
-use crate::event::Event;
+use crate::drag::DragTracker;
+use crate::event::{Event, EventSource};
 use crate::render::Renderer;
 use anyhow::Result;
+use std::time::Duration;
 
 /// Application runner that owns the event loop and renderer
 pub struct App;
 
+/// Settings for the run loops
+///
+/// The defaults match `App::run_tui`: Ctrl-Z suspends the process, and the
+/// loop blocks until input arrives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunOptions {
+    suspend_on_ctrl_z: bool,
+    tick: Option<Duration>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunOptions {
+    /// Options with the defaults
+    pub fn new() -> Self {
+        RunOptions {
+            suspend_on_ctrl_z: true,
+            tick: None,
+        }
+    }
+
+    /// Whether Ctrl-Z from a live source suspends the process
+    ///
+    /// Turn it off to receive Ctrl-Z in the callback, e.g. to bind it to undo.
+    pub fn with_suspend_on_ctrl_z(mut self, suspend: bool) -> Self {
+        self.suspend_on_ctrl_z = suspend;
+        self
+    }
+
+    /// Send an `EventKind::Redraw` after `interval` without input
+    ///
+    /// Components act on timeouts, like a pending key sequence in a
+    /// `SplitView`, when an event arrives; the tick lets them fire while the
+    /// user is idle. `Keymap::timeout` is a reasonable upper bound.
+    pub fn with_tick(mut self, interval: Duration) -> Self {
+        self.tick = Some(interval);
+        self
+    }
+
+    /// Wait for the next event, or the tick if one is set
+    fn next_event<S: EventSource>(&self, events: &mut S) -> Result<Option<Event>> {
+        match self.tick {
+            Some(interval) => events.next_event_timeout(interval),
+            None => events.next_event(),
+        }
+    }
+
+    /// Whether the loop suspends instead of passing `event` on
+    #[cfg(feature = "tui")]
+    fn suspends_on(&self, event: &Event, live: bool) -> bool {
+        self.suspend_on_ctrl_z && live && event.kind.is_ctrl('z')
+    }
+}
+
+/// The TUI event loop, on a renderer that is already set up
+#[cfg(feature = "tui")]
+fn run_terminal<S, F>(
+    mut events: S,
+    options: RunOptions,
+    renderer: &mut crate::tui::TerminalRenderer,
+    mut callback: F,
+) -> Result<()>
+where
+    S: EventSource,
+    F: FnMut(&Event, &mut dyn Renderer) -> bool,
+{
+    use crate::event::EventKind;
+    use crate::terminal::TerminalGeometry;
+
+    let live = events.is_live();
+    let mut drags = DragTracker::new();
+
+    while let Some(event) = options.next_event(&mut events)? {
+        renderer.record_input(&event);
+
+        if options.suspends_on(&event, live) {
+            renderer.suspend()?;
+            let geometry = renderer.context().geometry;
+            let resumed = Event::resize(geometry.cols, geometry.rows);
+            if !callback(&resumed, renderer) {
+                break;
+            }
+            continue;
+        }
+
+        if let EventKind::Resize(cols, rows) = event.kind {
+            if live {
+                renderer.refresh_geometry()?;
+            } else {
+                let current = renderer.context().geometry;
+                let geometry =
+                    TerminalGeometry::with_char_size(cols, rows, current.char_width, current.char_height);
+                renderer.set_geometry(geometry)?;
+            }
+        }
+
+        for event in drags.process(&event) {
+            if !callback(&event, renderer) {
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl App {
     /// Run the application with the TUI backend
     ///
     /// Creates a `TerminalRenderer`, enters alt screen, and runs a blocking
     /// event loop. The callback receives each event and the renderer. Return
     /// `false` from the callback to exit.
+    ///
+    /// Ctrl-Z suspends the process like any shell job; on resume the callback
+    /// receives a `Resize` event so it can draw a fresh frame. Use
+    /// `run_tui_with` and `RunOptions::with_suspend_on_ctrl_z` to handle
+    /// Ctrl-Z yourself.
     #[cfg(feature = "tui")]
-    pub fn run_tui<F>(mut callback: F) -> Result<()>
+    pub fn run_tui<F>(callback: F) -> Result<()>
     where
         F: FnMut(&Event, &mut dyn Renderer) -> bool,
     {
-        use crate::event::{EventKind, EventPoller};
-        use crate::tui::TerminalRenderer;
+        Self::run_tui_with(crate::event::EventPoller::new()?, RunOptions::new(), callback)
+    }
 
-        let mut renderer = TerminalRenderer::new()?;
+    /// Run the application with the TUI backend, taking events from `events`
+    ///
+    /// Like `run_tui`, but input comes from any `EventSource` — e.g. a
+    /// `replay::ReplaySource` to reproduce a recorded session. The loop also
+    /// ends when the source runs out of events.
+    ///
+    /// Only live sources (see `EventSource::is_live`) suspend on Ctrl-Z, and
+    /// only while `options` allows it, and re-read the terminal size; a
+    /// replayed resize applies the recorded size instead.
+    #[cfg(feature = "tui")]
+    pub fn run_tui_with<S, F>(events: S, options: RunOptions, callback: F) -> Result<()>
+    where
+        S: EventSource,
+        F: FnMut(&Event, &mut dyn Renderer) -> bool,
+    {
+        let mut renderer = crate::tui::TerminalRenderer::new()?;
         renderer.enter_alt_screen()?;
+        run_terminal(events, options, &mut renderer, callback)
+    }
 
-        let events = EventPoller::new()?;
-
-        loop {
-            let event = events.read()?;
-
-            if let EventKind::Resize(_, _) = &event.kind {
-                renderer.refresh_geometry()?;
-            }
-
-            if !callback(&event, &mut renderer) {
-                break;
+    /// Run an event loop against an existing renderer
+    ///
+    /// Backend-neutral: no terminal is touched, so a `ReplaySource` and a
+    /// `TestRenderer` can drive the app in integration tests. Returns when
+    /// the callback returns `false` or the source is exhausted. Only the
+    /// tick of `options` applies here.
+    pub fn run_with<S, F>(
+        mut events: S,
+        options: RunOptions,
+        renderer: &mut dyn Renderer,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        S: EventSource,
+        F: FnMut(&Event, &mut dyn Renderer) -> bool,
+    {
+        let mut drags = DragTracker::new();
+        while let Some(event) = options.next_event(&mut events)? {
+            for event in drags.process(&event) {
+                if !callback(&event, renderer) {
+                    return Ok(());
+                }
             }
         }
-
         Ok(())
     }
 
@@ -55,7 +200,7 @@ impl App {
     where
         F: FnMut(&Event, &mut dyn Renderer) -> bool + 'static,
     {
-        use crate::event::convert_winit_event;
+        use crate::event::{convert_winit_event, EventKind, KeyState, MouseButton, MouseEvent};
         use crate::gui::WgpuRenderer;
         use std::sync::Arc;
         use winit::application::ApplicationHandler;
@@ -68,6 +213,9 @@ impl App {
             font_size: f32,
             window: Option<Arc<Window>>,
             renderer: Option<WgpuRenderer>,
+            /// Last pointer position in physical pixels, for link hit-testing
+            pointer: (f64, f64),
+            drags: DragTracker,
             callback: F,
         }
 
@@ -111,15 +259,43 @@ impl App {
                     }
                 }
 
-                if let Some(mkui_event) = convert_winit_event(&event) {
-                    if let Some(renderer) = &mut self.renderer {
-                        if !(self.callback)(&mkui_event, renderer) {
-                            self.renderer.take();
-                            self.window.take();
-                            event_loop.exit();
-                        }
+                if let WindowEvent::CursorMoved { position, .. } = &event {
+                    self.pointer = (position.x, position.y);
+                }
+
+                let Some(mkui_event) = convert_winit_event(&event) else {
+                    return;
+                };
+                let Some(renderer) = &mut self.renderer else {
+                    return;
+                };
+
+                // A click on linked text is also reported as the link
+                let link = match mkui_event.kind {
+                    EventKind::Mouse(MouseEvent::Button {
+                        button: MouseButton::Left,
+                        state: KeyState::Pressed,
+                        ..
+                    }) => renderer.link_at(self.pointer.0, self.pointer.1),
+                    _ => None,
+                };
+
+                let mut keep_running = true;
+                for event in self.drags.process(&mkui_event) {
+                    keep_running = (self.callback)(&event, renderer);
+                    if !keep_running {
+                        break;
                     }
                 }
+                if let Some(link) = link.filter(|_| keep_running) {
+                    let clicked = Event::new(EventKind::LinkClicked(link));
+                    keep_running = (self.callback)(&clicked, renderer);
+                }
+                if !keep_running {
+                    self.renderer.take();
+                    self.window.take();
+                    event_loop.exit();
+                }
             }
 
             fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
@@ -135,6 +311,8 @@ impl App {
             font_size,
             window: None,
             renderer: None,
+            pointer: (0.0, 0.0),
+            drags: DragTracker::new(),
             callback,
         };
         event_loop.run_app(&mut handler)?;
@@ -142,3 +320,65 @@ impl App {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+    use crate::event::{EventKind, Key, Modifiers};
+    use crate::replay::ReplaySource;
+    use crate::terminal::TerminalGeometry;
+    use crate::tui::TerminalRenderer;
+
+    #[test]
+    fn test_replay_applies_recorded_size_and_passes_ctrl_z() {
+        let geometry = TerminalGeometry::with_char_size(80, 24, 10, 20);
+        let mut renderer = TerminalRenderer::with_writer(std::io::sink(), geometry);
+        let source = ReplaySource::parse("0 resize 100 30\n5 key \"z\" press ctrl\n")
+            .unwrap()
+            .instant();
+
+        let mut sizes = Vec::new();
+        let mut keys = Vec::new();
+        run_terminal(source, RunOptions::new(), &mut renderer, |event, renderer| {
+            match &event.kind {
+                EventKind::Resize(..) => sizes.push(renderer.dimensions()),
+                EventKind::Key { key, .. } => keys.push(*key),
+                _ => {}
+            }
+            true
+        })
+        .unwrap();
+
+        assert_eq!(sizes, [(100, 30)]);
+        assert_eq!(keys, [Key::Char('z')]);
+    }
+
+    #[test]
+    fn test_loops_wait_for_the_tick_only_when_asked() {
+        struct Idle;
+
+        impl EventSource for Idle {
+            fn next_event(&mut self) -> Result<Option<Event>> {
+                Ok(None)
+            }
+
+            fn next_event_timeout(&mut self, _timeout: Duration) -> Result<Option<Event>> {
+                Ok(Some(Event::new(EventKind::Redraw)))
+            }
+        }
+
+        assert!(RunOptions::new().next_event(&mut Idle).unwrap().is_none());
+        let ticking = RunOptions::new().with_tick(Duration::from_millis(100));
+        assert!(ticking.next_event(&mut Idle).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_suspend_on_ctrl_z_can_be_turned_off() {
+        let ctrl_z = Event::key_with_mods(Key::Char('z'), Modifiers { ctrl: true, ..Modifiers::none() });
+        assert!(RunOptions::new().suspends_on(&ctrl_z, true));
+        assert!(!RunOptions::new().suspends_on(&ctrl_z, false));
+        assert!(!RunOptions::new()
+            .with_suspend_on_ctrl_z(false)
+            .suspends_on(&ctrl_z, true));
+    }
+}
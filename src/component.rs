@@ -2,6 +2,7 @@
 
 use crate::context::RenderContext;
 use crate::event::{Event, EventHandler};
+use crate::hit::HitMap;
 use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::signal::SignalBase;
@@ -85,6 +86,52 @@ pub fn propagate_event(children: &mut [Box<dyn Component>], event: &Event) -> bo
     false
 }
 
+/// Helper to render children clipped to their bounds, recording each in
+/// `hits` under its index for `route_event`
+///
+/// Children without bounds are skipped.
+pub fn render_children(
+    children: &mut [Box<dyn Component>],
+    bounds: &[Rect],
+    hits: &mut HitMap,
+    renderer: &mut dyn Renderer,
+    ctx: &RenderContext,
+) -> Result<()> {
+    hits.clear();
+    for (i, (child, rect)) in children.iter_mut().zip(bounds).enumerate() {
+        hits.record(i, *rect);
+        render_clipped(child.as_mut(), renderer, *rect, ctx)?;
+    }
+    Ok(())
+}
+
+/// Helper to route an event to children rendered with `render_children`
+///
+/// Mouse events go only to the child under the pointer, or to the one
+/// holding the capture during a drag (see `HitMap`); other events are
+/// offered to each child in turn, as with `propagate_event`.
+pub fn route_event(children: &mut [Box<dyn Component>], hits: &mut HitMap, event: &Event) -> bool {
+    let routed = hits.dispatch(event, |&i, event| {
+        children.get_mut(i).is_some_and(|child| child.handle_event(event))
+    });
+    routed.unwrap_or_else(|| propagate_event(children, event))
+}
+
+/// Helper to render a child clipped to its bounds
+///
+/// The clip is popped even when the child fails to render.
+pub fn render_clipped(
+    child: &mut dyn Component,
+    renderer: &mut dyn Renderer,
+    bounds: Rect,
+    ctx: &RenderContext,
+) -> Result<()> {
+    renderer.push_clip(bounds);
+    let result = child.render(renderer, bounds, ctx);
+    renderer.pop_clip();
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,8 +4,11 @@
 //! what needs repainting. The framework only calls `render()` when either:
 //! - The component's data changed (generation increased)
 //! - The component's bounds changed (layout gave it different dimensions/position)
+//! - Its bounds overlap the renderer's dirty region (something painted over it)
 //!
 //! When bounds shrink or move, the vacated region is cleared automatically.
+//! Every repainted area is marked dirty, so components overlapping it later
+//! in the frame repaint too, while those elsewhere on screen are left alone.
 
 use crate::layout::Rect;
 use crate::render::Renderer;
@@ -41,7 +44,8 @@ impl RenderTracker {
     /// Check if a component needs rendering and update its tracked state.
     ///
     /// Returns true if the component should be rendered. If the bounds changed,
-    /// clears the old region automatically.
+    /// clears the old region automatically. Components are checked in paint
+    /// order, so one rendered earlier in the frame damages those beneath it.
     ///
     /// `id` is a stable identifier for the component (index, hash, etc.)
     pub fn needs_render(
@@ -60,8 +64,11 @@ impl RenderTracker {
                 let gen_changed = generation != old.generation && generation != u64::MAX;
                 let bounds_changed = bounds != old.bounds;
 
-                // GPU surfaces don't retain — must redraw everything every frame
-                let must_redraw = !renderer.retains_content();
+                // Surfaces that don't retain must redraw everything every frame
+                let must_redraw = !renderer.retains_content()
+                    || renderer
+                        .dirty_region()
+                        .intersects(bounds.x, bounds.y, bounds.width, bounds.height);
 
                 if !must_redraw && !gen_changed && !bounds_changed && generation != u64::MAX {
                     return false;
@@ -70,15 +77,19 @@ impl RenderTracker {
                 // Clear vacated region if bounds moved or shrunk
                 if bounds_changed {
                     clear_vacated(renderer, old.bounds, bounds, self.bg_color);
+                    let vacated = old.bounds;
+                    renderer.mark_dirty(vacated.x, vacated.y, vacated.width, vacated.height);
                 }
 
                 // Update state
                 self.states[idx].1 = RenderState { generation, bounds };
+                renderer.mark_dirty(bounds.x, bounds.y, bounds.width, bounds.height);
                 true
             }
             None => {
                 // New component — always render
                 self.states.push((id, RenderState { generation, bounds }));
+                renderer.mark_dirty(bounds.x, bounds.y, bounds.width, bounds.height);
                 true
             }
         }
@@ -177,10 +188,8 @@ mod tests {
 
     #[test]
     fn test_new_component_always_renders() {
-        struct NullRenderer;
         // Can't easily test with a real renderer, so test the state logic directly
-        let mut tracker = RenderTracker::new();
-        let bounds = Rect::new(0, 0, 10, 5);
+        let tracker = RenderTracker::new();
 
         // First check — new component, should render
         let existing = tracker.states.iter().position(|(sid, _)| *sid == 0);
@@ -233,4 +242,24 @@ mod tests {
         // gen_changed is false because both are MAX, but the special case handles it
         assert!(!gen_changed); // This is correct — the needs_render method handles u64::MAX specially
     }
+
+    #[test]
+    fn test_only_damaged_components_rerender() {
+        let mut renderer = crate::testing::TestRenderer::new(80, 24);
+        let mut tracker = RenderTracker::new();
+        let top_left = Rect::new(0, 0, 10, 1);
+        let bottom_right = Rect::new(70, 23, 10, 1);
+        let under_top_left = Rect::new(0, 0, 20, 5);
+
+        for (id, bounds) in [(0, top_left), (1, bottom_right), (2, under_top_left)] {
+            assert!(tracker.needs_render(&mut renderer, id, 1, bounds));
+        }
+        renderer.clear_dirty();
+
+        // The top-left meter ticks: whatever it overlaps repaints, the far corner doesn't
+        assert!(tracker.needs_render(&mut renderer, 0, 2, top_left));
+        assert!(!tracker.needs_render(&mut renderer, 1, 1, bottom_right));
+        assert!(tracker.needs_render(&mut renderer, 2, 1, under_top_left));
+        assert_eq!(renderer.dirty_region().rects(), &[under_top_left]);
+    }
 }
@@ -112,9 +112,14 @@ pub struct CommandPalette {
     dirty: bool,
     /// Saved input before history navigation
     saved_input: Option<String>,
+    /// Bounds the input occupies on the overlay, if drawn
+    drawn: Option<Rect>,
 }
 
 impl CommandPalette {
+    /// Overlay layer the input is drawn on, above popups
+    pub const LAYER: u16 = 20;
+
     /// Create a new command palette
     pub fn new() -> Self {
         CommandPalette {
@@ -130,6 +135,7 @@ impl CommandPalette {
             last_message: None,
             dirty: true,
             saved_input: None,
+            drawn: None,
         }
     }
 
@@ -410,8 +416,24 @@ impl EventHandler for CommandPalette {
     }
 }
 
+impl CommandPalette {
+    /// Draw the input over a blanked line, so nothing from the last frame
+    /// survives on the layer
+    fn draw_input(&mut self, renderer: &mut dyn Renderer, bounds: Rect, ctx: &RenderContext) -> Result<()> {
+        renderer.move_cursor(bounds.x, bounds.y)?;
+        renderer.write_repeated(' ', bounds.width as usize)?;
+        self.input.render(renderer, bounds, ctx)
+    }
+}
+
 impl Component for CommandPalette {
     fn render(&mut self, renderer: &mut dyn Renderer, bounds: Rect, ctx: &RenderContext) -> Result<()> {
+        // The input floats on its own layer, kept while it stays open, so
+        // closing it restores the line
+        let moved = self.drawn.is_some_and(|drawn| drawn != bounds);
+        if (!self.active || moved) && self.drawn.take().is_some() {
+            renderer.clear_layer(Self::LAYER);
+        }
         if !self.active {
             // When inactive, show last message or error if any
             if let Some(error) = &self.last_error {
@@ -426,9 +448,11 @@ impl Component for CommandPalette {
         }
 
         // Render the text input
-        self.input.render(renderer, bounds, ctx)?;
-
-        Ok(())
+        self.drawn = Some(bounds);
+        renderer.push_layer(Self::LAYER);
+        let result = self.draw_input(renderer, bounds, ctx);
+        renderer.pop_layer();
+        result
     }
 
     fn min_size(&self) -> (u16, u16) {
@@ -445,6 +469,7 @@ impl Component for CommandPalette {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{snapshot_theme, Snapshot};
 
     struct MockExecutor;
 
@@ -539,4 +564,50 @@ mod tests {
         palette.complete_prev();
         assert_eq!(palette.value(), "write");
     }
+
+    #[test]
+    fn test_snapshot_active_with_input() {
+        let mut palette = CommandPalette::new();
+        palette.activate(CommandMode::Ex);
+        palette.input.set_value("write");
+        let snapshot = Snapshot::render_with_theme(&mut palette, 20, 1, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("command_palette_active_with_input", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_input_scrolls() {
+        let mut palette = CommandPalette::new();
+        palette.activate(CommandMode::Search);
+        palette.input.set_value("a search pattern longer than the bar");
+        let snapshot = Snapshot::render_with_theme(&mut palette, 16, 1, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("command_palette_input_scrolls", snapshot);
+    }
+
+    #[test]
+    fn test_shortened_input_leaves_no_stale_cells() {
+        use crate::testing::TestRenderer;
+        use crate::theme::Theme;
+
+        let theme = Theme::new();
+        let slots = crate::slots::Slots::new();
+        let ctx = RenderContext::new(&theme, &slots);
+        let bounds = Rect::new(0, 0, 20, 1);
+        let mut renderer = TestRenderer::new(20, 1);
+        let mut palette = CommandPalette::new();
+        palette.activate(CommandMode::Ex);
+        palette.input.set_value("write");
+        palette.render(&mut renderer, bounds, &ctx).unwrap();
+
+        palette.input.set_value("w");
+        palette.render(&mut renderer, bounds, &ctx).unwrap();
+        assert_eq!(renderer.row_text(0), ":w");
+    }
+
+    #[test]
+    fn test_snapshot_inactive_error() {
+        let mut palette = CommandPalette::new();
+        palette.set_error("E492: Not an editor command");
+        let snapshot = Snapshot::render_with_theme(&mut palette, 30, 1, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("command_palette_inactive_error", snapshot);
+    }
 }
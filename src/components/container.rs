@@ -1,8 +1,16 @@
 //! Flex container component
-
-use crate::component::{propagate_event, Component as ComponentTrait, Container as ContainerTrait};
+//!
+//! Mouse events go only to the child under the pointer (see
+//! `component::route_event`), so a child sees clicks, scrolls, and hover
+//! within its own bounds and the drags it started. Other events are offered
+//! to each child in turn until one consumes them.
+
+use crate::component::{
+    render_children, route_event, Component as ComponentTrait, Container as ContainerTrait,
+};
 use crate::context::RenderContext;
 use crate::event::{Event, EventHandler};
+use crate::hit::HitMap;
 use crate::layout::{FlexDirection, FlexLayout, Rect, Size};
 use crate::render::Renderer;
 use anyhow::Result;
@@ -13,6 +21,8 @@ pub struct Container {
     layout: FlexLayout,
     sizes: Vec<Size>,
     dirty: bool,
+    /// Child bounds from the last render, by child index
+    hits: HitMap,
 }
 
 impl Container {
@@ -23,6 +33,7 @@ impl Container {
             layout: FlexLayout::new(direction),
             sizes: Vec::new(),
             dirty: true,
+            hits: HitMap::new(),
         }
     }
 
@@ -67,7 +78,7 @@ impl Container {
 
 impl EventHandler for Container {
     fn handle_event(&mut self, event: &Event) -> bool {
-        propagate_event(&mut self.children, event)
+        route_event(&mut self.children, &mut self.hits, event)
     }
 }
 
@@ -77,11 +88,7 @@ impl ComponentTrait for Container {
         let child_bounds = self.layout.layout(bounds, &self.sizes);
 
         // Render each child in its calculated bounds
-        for (child, rect) in self.children.iter_mut().zip(child_bounds.iter()) {
-            child.render(renderer, *rect, ctx)?;
-        }
-
-        Ok(())
+        render_children(&mut self.children, &child_bounds, &mut self.hits, renderer, ctx)
     }
 
 
@@ -108,6 +115,7 @@ impl ContainerTrait for Container {
     fn remove_child(&mut self, index: usize) -> Option<Box<dyn ComponentTrait>> {
         if index < self.children.len() {
             self.sizes.remove(index);
+            self.hits.clear();
             self.dirty = true;
             Some(self.children.remove(index))
         } else {
@@ -115,3 +123,59 @@ impl ContainerTrait for Container {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{EventKind, Key, KeyState, Modifiers, MouseButton, MouseEvent};
+    use crate::testing::{snapshot_theme, Snapshot};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Child that logs the events it is offered and consumes none
+    struct Probe(Rc<RefCell<Vec<EventKind>>>);
+
+    impl EventHandler for Probe {
+        fn handle_event(&mut self, event: &Event) -> bool {
+            self.0.borrow_mut().push(event.kind.clone());
+            false
+        }
+    }
+
+    impl ComponentTrait for Probe {
+        fn render(&mut self, _renderer: &mut dyn Renderer, _bounds: Rect, _ctx: &RenderContext) -> Result<()> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "Probe"
+        }
+    }
+
+    #[test]
+    fn test_mouse_events_go_to_child_under_pointer() {
+        let logs = [Rc::default(), Rc::default()];
+        let mut container = Container::row();
+        for log in &logs {
+            container.add_flex(Box::new(Probe(Rc::clone(log))), 1);
+        }
+        Snapshot::render_with_theme(&mut container, 20, 1, &snapshot_theme()).unwrap();
+
+        let click = EventKind::Mouse(MouseEvent::Button {
+            button: MouseButton::Left,
+            state: KeyState::Pressed,
+            col: 15,
+            row: 0,
+            modifiers: Modifiers::none(),
+        });
+        container.handle_event(&Event::new(click.clone()));
+        assert!(logs[0].borrow().is_empty());
+        assert_eq!(*logs[1].borrow(), [click]);
+
+        // Everything else is still offered to every child
+        let key = Event::key(Key::Char('x'));
+        container.handle_event(&key);
+        assert_eq!(logs[0].borrow().last(), Some(&key.kind));
+        assert_eq!(logs[1].borrow().last(), Some(&key.kind));
+    }
+}
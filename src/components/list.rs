@@ -5,6 +5,8 @@
 //! - Selection highlighting
 //! - Virtual scrolling for large lists
 //! - Focus integration
+//! - Click to select, scroll wheel to scroll
+//! - Dragging items in and out with `with_drag_and_drop`
 //!
 //! # Example
 //!
@@ -29,10 +31,13 @@
 use crate::component::Component;
 use crate::components::scrollable::ScrollableView;
 use crate::context::RenderContext;
-use crate::event::{Event, EventHandler, Key};
+use crate::drag::DragAndDrop;
+use crate::event::{Event, EventHandler, EventKind, Key, KeyState, MouseButton, MouseEvent};
+use crate::hit::HitMap;
 use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::style::Style;
+use crate::unicode;
 use anyhow::Result;
 
 /// Selection mode for the list
@@ -79,6 +84,44 @@ pub struct List<T> {
 
     /// Viewport height (set during render)
     viewport_height: u16,
+
+    /// Row bounds from the last render, by item index; the blank space
+    /// after the last item is keyed by the item count
+    hits: HitMap,
+
+    /// Drag and drop wiring, set by `with_drag_and_drop`
+    drag: Option<ListDrag<T>>,
+}
+
+/// How a list takes part in drag and drop
+///
+/// The payload is an item; the functions are instantiated where `T` is
+/// known to be `Clone`, so the rest of `List` doesn't need that bound.
+#[derive(Debug)]
+struct ListDrag<T> {
+    dnd: DragAndDrop,
+    /// Start dragging a copy of an item
+    start: fn(&DragAndDrop, &T),
+    /// Take the payload if it is an item
+    accept: fn(&DragAndDrop) -> Option<T>,
+    /// Whether the payload is an item
+    carries_item: fn(&DragAndDrop) -> bool,
+    /// Item being dragged out of this list
+    from: Option<usize>,
+    /// Row a hovering drag would drop on
+    target: Option<usize>,
+}
+
+fn start_item_drag<T: Clone + 'static>(dnd: &DragAndDrop, item: &T) {
+    dnd.start(item.clone());
+}
+
+fn accept_item<T: Clone + 'static>(dnd: &DragAndDrop) -> Option<T> {
+    dnd.accept::<T>().map(|item| T::clone(&item))
+}
+
+fn carries_item<T: 'static>(dnd: &DragAndDrop) -> bool {
+    dnd.payload::<T>().is_some()
 }
 
 impl<T> List<T> {
@@ -94,6 +137,8 @@ impl<T> List<T> {
             focused: false,
             dirty: true,
             viewport_height: 10,
+            hits: HitMap::new(),
+            drag: None,
         }
     }
 
@@ -115,6 +160,51 @@ impl<T> List<T> {
         self.selected_index = None;
         self.selected_indices.clear();
         self.scroll = ScrollableView::vertical(height);
+        self.hits.clear();
+    }
+
+    /// Insert an item before `index` (or at the end), keeping the selection
+    /// on the same items
+    pub fn insert(&mut self, index: usize, item: T) {
+        let index = index.min(self.items.len());
+        self.items.insert(index, item);
+        let shift = |i: &mut usize| {
+            if *i >= index {
+                *i += 1;
+            }
+        };
+        self.selected_indices.iter_mut().chain(&mut self.selected_index).for_each(shift);
+        self.content_changed();
+    }
+
+    /// Remove an item, keeping the selection on the remaining items
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.items.len() {
+            return None;
+        }
+        let item = self.items.remove(index);
+        self.selected_indices.retain(|&i| i != index);
+        for i in &mut self.selected_indices {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+        self.selected_index = match self.selected_index {
+            Some(i) if i > index => Some(i - 1),
+            Some(i) if i == index && self.items.is_empty() => None,
+            Some(i) if i == index => Some(index.min(self.items.len() - 1)),
+            other => other,
+        };
+        self.content_changed();
+        Some(item)
+    }
+
+    /// Resize the scroll area and drop stale row bounds after items change
+    fn content_changed(&mut self) {
+        let (width, _) = self.scroll.content_size();
+        self.scroll.set_content_size(width, self.items.len());
+        self.hits.clear();
+        self.dirty = true;
     }
 
     /// Get items
@@ -308,6 +398,25 @@ impl<T> List<T> {
     }
 }
 
+impl<T: Clone + 'static> List<T> {
+    /// Let items be dragged out of this list and dropped into it
+    ///
+    /// The payload is a copy of the item. A drop inserts it before the row
+    /// under the pointer, or at the end, and the source list then removes
+    /// its copy; dropping back onto the same list moves the item there.
+    pub fn with_drag_and_drop(mut self, dnd: DragAndDrop) -> Self {
+        self.drag = Some(ListDrag {
+            dnd,
+            start: start_item_drag::<T>,
+            accept: accept_item::<T>,
+            carries_item: carries_item::<T>,
+            from: None,
+            target: None,
+        });
+        self
+    }
+}
+
 impl<T: ToString> List<T> {
     /// Render the list with default string conversion
     pub fn render_default(
@@ -317,10 +426,18 @@ impl<T: ToString> List<T> {
         _ctx: &RenderContext,
     ) -> Result<()> {
         self.viewport_height = bounds.height;
+        self.hits.clear();
+
+        let drop_target = self.drop_target();
 
         if self.items.is_empty() {
+            self.hits.record(0, bounds);
             renderer.move_cursor(bounds.x, bounds.y)?;
-            renderer.write_text("(empty)")?;
+            if drop_target.is_some() {
+                renderer.write_styled("(empty)", &Style::new().overline(true))?;
+            } else {
+                renderer.write_text("(empty)")?;
+            }
             return Ok(());
         }
 
@@ -338,23 +455,42 @@ impl<T: ToString> List<T> {
             let is_selected = self.is_selected(i);
             let is_cursor = self.selected_index == Some(i);
 
+            self.hits.record(i, Rect::new(bounds.x, y, bounds.width, 1));
             renderer.move_cursor(bounds.x, y)?;
 
             // Render item text, truncated to fit
             let text = item.to_string();
             let max_width = bounds.width as usize;
-            let display_text = if text.len() > max_width {
-                format!("{}...", &text[..max_width.saturating_sub(3)])
-            } else {
-                format!("{:width$}", text, width = max_width)
-            };
+            let fitted = unicode::truncate_with_ellipsis(&text, max_width);
+            let padding = max_width - unicode::width(&fitted);
+            let display_text = format!("{}{}", fitted, " ".repeat(padding));
 
-            if is_selected && self.focused {
-                renderer.write_styled(&display_text, &Style::new().reverse(true))?;
+            let style = if is_selected && self.focused {
+                Style::new().reverse(true)
             } else if is_cursor {
-                renderer.write_styled(&display_text, &Style::new().underline(true))?;
+                Style::new().underline(true)
             } else {
+                Style::new()
+            };
+            // A line over the row a drag would drop on
+            if drop_target == Some(i) {
+                renderer.write_styled(&display_text, &style.overline(true))?;
+            } else if style.is_empty() {
                 renderer.write_text(&display_text)?;
+            } else {
+                renderer.write_styled(&display_text, &style)?;
+            }
+        }
+
+        // The blank space after the last item takes drops at the end
+        let used = (self.items.len() - offset).min(visible_count) as u16;
+        if used < bounds.height {
+            let blank = Rect::new(bounds.x, bounds.y + used, bounds.width, bounds.height - used);
+            self.hits.record(self.items.len(), blank);
+            if drop_target == Some(self.items.len()) {
+                renderer.move_cursor(blank.x, blank.y)?;
+                let line = " ".repeat(bounds.width as usize);
+                renderer.write_styled(&line, &Style::new().overline(true))?;
             }
         }
 
@@ -362,8 +498,140 @@ impl<T: ToString> List<T> {
     }
 }
 
+impl<T> List<T> {
+    /// Row a drag hovering over the list would drop on; the item count
+    /// stands for the end of the list
+    pub fn drop_target(&self) -> Option<usize> {
+        self.drag.as_ref().and_then(|drag| drag.target)
+    }
+
+    /// Handle clicks, the wheel, and drags
+    ///
+    /// Only events over the list count, so this doesn't need focus; the
+    /// exceptions are the end of a drag that started here and the pointer
+    /// leaving.
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> bool {
+        match mouse {
+            MouseEvent::Leave => return self.set_drop_target(None),
+            MouseEvent::DragEnd { .. } => return self.end_drag(),
+            _ => {}
+        }
+
+        let Some(&index) = mouse.position().and_then(|(col, row)| self.hits.hit(col, row)) else {
+            return false;
+        };
+        match mouse {
+            MouseEvent::DragStart {
+                button: MouseButton::Left,
+                ..
+            } => {
+                let (Some(drag), Some(item)) = (&mut self.drag, self.items.get(index)) else {
+                    return false;
+                };
+                (drag.start)(&drag.dnd, item);
+                drag.from = Some(index);
+                true
+            }
+            MouseEvent::Enter { .. } | MouseEvent::Hover { .. } => {
+                let accepts = self.drag.as_ref().is_some_and(|drag| (drag.carries_item)(&drag.dnd));
+                self.set_drop_target(accepts.then_some(index))
+            }
+            MouseEvent::Drop { .. } => {
+                self.set_drop_target(None);
+                self.drop_at(index)
+            }
+            MouseEvent::Button {
+                button: MouseButton::Left,
+                state: KeyState::Pressed,
+                ..
+            } => {
+                if self.selection_mode == SelectionMode::Multiple && self.is_selected(index) {
+                    self.toggle_select(index);
+                    self.selected_index = Some(index);
+                    self.dirty = true;
+                    true
+                } else {
+                    self.select(index)
+                }
+            }
+            MouseEvent::Scroll { delta_y, .. } if delta_y != 0.0 => {
+                let viewport = self.viewport_height as usize;
+                if delta_y < 0.0 {
+                    self.scroll.scroll_up(viewport);
+                } else if self.scroll.offset_y() + viewport < self.items.len() {
+                    self.scroll.scroll_down(viewport);
+                }
+                self.dirty = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Show where a drag would drop, returning whether a drag is over
+    fn set_drop_target(&mut self, target: Option<usize>) -> bool {
+        let Some(drag) = &mut self.drag else {
+            return false;
+        };
+        if drag.target != target {
+            drag.target = target;
+            self.dirty = true;
+        }
+        target.is_some()
+    }
+
+    /// Take a dropped item onto row `index`
+    fn drop_at(&mut self, index: usize) -> bool {
+        let Some(drag) = &mut self.drag else {
+            return false;
+        };
+        // Dropped back here: move the item instead of copying it
+        if let Some(from) = drag.from.take() {
+            drag.dnd.cancel();
+            // Dropping just before or after itself leaves it where it is
+            if index == from || index == from + 1 {
+                return true;
+            }
+            if let Some(item) = self.remove(from) {
+                // Rows below the item moved up when it was removed
+                let index = if from < index { index - 1 } else { index };
+                let index = index.min(self.items.len());
+                self.insert(index, item);
+                self.select(index);
+            }
+            return true;
+        }
+        match (drag.accept)(&drag.dnd) {
+            Some(item) => {
+                let index = index.min(self.items.len());
+                self.insert(index, item);
+                self.select(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Finish a drag that started here, removing the item if it was taken
+    fn end_drag(&mut self) -> bool {
+        let Some(drag) = &mut self.drag else {
+            return false;
+        };
+        let Some(from) = drag.from.take() else {
+            return false;
+        };
+        if drag.dnd.finish() {
+            self.remove(from);
+        }
+        true
+    }
+}
+
 impl<T: ToString + 'static> EventHandler for List<T> {
     fn handle_event(&mut self, event: &Event) -> bool {
+        if let EventKind::Mouse(mouse) = event.kind {
+            return self.handle_mouse(mouse);
+        }
         if !self.focused {
             return false;
         }
@@ -418,6 +686,7 @@ impl<T: ToString + 'static> Component for List<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{snapshot_theme, Snapshot};
 
     #[test]
     fn test_selection() {
@@ -486,4 +755,168 @@ mod tests {
         assert!(!list.select_prev());
         assert_eq!(list.selected(), None);
     }
+
+    #[test]
+    fn test_mouse_selects_and_scrolls() {
+        use crate::event::Modifiers;
+        let mouse = |mouse| Event::new(EventKind::Mouse(mouse));
+        let click = |row| {
+            mouse(MouseEvent::Button {
+                button: MouseButton::Left,
+                state: KeyState::Pressed,
+                col: 2,
+                row,
+                modifiers: Modifiers::none(),
+            })
+        };
+        let mut list = List::new(vec!["a", "b", "c", "d", "e"]);
+        Snapshot::render_with_theme(&mut list, 10, 3, &snapshot_theme()).unwrap();
+
+        // No focus needed, but the click has to land on a row
+        assert!(list.handle_event(&click(1)));
+        assert_eq!(list.selected(), Some(&"b"));
+        assert!(!list.handle_event(&click(3)));
+
+        let down = mouse(MouseEvent::Scroll {
+            delta_x: 0.0,
+            delta_y: 1.0,
+            col: 0,
+            row: 0,
+            modifiers: Modifiers::none(),
+        });
+        for _ in 0..5 {
+            assert!(list.handle_event(&down));
+        }
+        assert_eq!(list.scroll_offset(), 2);
+    }
+
+    /// Render `lists` side by side, 10 columns each, and drag with the left
+    /// button from one cell to another
+    ///
+    /// Returns each list's drop target just before the button is released.
+    fn drag(lists: &mut [List<&'static str>], from: (u16, u16), to: (u16, u16)) -> Vec<Option<usize>> {
+        use crate::drag::DragTracker;
+        use crate::event::Modifiers;
+        use crate::slots::Slots;
+        use crate::testing::TestRenderer;
+
+        // Side by side, the way a parent container would lay them out
+        let theme = snapshot_theme();
+        let slots = Slots::new();
+        let ctx = RenderContext::new(&theme, &slots);
+        let mut renderer = TestRenderer::new(lists.len() as u16 * 10, 4);
+        let mut hits = HitMap::new();
+        for (i, list) in lists.iter_mut().enumerate() {
+            let bounds = Rect::new(i as u16 * 10, 0, 10, 4);
+            hits.record(i, bounds);
+            list.render(&mut renderer, bounds, &ctx).unwrap();
+        }
+
+        let button = |state, (col, row)| MouseEvent::Button {
+            button: MouseButton::Left,
+            state,
+            col,
+            row,
+            modifiers: Modifiers::none(),
+        };
+        let gesture = [
+            button(KeyState::Pressed, from),
+            // Past the drag threshold even when dropping back on the same cell
+            MouseEvent::Moved { col: from.0 + 1, row: from.1 },
+            MouseEvent::Moved { col: to.0, row: to.1 },
+            button(KeyState::Released, to),
+        ];
+
+        let mut drags = DragTracker::new();
+        let mut targets = Vec::new();
+        for mouse in gesture {
+            if matches!(mouse, MouseEvent::Button { state: KeyState::Released, .. }) {
+                targets = lists.iter().map(List::drop_target).collect();
+            }
+            for event in drags.process(&Event::new(EventKind::Mouse(mouse))) {
+                hits.dispatch(&event, |&i, event| lists[i].handle_event(event));
+            }
+        }
+        targets
+    }
+
+    #[test]
+    fn test_drag_item_between_lists() {
+        let dnd = DragAndDrop::new();
+        let mut lists = [
+            List::new(vec!["a", "b", "c"]).with_drag_and_drop(dnd.clone()),
+            List::new(vec!["x"]).with_drag_and_drop(dnd.clone()),
+        ];
+
+        // Hovering the second list marks where the item would land
+        let targets = drag(&mut lists, (2, 1), (12, 1));
+        assert_eq!(targets[1], Some(1));
+
+        assert_eq!(lists[0].items(), ["a", "c"]);
+        assert_eq!(lists[1].items(), ["x", "b"]);
+        assert_eq!(lists[1].selected(), Some(&"b"));
+        assert_eq!(lists[1].drop_target(), None);
+        assert!(!dnd.is_active());
+    }
+
+    #[test]
+    fn test_drag_item_within_list() {
+        let dnd = DragAndDrop::new();
+        let mut lists = [List::new(vec!["a", "b", "c"]).with_drag_and_drop(dnd.clone())];
+
+        // Down: lands before the row it was dropped on
+        drag(&mut lists, (2, 0), (2, 2));
+        assert_eq!(lists[0].items(), ["b", "a", "c"]);
+        assert_eq!(lists[0].selected(), Some(&"a"));
+
+        // Down past the last item
+        drag(&mut lists, (2, 0), (2, 3));
+        assert_eq!(lists[0].items(), ["a", "c", "b"]);
+        assert_eq!(lists[0].selected(), Some(&"b"));
+
+        // Up
+        drag(&mut lists, (2, 2), (2, 0));
+        assert_eq!(lists[0].items(), ["b", "a", "c"]);
+        assert_eq!(lists[0].selected(), Some(&"b"));
+
+        // Onto itself
+        drag(&mut lists, (2, 1), (2, 1));
+        assert_eq!(lists[0].items(), ["b", "a", "c"]);
+        assert_eq!(lists[0].selected(), Some(&"a"));
+        assert!(!dnd.is_active());
+    }
+
+    #[test]
+    fn test_snapshot_cursor_and_truncation() {
+        let mut list = List::new(vec!["alpha", "a rather long entry name", "gamma"]);
+        list.select(1);
+        let snapshot = Snapshot::render_with_theme(&mut list, 12, 4, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("list_cursor_and_truncation", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_wide_text() {
+        let mut list = List::new(vec!["夜に駆ける", "🎵 Lo-fi mix", "Café"]);
+        list.select(0);
+        let snapshot = Snapshot::render_with_theme(&mut list, 9, 3, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("list_wide_text", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_focused_multiple_selection() {
+        let mut list =
+            List::new(vec!["one", "two", "three", "four"]).with_selection_mode(SelectionMode::Multiple);
+        list.set_focused(true);
+        list.select(0);
+        list.toggle_select(2);
+        let snapshot = Snapshot::render_with_theme(&mut list, 10, 3, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("list_focused_multiple_selection", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_empty() {
+        let mut list: List<String> = List::empty();
+        let snapshot = Snapshot::render_with_theme(&mut list, 10, 2, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("list_empty", snapshot);
+    }
 }
@@ -8,6 +8,7 @@ use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::style::Style;
 use crate::theme::Color;
+use crate::unicode;
 use anyhow::Result;
 
 /// Logo component - displays text with white background and black text
@@ -40,7 +41,7 @@ impl Component for Logo {
 
         // Content with padding: " TEXT "
         let padded = format!(" {} ", self.text);
-        let content_len = padded.len() as u16;
+        let content_len = unicode::width(&padded) as u16;
 
         // Right-align within bounds
         let x = bounds
@@ -55,7 +56,7 @@ impl Component for Logo {
 
     fn min_size(&self) -> (u16, u16) {
         // Text + 2 chars padding
-        ((self.text.len() + 2) as u16, 1)
+        ((unicode::width(&self.text) + 2) as u16, 1)
     }
 
 
@@ -69,7 +70,7 @@ impl SlotContent for Logo {
     fn responsive_sizes(&self) -> Vec<crate::components::slotted_bar::SlotSize> {
         use crate::components::slotted_bar::SlotSize;
         // Fixed size - text + padding
-        vec![SlotSize::Blocks((self.text.len() + 2) as u16)]
+        vec![SlotSize::Blocks((unicode::width(&self.text) + 2) as u16)]
     }
 
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -3,13 +3,14 @@
 //! A panel renders a header bar with a title, then delegates the remaining
 //! space to a content component. Useful for panes, sidebars, and overlays.
 
-use crate::component::Component;
+use crate::component::{render_clipped, Component};
 use crate::context::RenderContext;
 use crate::event::{Event, EventHandler};
 use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::style::Style;
 use crate::theme::Color;
+use crate::unicode;
 use anyhow::Result;
 
 /// A titled container that renders a header and delegates content
@@ -82,12 +83,7 @@ impl Component for Panel {
             self.header_style.bg.unwrap_or(Color::black()))?;
         renderer.move_cursor(bounds.x, bounds.y)?;
 
-        let max_title = bounds.width as usize;
-        let display_title = if self.title.len() > max_title {
-            &self.title[..max_title]
-        } else {
-            &self.title
-        };
+        let display_title = unicode::truncate(&self.title, bounds.width as usize);
         renderer.write_styled(display_title, &self.header_style)?;
 
         // Content area
@@ -99,7 +95,7 @@ impl Component for Panel {
         );
 
         if content_bounds.height > 0 {
-            self.content.render(renderer, content_bounds, ctx)?;
+            render_clipped(self.content.as_mut(), renderer, content_bounds, ctx)?;
         }
 
         // Optional border
@@ -5,16 +5,19 @@
 //! - Border/chrome styling
 //! - Focus trapping
 //! - ESC to close
+//! - Mouse events inside the frame routed to the content
 
 mod confirm;
 
 pub use confirm::ConfirmPopup;
 
-use crate::component::Component;
+use crate::component::{render_clipped, Component};
 use crate::context::RenderContext;
 use crate::event::{Event, EventHandler, Key};
+use crate::hit::HitMap;
 use crate::layout::Rect;
 use crate::render::Renderer;
+use crate::unicode;
 use anyhow::Result;
 
 /// Popup position on screen
@@ -116,6 +119,18 @@ pub struct Popup {
     close_on_escape: bool,
     trap_focus: bool,
     result: PopupResult,
+    layer: u16,
+    /// Frame left on the overlay by the last render, if any
+    drawn: Option<Rect>,
+    /// Frame and content bounds from the last render
+    hits: HitMap<Region>,
+}
+
+/// Parts of a popup that take mouse events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    Frame,
+    Content,
 }
 
 impl std::fmt::Debug for Popup {
@@ -132,6 +147,9 @@ impl std::fmt::Debug for Popup {
 }
 
 impl Popup {
+    /// Overlay layer popups draw on unless `with_layer` picks another
+    pub const DEFAULT_LAYER: u16 = 10;
+
     /// Creates a new popup wrapping the given content component
     pub fn new(content: Box<dyn Component>) -> Self {
         Self {
@@ -144,6 +162,9 @@ impl Popup {
             close_on_escape: true,
             trap_focus: true,
             result: PopupResult::Open,
+            layer: Self::DEFAULT_LAYER,
+            drawn: None,
+            hits: HitMap::new(),
         }
     }
 
@@ -188,6 +209,15 @@ impl Popup {
         self
     }
 
+    /// Draws the popup on overlay layer `z` (see `Renderer::push_layer`)
+    ///
+    /// Popups that can be open at the same time need distinct layers;
+    /// the higher layer is drawn on top.
+    pub fn with_layer(mut self, z: u16) -> Self {
+        self.layer = z;
+        self
+    }
+
     /// Makes the popup visible and resets the result to Open
     pub fn show(&mut self) {
         self.visible = true;
@@ -309,6 +339,19 @@ impl EventHandler for Popup {
             return true;
         }
 
+        // The popup covers whatever is beneath it, so mouse events inside
+        // the frame stop here even when the content ignores them
+        let content = &mut self.content;
+        let routed = self.hits.dispatch(event, |region, event| {
+            if *region == Region::Content {
+                content.handle_event(event);
+            }
+            true
+        });
+        if let Some(inside) = routed {
+            return inside || self.trap_focus;
+        }
+
         if self.content.handle_event(event) {
             return true;
         }
@@ -317,14 +360,14 @@ impl EventHandler for Popup {
     }
 }
 
-impl Component for Popup {
-    fn render(&mut self, renderer: &mut dyn Renderer, bounds: Rect, ctx: &RenderContext) -> Result<()> {
-        if !self.visible {
-            return Ok(());
-        }
-
+impl Popup {
+    /// Draw the border and content on the active layer
+    fn draw(&mut self, renderer: &mut dyn Renderer, bounds: Rect, ctx: &RenderContext) -> Result<()> {
         let popup_bounds = self.calculate_bounds(bounds);
         let content_bounds = self.content_bounds(popup_bounds);
+        self.hits.clear();
+        self.hits.record(Region::Frame, popup_bounds);
+        self.hits.record(Region::Content, content_bounds);
 
         if let Some(chars) = self.border_style.chars() {
             renderer.move_cursor(popup_bounds.x, popup_bounds.y)?;
@@ -332,15 +375,13 @@ impl Component for Popup {
 
             if let Some(title) = &self.title {
                 let title_space = (popup_bounds.width as usize).saturating_sub(4);
-                let display_title = if title.len() > title_space {
-                    format!(" {}... ", &title[..title_space.saturating_sub(3)])
-                } else {
-                    format!(" {} ", title)
-                };
+                let display_title =
+                    format!(" {} ", unicode::truncate_with_ellipsis(title, title_space));
+                let title_width = unicode::width(&display_title);
 
-                let padding_left = (popup_bounds.width as usize - display_title.len() - 2) / 2;
-                let padding_right =
-                    popup_bounds.width as usize - display_title.len() - 2 - padding_left;
+                let inner_width = (popup_bounds.width as usize).saturating_sub(2);
+                let padding_left = inner_width.saturating_sub(title_width) / 2;
+                let padding_right = inner_width.saturating_sub(title_width + padding_left);
 
                 for _ in 0..padding_left {
                     renderer.write_text(&chars.horizontal.to_string())?;
@@ -373,12 +414,40 @@ impl Component for Popup {
                 renderer.write_text(&chars.horizontal.to_string())?;
             }
             renderer.write_text(&chars.bottom_right.to_string())?;
+        } else {
+            // Blank the frame, so nothing from the last frame survives on the layer
+            for y in 0..popup_bounds.height {
+                renderer.move_cursor(popup_bounds.x, popup_bounds.y + y)?;
+                renderer.write_repeated(' ', popup_bounds.width as usize)?;
+            }
         }
 
-        self.content.render(renderer, content_bounds, ctx)?;
+        render_clipped(self.content.as_mut(), renderer, content_bounds, ctx)?;
 
         Ok(())
     }
+}
+
+impl Component for Popup {
+    fn render(&mut self, renderer: &mut dyn Renderer, bounds: Rect, ctx: &RenderContext) -> Result<()> {
+        // The overlay is kept between frames and erased only when the popup
+        // closes or moves, so a closed popup reveals what's beneath
+        if !self.visible {
+            if self.drawn.take().is_some() {
+                renderer.clear_layer(self.layer);
+            }
+            return Ok(());
+        }
+        let frame = self.calculate_bounds(bounds);
+        if self.drawn.replace(frame) != Some(frame) {
+            renderer.clear_layer(self.layer);
+        }
+
+        renderer.push_layer(self.layer);
+        let result = self.draw(renderer, bounds, ctx);
+        renderer.pop_layer();
+        result
+    }
 
     fn min_size(&self) -> (u16, u16) {
         if let Some((w, h)) = self.size {
@@ -418,12 +487,7 @@ impl Component for MessageContent {
 
         for (i, line) in lines.iter().enumerate().take(bounds.height as usize) {
             renderer.move_cursor(bounds.x, bounds.y + i as u16)?;
-            let display = if line.len() > bounds.width as usize {
-                &line[..bounds.width as usize]
-            } else {
-                line
-            };
-            renderer.write_text(display)?;
+            renderer.write_text(unicode::truncate(line, bounds.width as usize))?;
         }
 
         Ok(())
@@ -431,7 +495,7 @@ impl Component for MessageContent {
 
     fn min_size(&self) -> (u16, u16) {
         let lines: Vec<&str> = self.text.lines().collect();
-        let max_width = lines.iter().map(|l| l.len()).max().unwrap_or(10) as u16;
+        let max_width = lines.iter().map(|l| unicode::width(l)).max().unwrap_or(10) as u16;
         let height = lines.len().max(1) as u16;
         (max_width, height)
     }
@@ -444,6 +508,7 @@ impl Component for MessageContent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{snapshot_theme, Snapshot};
 
     struct TestContent;
 
@@ -535,4 +600,79 @@ mod tests {
 
         assert_eq!(popup.title(), Some("Warning"));
     }
+
+    #[test]
+    fn test_snapshot_message_with_title() {
+        let mut popup = Popup::message("Hello\nworld").with_title("Note");
+        popup.show();
+        let snapshot = Snapshot::render_with_theme(&mut popup, 20, 6, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("popup_message_with_title", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_long_title_truncated() {
+        let mut popup = Popup::message("body")
+            .with_title("A very long title that cannot fit")
+            .with_size(16, 4);
+        popup.show();
+        let snapshot = Snapshot::render_with_theme(&mut popup, 20, 6, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("popup_long_title_truncated", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_clamped_to_surface() {
+        let mut popup = Popup::message("this line is much too wide\ntwo\nthree\nfour")
+            .with_border(PopupBorderStyle::Double)
+            .with_position(PopupPosition::Top);
+        popup.show();
+        let snapshot = Snapshot::render_with_theme(&mut popup, 12, 5, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("popup_clamped_to_surface", snapshot);
+    }
+
+    #[test]
+    fn test_closing_restores_underlying_content() {
+        use crate::testing::TestRenderer;
+        use crate::theme::Theme;
+
+        let theme = Theme::new();
+        let slots = crate::slots::Slots::new();
+        let ctx = RenderContext::new(&theme, &slots);
+        let bounds = Rect::new(0, 0, 20, 6);
+        let mut renderer = TestRenderer::new(20, 6);
+        renderer.move_cursor(0, 2).unwrap();
+        renderer.write_text("background text here").unwrap();
+
+        let mut popup = Popup::message("hi").with_size(8, 3);
+        popup.show();
+        popup.render(&mut renderer, bounds, &ctx).unwrap();
+        assert_ne!(renderer.row_text(2), "background text here");
+
+        popup.close();
+        popup.render(&mut renderer, bounds, &ctx).unwrap();
+        assert_eq!(renderer.row_text(2), "background text here");
+    }
+
+    #[test]
+    fn test_open_popup_keeps_its_layer_between_frames() {
+        use crate::testing::TestRenderer;
+        use crate::theme::Theme;
+
+        let theme = Theme::new();
+        let slots = crate::slots::Slots::new();
+        let ctx = RenderContext::new(&theme, &slots);
+        let mut renderer = TestRenderer::new(20, 6);
+        let mut popup = Popup::message("hi").with_size(8, 3);
+        popup.show();
+        popup.render(&mut renderer, Rect::new(0, 0, 20, 6), &ctx).unwrap();
+        renderer.end_frame().unwrap();
+
+        // Clearing the layer would mark the area it covered dirty
+        popup.render(&mut renderer, Rect::new(0, 0, 20, 6), &ctx).unwrap();
+        assert!(renderer.dirty_region().rects().is_empty());
+
+        // A new frame position starts from an empty layer
+        popup.render(&mut renderer, Rect::new(0, 0, 20, 4), &ctx).unwrap();
+        assert_eq!(renderer.dirty_region().bounds(), Some(Rect::new(6, 1, 8, 3)));
+        assert_eq!(renderer.row_text(3), "");
+    }
 }
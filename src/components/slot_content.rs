@@ -8,6 +8,7 @@ use crate::event::EventHandler;
 use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::style::Style;
+use crate::unicode;
 use anyhow::Result;
 
 /// Text slot content with alignment and styling
@@ -66,19 +67,14 @@ impl EventHandler for TextSlot {}
 
 impl Component for TextSlot {
     fn render(&mut self, renderer: &mut dyn Renderer, bounds: Rect, ctx: &RenderContext) -> Result<()> {
-        let text_len = self.text.len() as u16;
-
         // Don't render if no space
         if bounds.width == 0 {
             return Ok(());
         }
 
         // Truncate if needed (no overflow)
-        let display_text = if text_len > bounds.width {
-            &self.text[..bounds.width as usize]
-        } else {
-            &self.text
-        };
+        let display_text = unicode::truncate(&self.text, bounds.width as usize);
+        let display_width = unicode::width(display_text) as u16;
 
         // Resolve logical alignment to physical based on text direction
         let text_direction = self.use_text_direction(ctx);
@@ -88,11 +84,11 @@ impl Component for TextSlot {
         let x = match physical_align {
             crate::components::text::PhysicalAlign::Left => bounds.x,
             crate::components::text::PhysicalAlign::Center => {
-                let offset = (bounds.width.saturating_sub(display_text.len() as u16)) / 2;
+                let offset = (bounds.width.saturating_sub(display_width)) / 2;
                 bounds.x.saturating_add(offset)
             }
             crate::components::text::PhysicalAlign::Right => {
-                let offset = bounds.width.saturating_sub(display_text.len() as u16);
+                let offset = bounds.width.saturating_sub(display_width);
                 bounds.x.saturating_add(offset)
             }
         };
@@ -109,7 +105,7 @@ impl Component for TextSlot {
     }
 
     fn min_size(&self) -> (u16, u16) {
-        (self.text.len() as u16, 1)
+        (unicode::width(&self.text) as u16, 1)
     }
 
 
@@ -128,7 +124,7 @@ impl SlotContent for TextSlot {
             vec![SlotSize::Blocks(fixed)]
         } else {
             // Flexible - can fill or shrink to text length
-            let text_len = self.text.len() as u16;
+            let text_len = unicode::width(&self.text) as u16;
             vec![SlotSize::Fill, SlotSize::Blocks(text_len)]
         }
     }
@@ -172,7 +168,7 @@ impl Badge {
 
     /// Get total width (text + padding on both sides)
     fn total_width(&self) -> u16 {
-        self.text.len() as u16 + (self.padding * 2)
+        unicode::width(&self.text) as u16 + (self.padding * 2)
     }
 }
 
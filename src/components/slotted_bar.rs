@@ -3,6 +3,7 @@
 use crate::component::Component;
 use crate::context::RenderContext;
 use crate::event::EventHandler;
+use crate::hit::HitMap;
 use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::style::Style;
@@ -84,6 +85,8 @@ impl Slot {
 pub struct SlottedBar {
     slots: Vec<Slot>,
     background_style: Style,
+    /// Slot bounds from the last render, by slot index
+    hits: HitMap,
 }
 
 impl SlottedBar {
@@ -92,6 +95,7 @@ impl SlottedBar {
         SlottedBar {
             slots: Vec::new(),
             background_style: Style::new().reverse(true),
+            hits: HitMap::new(),
         }
     }
 
@@ -138,7 +142,7 @@ impl SlottedBar {
             .collect();
 
         // Sort by priority (highest first)
-        slot_info.sort_by(|a, b| b.1.cmp(&a.1));
+        slot_info.sort_by_key(|s| std::cmp::Reverse(s.1));
 
         // Try to allocate, hiding slots if needed
         let mut visible_slots = slot_info.clone();
@@ -276,6 +280,14 @@ impl Default for SlottedBar {
 
 impl EventHandler for SlottedBar {
     fn handle_event(&mut self, event: &crate::event::Event) -> bool {
+        let slots = &mut self.slots;
+        let routed = self.hits.dispatch(event, |&i, event| {
+            slots.get_mut(i).is_some_and(|slot| slot.content.handle_event(event))
+        });
+        if let Some(consumed) = routed {
+            return consumed;
+        }
+
         for slot in &mut self.slots {
             if slot.content.handle_event(event) {
                 return true;
@@ -298,9 +310,11 @@ impl Component for SlottedBar {
 
         // Render each slot - components must respect their allocated bounds
         let mut x_offset = bounds.x;
+        self.hits.clear();
         for (idx, allocated_width) in widths {
             if allocated_width > 0 {
                 let slot_bounds = Rect::new(x_offset, bounds.y, allocated_width, bounds.height);
+                self.hits.record(idx, slot_bounds);
 
                 // Components receive their exact allocated width via bounds
                 // They must render within these bounds (no overflow)
@@ -323,6 +337,7 @@ impl Component for SlottedBar {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{snapshot_theme, Snapshot};
 
     struct TestSlotContent {
         width: Option<u16>,
@@ -376,4 +391,30 @@ mod tests {
         assert_eq!(widths[1].1, 20); // Medium priority gets its preferred
         assert_eq!(widths[2].1, 50); // Flexible gets remainder (80 - 10 - 20)
     }
+
+    fn status_bar() -> SlottedBar {
+        use crate::components::slot_content::{Badge, Spacer, TextSlot};
+        use crate::theme::Color;
+
+        let mut bar = SlottedBar::new().with_background(Style::new().bg(Color::rgb(40, 0, 60)));
+        bar.add(Box::new(Badge::new("NORMAL")), 100);
+        bar.add(Box::new(TextSlot::new("src/main.rs")), 50);
+        bar.add(Box::new(Spacer::new()), 0);
+        bar.add(Box::new(TextSlot::new("ln 12, col 4").with_style(Style::new().bold(true))), 10);
+        bar
+    }
+
+    #[test]
+    fn test_snapshot_all_slots_fit() {
+        let mut bar = status_bar();
+        let snapshot = Snapshot::render_with_theme(&mut bar, 40, 1, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("slotted_bar_all_slots_fit", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_low_priority_hidden() {
+        let mut bar = status_bar();
+        let snapshot = Snapshot::render_with_theme(&mut bar, 22, 1, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("slotted_bar_low_priority_hidden", snapshot);
+    }
 }
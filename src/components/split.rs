@@ -4,7 +4,9 @@
 //! - Horizontal and vertical splits
 //! - Resizable dividers
 //! - Active pane tracking
-//! - Ctrl-w navigation
+//! - Ctrl-w navigation in Normal mode through a remappable `Keymap` (see
+//!   `WINDOW_KEYMAP`)
+//! - Click to focus a pane; mouse events go to the pane under the pointer
 //!
 //! # Example
 //!
@@ -19,12 +21,31 @@
 //! split.focus_right(); // Ctrl-w l
 //! ```
 
-use crate::component::Component;
+use crate::component::{render_clipped, Component};
 use crate::context::RenderContext;
-use crate::event::{Event, EventHandler};
+use crate::event::{Event, EventHandler, EventKind, KeyState, MouseEvent};
+use crate::hit::HitMap;
+use crate::keymap::{KeyChord, Keymap, Resolution};
 use crate::layout::Rect;
+use crate::modal::Mode;
 use crate::render::Renderer;
 use anyhow::Result;
+use std::time::Instant;
+
+/// Default pane navigation bindings, in keymap config syntax
+///
+/// They apply in Normal mode only, so a pane in Insert mode still gets
+/// Ctrl-w (e.g. `TextInput`'s delete-word); see `SplitView::set_mode`.
+pub const WINDOW_KEYMAP: &str = "\
+[normal]
+bind <C-w>w window.next
+bind <C-w><C-w> window.next
+bind <C-w>W window.prev
+bind <C-w>h window.left
+bind <C-w>l window.right
+bind <C-w>k window.up
+bind <C-w>j window.down
+";
 
 /// Split direction
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -88,6 +109,14 @@ pub struct SplitView {
     dirty: bool,
     /// Divider character
     divider_char: char,
+    /// Bindings for the `window.*` actions
+    keymap: Keymap,
+    /// Pane bounds from the last render, by pane index
+    hits: HitMap,
+}
+
+fn window_keymap() -> Keymap {
+    Keymap::parse(WINDOW_KEYMAP).expect("built-in window keymap parses")
 }
 
 impl Default for SplitView {
@@ -99,6 +128,8 @@ impl Default for SplitView {
             divider_positions: Vec::new(),
             dirty: true,
             divider_char: '│',
+            keymap: window_keymap(),
+            hits: HitMap::new(),
         }
     }
 }
@@ -113,6 +144,8 @@ impl SplitView {
             divider_positions: Vec::new(),
             dirty: true,
             divider_char: '│',
+            keymap: window_keymap(),
+            hits: HitMap::new(),
         }
     }
 
@@ -131,6 +164,41 @@ impl SplitView {
         self
     }
 
+    /// Replace the pane navigation bindings
+    ///
+    /// The keymap's actions are `window.next`, `window.prev`, `window.left`,
+    /// `window.right`, `window.up`, and `window.down`.
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Pane navigation bindings, for remapping
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
+    /// Tell the keymap which modal mode the app is in
+    ///
+    /// Call it whenever the mode changes, e.g. from `ModalState::mode`.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.keymap.set_mode(mode);
+    }
+
+    /// Run a `window.*` action, returning false for unknown actions
+    pub fn run_action(&mut self, action: &str) -> bool {
+        match action {
+            "window.next" => self.focus_next(),
+            "window.prev" => self.focus_prev(),
+            "window.left" => self.focus_left(),
+            "window.right" => self.focus_right(),
+            "window.up" => self.focus_up(),
+            "window.down" => self.focus_down(),
+            _ => return false,
+        }
+        true
+    }
+
     /// Get current split direction
     pub fn direction(&self) -> SplitDirection {
         self.direction
@@ -168,6 +236,7 @@ impl SplitView {
         } else {
             let insert_pos = self.active_pane + 1;
             self.panes.insert(insert_pos, Pane::new(content));
+            self.hits.clear();
             self.recalculate_dividers();
             self.active_pane = insert_pos;
         }
@@ -185,6 +254,7 @@ impl SplitView {
         }
 
         let pane = self.panes.remove(index);
+        self.hits.clear();
 
         // Recalculate dividers
         self.recalculate_dividers();
@@ -361,9 +431,79 @@ impl SplitView {
     }
 }
 
-impl EventHandler for SplitView {
-    fn handle_event(&mut self, event: &Event) -> bool {
-        // First, try to handle in active pane
+impl SplitView {
+    /// Resolve a pending key sequence that has timed out
+    fn expire_keys(&mut self) -> bool {
+        let mut handled = false;
+        for resolution in self.keymap.expire(Instant::now()) {
+            handled |= match resolution {
+                Resolution::Action(action) => self.run_action(&action),
+                Resolution::Pending => true,
+                Resolution::Unmatched(keys) => self.replay_keys(&keys, None),
+            };
+        }
+        handled
+    }
+
+    fn route_event(&mut self, event: &Event) -> bool {
+        // Window bindings take precedence, so a pane can't swallow Ctrl-w.
+        // Keys reach the pane only when the keymap hands them back.
+        let resolutions = self.keymap.handle(&event.kind);
+        if resolutions.is_empty() {
+            return self.dispatch(event);
+        }
+        let mut handled = false;
+        for resolution in resolutions {
+            handled |= match resolution {
+                Resolution::Action(action) => self.run_action(&action) || self.dispatch(event),
+                Resolution::Pending => true,
+                Resolution::Unmatched(keys) => self.replay_keys(&keys, Some(event)),
+            };
+        }
+        handled
+    }
+
+    /// Send keys the keymap handed back to the panes, in order
+    ///
+    /// The keys end with `event` when it is the one that broke a sequence;
+    /// it is passed on as is, and the held keys are rebuilt from their chords.
+    fn replay_keys(&mut self, keys: &[KeyChord], event: Option<&Event>) -> bool {
+        let mut handled = false;
+        for (i, chord) in keys.iter().enumerate() {
+            let original = event.filter(|event| {
+                i + 1 == keys.len() && KeyChord::from_event(&event.kind) == Some(*chord)
+            });
+            handled |= match original {
+                Some(event) => self.dispatch(event),
+                None => self.dispatch(&Event::key_with_mods(chord.key, chord.modifiers)),
+            };
+        }
+        handled
+    }
+
+    /// Send an event to the pane under the pointer or the active pane
+    fn dispatch(&mut self, event: &Event) -> bool {
+        // A click focuses the pane under it before the pane sees the click
+        let mut clicked = false;
+        if let EventKind::Mouse(MouseEvent::Button {
+            state: KeyState::Pressed,
+            col,
+            row,
+            ..
+        }) = event.kind
+        {
+            if let Some(&index) = self.hits.hit(col, row) {
+                clicked = index != self.active_pane && self.focus_pane(index);
+            }
+        }
+        let panes = &mut self.panes;
+        let routed = self.hits.dispatch(event, |&i, event| {
+            panes.get_mut(i).is_some_and(|pane| pane.content.handle_event(event))
+        });
+        if let Some(consumed) = routed {
+            return consumed || clicked;
+        }
+
         if let Some(pane) = self.panes.get_mut(self.active_pane) {
             if pane.content.handle_event(event) {
                 return true;
@@ -374,6 +514,15 @@ impl EventHandler for SplitView {
     }
 }
 
+impl EventHandler for SplitView {
+    fn handle_event(&mut self, event: &Event) -> bool {
+        // Any event, including the idle `Redraw` of `RunOptions::with_tick`,
+        // lets a timed out key sequence fire
+        let expired = self.expire_keys();
+        self.route_event(event) || expired
+    }
+}
+
 impl Component for SplitView {
     fn render(&mut self, renderer: &mut dyn Renderer, bounds: Rect, ctx: &RenderContext) -> Result<()> {
         if self.panes.is_empty() {
@@ -384,8 +533,10 @@ impl Component for SplitView {
         let pane_count = self.panes.len();
 
         // Render each pane
+        self.hits.clear();
         for (i, (pane, pane_rect)) in self.panes.iter_mut().zip(pane_bounds.iter()).enumerate() {
-            pane.content.render(renderer, *pane_rect, ctx)?;
+            self.hits.record(i, *pane_rect);
+            render_clipped(pane.content.as_mut(), renderer, *pane_rect, ctx)?;
 
             // Draw divider after each pane (except last)
             if i < pane_count - 1 {
@@ -440,6 +591,7 @@ impl Component for SplitView {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{snapshot_theme, Snapshot};
 
     // Minimal test component
     struct TestPane {
@@ -529,4 +681,181 @@ mod tests {
         assert!(pane_bounds[0].width > 35);
         assert!(pane_bounds[1].width > 35);
     }
+
+    fn text_pane(text: &str) -> Box<dyn Component> {
+        Box::new(crate::components::Text::new(text))
+    }
+
+    #[test]
+    fn test_snapshot_horizontal_three_panes() {
+        let mut split = SplitView::new(text_pane("left"));
+        split.split_horizontal(text_pane("middle"));
+        split.split_horizontal(text_pane("right"));
+        let snapshot = Snapshot::render_with_theme(&mut split, 23, 3, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("split_horizontal_three_panes", snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_vertical_resized() {
+        let mut split = SplitView::new(text_pane("top")).with_direction(SplitDirection::Vertical);
+        split.split_vertical(text_pane("bottom"));
+        split.resize_divider(0, 0.25);
+        let snapshot = Snapshot::render_with_theme(&mut split, 10, 8, &snapshot_theme()).unwrap();
+        crate::assert_snapshot!("split_vertical_resized", snapshot);
+    }
+
+    struct WidePane;
+
+    impl EventHandler for WidePane {}
+
+    impl Component for WidePane {
+        fn render(
+            &mut self,
+            renderer: &mut dyn Renderer,
+            bounds: Rect,
+            _ctx: &RenderContext,
+        ) -> Result<()> {
+            renderer.move_cursor(bounds.x, bounds.y)?;
+            renderer.write_text("xxxxxxxxxxxxxxxxxxxx")
+        }
+    }
+
+    #[test]
+    fn test_panes_are_clipped() {
+        let mut split = SplitView::new(Box::new(WidePane));
+        split.split_horizontal(text_pane("right"));
+        let snapshot = Snapshot::render_with_theme(&mut split, 21, 1, &snapshot_theme()).unwrap();
+        let (_, right) = snapshot.as_str().split_once('│').unwrap();
+        assert!(right.starts_with("right") && !right.contains('x'), "{}", snapshot.as_str());
+    }
+
+    #[test]
+    fn test_click_focuses_pane_under_pointer() {
+        use crate::event::{Modifiers, MouseButton};
+        let mut split = SplitView::new(make_pane("left"));
+        split.split_horizontal(make_pane("right"));
+        split.focus_pane(0);
+        Snapshot::render_with_theme(&mut split, 21, 1, &snapshot_theme()).unwrap();
+
+        let click = Event::new(EventKind::Mouse(MouseEvent::Button {
+            button: MouseButton::Left,
+            state: KeyState::Pressed,
+            col: 15,
+            row: 0,
+            modifiers: Modifiers::none(),
+        }));
+        assert!(split.handle_event(&click));
+        assert_eq!(split.active_pane(), 1);
+        assert_eq!(split.hits.hovered(), None);
+
+        let moved = Event::new(EventKind::Mouse(MouseEvent::Moved { col: 2, row: 0 }));
+        split.handle_event(&moved);
+        assert_eq!(split.hits.hovered(), Some(&0));
+        assert_eq!(split.active_pane(), 1);
+    }
+
+    #[test]
+    fn test_pending_keys_fire_on_timeout() {
+        use crate::event::{Key, Modifiers};
+        use std::time::Duration;
+        let keymap = Keymap::parse("bind <C-w> window.next\nbind <C-w>p window.prev")
+            .unwrap()
+            .with_timeout(Duration::ZERO);
+        let mut split = SplitView::new(make_pane("left")).with_keymap(keymap);
+        split.split_horizontal(make_pane("right"));
+        split.focus_pane(0);
+
+        let ctrl_w = Event::key_with_mods(Key::Char('w'), Modifiers { ctrl: true, ..Modifiers::none() });
+        assert!(split.handle_event(&ctrl_w));
+        assert_eq!(split.keymap.pending_display(), "<C-w>");
+        assert_eq!(split.active_pane(), 0);
+
+        // An idle tick (`RunOptions::with_tick`) lets the shorter binding fire
+        assert!(split.handle_event(&Event::new(EventKind::Redraw)));
+        assert_eq!(split.active_pane(), 1);
+        assert!(split.keymap.pending().is_empty());
+    }
+
+    #[test]
+    fn test_ctrl_w_bindings_and_remap() {
+        use crate::event::{Key, Modifiers};
+        let ctrl = |c| {
+            Event::key_with_mods(Key::Char(c), Modifiers { ctrl: true, ..Modifiers::none() })
+        };
+        let mut split = SplitView::new(make_pane("left"));
+        split.split_horizontal(make_pane("right"));
+        split.focus_pane(0);
+
+        assert!(split.handle_event(&ctrl('w')));
+        assert!(split.handle_event(&Event::key(Key::Char('l'))));
+        assert_eq!(split.active_pane(), 1);
+
+        let keymap = Keymap::parse("bind <C-h> window.left").unwrap();
+        let mut split = split.with_keymap(keymap);
+        assert!(!split.handle_event(&ctrl('w')));
+        assert!(split.handle_event(&ctrl('h')));
+        assert_eq!(split.active_pane(), 0);
+    }
+
+    /// Pane that logs the keys it is offered
+    struct KeyLog(std::rc::Rc<std::cell::RefCell<Vec<KeyChord>>>);
+
+    impl EventHandler for KeyLog {
+        fn handle_event(&mut self, event: &Event) -> bool {
+            self.0.borrow_mut().extend(KeyChord::from_event(&event.kind));
+            true
+        }
+    }
+
+    impl Component for KeyLog {
+        fn render(
+            &mut self,
+            _renderer: &mut dyn Renderer,
+            _bounds: Rect,
+            _ctx: &RenderContext,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_unmatched_keys_reach_pane_in_order() {
+        use crate::event::{Key, Modifiers};
+        use std::time::Duration;
+        let ctrl_w = KeyChord::new(Key::Char('w'), Modifiers { ctrl: true, ..Modifiers::none() });
+        let log = std::rc::Rc::default();
+        let keymap = Keymap::parse("bind <C-w>p window.prev")
+            .unwrap()
+            .with_timeout(Duration::ZERO);
+        let mut split = SplitView::new(Box::new(KeyLog(std::rc::Rc::clone(&log)))).with_keymap(keymap);
+
+        // The held prefix is replayed ahead of the key that broke the sequence
+        let key = |chord: KeyChord| Event::key_with_mods(chord.key, chord.modifiers);
+        assert!(split.handle_event(&key(ctrl_w)));
+        assert!(log.borrow().is_empty());
+        assert!(split.handle_event(&Event::key(Key::Char('x'))));
+        assert_eq!(*log.borrow(), [ctrl_w, KeyChord::new(Key::Char('x'), Modifiers::none())]);
+
+        // A prefix that times out is handed to the pane too
+        log.borrow_mut().clear();
+        split.handle_event(&key(ctrl_w));
+        assert!(split.handle_event(&Event::new(EventKind::Redraw)));
+        assert_eq!(*log.borrow(), [ctrl_w]);
+    }
+
+    #[test]
+    fn test_ctrl_w_deletes_word_in_insert_mode() {
+        use crate::components::TextInput;
+        use crate::event::{Key, Modifiers};
+        let mut input = TextInput::new("");
+        input.on_focus();
+        input.set_value("hello world");
+        let mut split = SplitView::new(Box::new(input));
+        let ctrl_w = Event::key_with_mods(Key::Char('w'), Modifiers { ctrl: true, ..Modifiers::none() });
+
+        split.set_mode(Mode::Insert);
+        assert!(split.handle_event(&ctrl_w));
+        let snapshot = Snapshot::render_with_theme(&mut split, 20, 1, &snapshot_theme()).unwrap();
+        assert!(snapshot.as_str().contains("|hello               |"), "{}", snapshot.as_str());
+    }
 }
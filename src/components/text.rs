@@ -9,6 +9,7 @@ use crate::render::Renderer;
 use crate::signal::Signal;
 use crate::signal::SignalBase;
 use crate::style::Style;
+use crate::unicode;
 use anyhow::Result;
 
 /// Text alignment - supports both logical and physical alignment
@@ -107,7 +108,7 @@ impl Component for Text {
         let text_direction = self.use_text_direction(ctx);
         let physical_align = self.align.resolve(text_direction);
 
-        let text_len = content.len() as u16;
+        let text_len = unicode::width(content) as u16;
         let x = match physical_align {
             PhysicalAlign::Left => bounds.x,
             PhysicalAlign::Center => {
@@ -133,7 +134,7 @@ impl Component for Text {
     }
 
     fn min_size(&self) -> (u16, u16) {
-        (self.content.get().len() as u16, 1)
+        (unicode::width(self.content.get()) as u16, 1)
     }
 
     fn generation(&self) -> u64 {
@@ -13,6 +13,7 @@ use crate::event::{Event, EventHandler, EventKind, Key};
 use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::style::Style;
+use crate::unicode;
 use anyhow::Result;
 
 /// Text input submission callback type
@@ -116,52 +117,37 @@ impl TextInput {
         self.cursor += c.len_utf8();
     }
 
-    /// Delete character before cursor (backspace)
+    /// Delete grapheme before cursor (backspace)
     fn delete_char_before(&mut self) {
         if self.cursor > 0 {
-            // Find the previous character boundary
-            let prev_boundary = self.buffer[..self.cursor]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
-
-            self.buffer.remove(prev_boundary);
+            let prev_boundary = unicode::prev_boundary(&self.buffer, self.cursor);
+            self.buffer.drain(prev_boundary..self.cursor);
             self.cursor = prev_boundary;
             self.dirty = true;
         }
     }
 
-    /// Delete character at cursor (delete key)
+    /// Delete grapheme at cursor (delete key)
     fn delete_char_at(&mut self) {
         if self.cursor < self.buffer.len() {
-            self.buffer.remove(self.cursor);
+            let next_boundary = unicode::next_boundary(&self.buffer, self.cursor);
+            self.buffer.drain(self.cursor..next_boundary);
             self.dirty = true;
         }
     }
 
-    /// Move cursor left
+    /// Move cursor left by one grapheme
     fn move_left(&mut self) {
         if self.cursor > 0 {
-            // Find previous character boundary
-            self.cursor = self.buffer[..self.cursor]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
+            self.cursor = unicode::prev_boundary(&self.buffer, self.cursor);
             self.dirty = true;
         }
     }
 
-    /// Move cursor right
+    /// Move cursor right by one grapheme
     fn move_right(&mut self) {
         if self.cursor < self.buffer.len() {
-            // Find next character boundary
-            self.cursor = self.buffer[self.cursor..]
-                .char_indices()
-                .nth(1)
-                .map(|(i, _)| self.cursor + i)
-                .unwrap_or(self.buffer.len());
+            self.cursor = unicode::next_boundary(&self.buffer, self.cursor);
             self.dirty = true;
         }
     }
@@ -394,53 +380,39 @@ impl Component for TextInput {
         }
 
         // Calculate available width for input
-        let prompt_len = self.prompt.chars().count() as u16;
-        let available_width = bounds.width.saturating_sub(prompt_len);
+        let prompt_width = unicode::width(&self.prompt) as u16;
+        let available_width = bounds.width.saturating_sub(prompt_width) as usize;
 
         if available_width == 0 {
             self.dirty = false;
             return Ok(());
         }
 
-        // Calculate visible portion of buffer (scroll if needed)
-        let cursor_char_pos = self.buffer[..self.cursor].chars().count();
-        let _buffer_char_len = self.buffer.chars().count();
-
-        // Determine scroll offset to keep cursor visible
-        let scroll_offset = if cursor_char_pos >= available_width as usize {
-            cursor_char_pos - (available_width as usize - 1)
-        } else {
-            0
-        };
-
-        // Get visible text
-        let visible_chars: String = self
-            .buffer
-            .chars()
-            .skip(scroll_offset)
-            .take(available_width as usize)
-            .collect();
-
-        let visible_cursor_pos = cursor_char_pos - scroll_offset;
+        // Scroll so the cursor (and the grapheme under it) stays visible
+        let cursor_col = unicode::width(&self.buffer[..self.cursor]);
+        let cursor_width = unicode::graphemes(&self.buffer[self.cursor..])
+            .next()
+            .map_or(1, unicode::grapheme_width);
+        let scroll_offset = (cursor_col + cursor_width).saturating_sub(available_width);
+
+        let scrolled = unicode::skip_width(&self.buffer, scroll_offset);
+        let visible = unicode::truncate(scrolled, available_width);
+        let visible_cursor = self
+            .cursor
+            .saturating_sub(self.buffer.len() - scrolled.len())
+            .min(visible.len());
+        let (before, under_cursor) = visible.split_at(visible_cursor);
 
         // Render text with cursor
-        if self.focused && visible_cursor_pos < visible_chars.chars().count() {
-            let before: String = visible_chars.chars().take(visible_cursor_pos).collect();
-            let cursor_char: String = visible_chars
-                .chars()
-                .nth(visible_cursor_pos)
-                .map(|c| c.to_string())
-                .unwrap_or_else(|| " ".to_string());
-            let after: String = visible_chars.chars().skip(visible_cursor_pos + 1).collect();
-
-            self.write_input_text(renderer, &before)?;
-            renderer.write_styled(&cursor_char, &self.cursor_style)?;
-            self.write_input_text(renderer, &after)?;
-        } else if self.focused {
-            self.write_input_text(renderer, &visible_chars)?;
-            renderer.write_styled(" ", &self.cursor_style)?;
+        if !self.focused {
+            self.write_input_text(renderer, visible)?;
+        } else if let Some(cursor_grapheme) = unicode::graphemes(under_cursor).next() {
+            self.write_input_text(renderer, before)?;
+            renderer.write_styled(cursor_grapheme, &self.cursor_style)?;
+            self.write_input_text(renderer, &under_cursor[cursor_grapheme.len()..])?;
         } else {
-            self.write_input_text(renderer, &visible_chars)?;
+            self.write_input_text(renderer, visible)?;
+            renderer.write_styled(" ", &self.cursor_style)?;
         }
 
         Ok(())
@@ -448,8 +420,8 @@ impl Component for TextInput {
 
     fn min_size(&self) -> (u16, u16) {
         // Minimum: prompt + at least some space for input
-        let prompt_len = self.prompt.chars().count() as u16;
-        (prompt_len + 10, 1)
+        let prompt_width = unicode::width(&self.prompt) as u16;
+        (prompt_width + 10, 1)
     }
 
 
@@ -516,6 +488,41 @@ mod tests {
         assert_eq!(input.value(), "ell");
     }
 
+    #[test]
+    fn test_grapheme_editing() {
+        let mut input = TextInput::new("");
+        input.set_value("日e\u{301}");
+
+        input.move_left();
+        assert_eq!(input.cursor_position(), 3);
+        input.move_left();
+        assert_eq!(input.cursor_position(), 0);
+
+        input.move_to_end();
+        input.delete_char_before();
+        assert_eq!(input.value(), "日");
+    }
+
+    #[test]
+    fn test_render_scrolls_by_width() {
+        use crate::testing::TestRenderer;
+        use crate::theme::Theme;
+
+        let mut input = TextInput::new("> ");
+        input.focused = true;
+        input.set_value("日本語です");
+
+        let theme = Theme::new();
+        let slots = crate::slots::Slots::new();
+        let ctx = RenderContext::new(&theme, &slots);
+        let mut renderer = TestRenderer::new(9, 1);
+        input.render(&mut renderer, Rect::new(0, 0, 9, 1), &ctx).unwrap();
+
+        // 7 columns for input: the cursor cell plus the last three ideographs
+        assert_eq!(renderer.row_text(0), "> 語です");
+        assert_eq!(renderer.cell(8, 0).map(|c| c.style), Some(input.cursor_style));
+    }
+
     #[test]
     fn test_word_navigation() {
         let mut input = TextInput::new("");
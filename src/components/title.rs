@@ -8,6 +8,7 @@ use crate::event::EventHandler;
 use crate::layout::Rect;
 use crate::render::Renderer;
 use crate::theme::Theme;
+use crate::unicode;
 use anyhow::Result;
 
 /// Title component - displays centered text with header styling.
@@ -60,7 +61,7 @@ impl SlotContent for Title {
     fn responsive_sizes(&self) -> Vec<crate::components::slotted_bar::SlotSize> {
         use crate::components::slotted_bar::SlotSize;
 
-        let text_len = self.inner.text().len() as u16;
+        let text_len = unicode::width(self.inner.text()) as u16;
 
         vec![
             SlotSize::Fill,
@@ -0,0 +1,302 @@
+//! Drag gestures and in-app drag and drop
+//!
+//! `DragTracker` watches press, move, and release and adds drag events to
+//! the stream once a held button has moved far enough:
+//!
+//! ```text
+//! press → move → ... (threshold) → DragStart, move, DragMove → ... → Drop, DragEnd, release
+//! ```
+//!
+//! It only needs plain button and move events, so it behaves the same on
+//! terminals (which report motion with a button held once mouse capture is
+//! on) and in the GUI. The `App` run loops feed every event through one.
+//!
+//! `DragAndDrop` carries a payload from the component where a drag started
+//! to the one it is dropped on. Hand a clone to each component taking part:
+//!
+//! - The source calls `start` on `DragStart` and `finish` on `DragEnd`,
+//!   which says whether a target took the payload
+//! - Targets check `payload` while hovered to highlight themselves, and
+//!   call `accept` on `Drop`
+
+use crate::event::{Event, EventKind, KeyState, MouseButton, MouseEvent};
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// Cells a held button has to move before a press becomes a drag
+pub const DEFAULT_DRAG_THRESHOLD: u16 = 1;
+
+/// Turns press → move → release into drag events
+#[derive(Debug, Clone)]
+pub struct DragTracker {
+    threshold: u16,
+    /// Button held and where it was pressed
+    pressed: Option<(MouseButton, u16, u16)>,
+    /// Whether the held button has passed the threshold
+    dragging: bool,
+}
+
+impl Default for DragTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DragTracker {
+    /// Create a tracker with `DEFAULT_DRAG_THRESHOLD`
+    pub fn new() -> Self {
+        DragTracker {
+            threshold: DEFAULT_DRAG_THRESHOLD,
+            pressed: None,
+            dragging: false,
+        }
+    }
+
+    /// Set how many cells, in either direction, start a drag
+    pub fn with_threshold(mut self, cells: u16) -> Self {
+        self.threshold = cells;
+        self
+    }
+
+    /// Cells a held button has to move before a drag starts
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// Whether a drag is in progress
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Events to deliver for `event`, in order
+    ///
+    /// The event itself is always included. A drag starts just before the
+    /// move that crosses the threshold, moves after it, and ends before the
+    /// release.
+    pub fn process(&mut self, event: &Event) -> Vec<Event> {
+        let synthesized = |mouse| Event::new(EventKind::Mouse(mouse));
+        let mut events = vec![event.clone()];
+        let EventKind::Mouse(mouse) = event.kind else {
+            return events;
+        };
+
+        match mouse {
+            MouseEvent::Button {
+                button,
+                state: KeyState::Pressed,
+                col,
+                row,
+                ..
+            } if self.pressed.is_none() => {
+                self.pressed = Some((button, col, row));
+            }
+            MouseEvent::Moved { col, row } => {
+                let Some((button, start_col, start_row)) = self.pressed else {
+                    return events;
+                };
+                let distance = start_col.abs_diff(col).max(start_row.abs_diff(row));
+                if !self.dragging && distance >= self.threshold {
+                    // Ahead of the move, so a target the move enters
+                    // already sees the payload
+                    self.dragging = true;
+                    events.insert(0, synthesized(MouseEvent::DragStart {
+                        button,
+                        col: start_col,
+                        row: start_row,
+                    }));
+                }
+                if self.dragging {
+                    events.push(synthesized(MouseEvent::DragMove { button, col, row }));
+                }
+            }
+            MouseEvent::Button {
+                button,
+                state: KeyState::Released,
+                col,
+                row,
+                ..
+            } if matches!(self.pressed, Some((held, _, _)) if held == button) => {
+                if self.dragging {
+                    events.insert(0, synthesized(MouseEvent::DragEnd { button, col, row }));
+                    events.insert(0, synthesized(MouseEvent::Drop { col, row }));
+                }
+                self.pressed = None;
+                self.dragging = false;
+            }
+            _ => {}
+        }
+        events
+    }
+}
+
+/// Payload of an in-app drag, shared between source and targets
+///
+/// Clones share the same state, like `Binding`.
+#[derive(Clone, Default)]
+pub struct DragAndDrop {
+    inner: Rc<RefCell<DragState>>,
+}
+
+#[derive(Default)]
+struct DragState {
+    payload: Option<Rc<dyn Any>>,
+    accepted: bool,
+}
+
+impl DragAndDrop {
+    /// Create a handle with no drag in progress
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start dragging `payload`, replacing any earlier drag
+    pub fn start<T: Any>(&self, payload: T) {
+        *self.inner.borrow_mut() = DragState {
+            payload: Some(Rc::new(payload)),
+            accepted: false,
+        };
+    }
+
+    /// Whether a payload is being dragged and hasn't been dropped yet
+    pub fn is_active(&self) -> bool {
+        let state = self.inner.borrow();
+        state.payload.is_some() && !state.accepted
+    }
+
+    /// The payload being dragged, if there is one of type `T`
+    ///
+    /// Targets use this to decide whether to highlight themselves.
+    pub fn payload<T: Any>(&self) -> Option<Rc<T>> {
+        let state = self.inner.borrow();
+        if state.accepted {
+            return None;
+        }
+        state.payload.clone()?.downcast().ok()
+    }
+
+    /// Take the payload on `Drop`, if it is of type `T`
+    ///
+    /// The source learns the drop was accepted from `finish`.
+    pub fn accept<T: Any>(&self) -> Option<Rc<T>> {
+        let payload = self.payload()?;
+        self.inner.borrow_mut().accepted = true;
+        Some(payload)
+    }
+
+    /// End the drag on `DragEnd`, returning whether a target accepted it
+    pub fn finish(&self) -> bool {
+        std::mem::take(&mut *self.inner.borrow_mut()).accepted
+    }
+
+    /// Abandon the drag without a drop
+    pub fn cancel(&self) {
+        *self.inner.borrow_mut() = DragState::default();
+    }
+}
+
+impl fmt::Debug for DragAndDrop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.borrow();
+        f.debug_struct("DragAndDrop")
+            .field("active", &state.payload.is_some())
+            .field("accepted", &state.accepted)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Modifiers;
+
+    fn mouse(mouse: MouseEvent) -> Event {
+        Event::new(EventKind::Mouse(mouse))
+    }
+
+    fn button(state: KeyState, col: u16, row: u16) -> Event {
+        mouse(MouseEvent::Button {
+            button: MouseButton::Left,
+            state,
+            col,
+            row,
+            modifiers: Modifiers::none(),
+        })
+    }
+
+    fn kinds(events: Vec<Event>) -> Vec<EventKind> {
+        events.into_iter().map(|event| event.kind).collect()
+    }
+
+    #[test]
+    fn test_drag_starts_past_threshold() {
+        let mut drags = DragTracker::new().with_threshold(2);
+        drags.process(&button(KeyState::Pressed, 5, 5));
+
+        let moved = mouse(MouseEvent::Moved { col: 6, row: 5 });
+        assert_eq!(drags.process(&moved).len(), 1);
+
+        let moved = mouse(MouseEvent::Moved { col: 7, row: 6 });
+        assert_eq!(
+            kinds(drags.process(&moved)),
+            [
+                EventKind::Mouse(MouseEvent::DragStart {
+                    button: MouseButton::Left,
+                    col: 5,
+                    row: 5
+                }),
+                moved.kind.clone(),
+                EventKind::Mouse(MouseEvent::DragMove {
+                    button: MouseButton::Left,
+                    col: 7,
+                    row: 6
+                }),
+            ]
+        );
+        assert!(drags.is_dragging());
+
+        let release = button(KeyState::Released, 9, 6);
+        assert_eq!(
+            kinds(drags.process(&release)),
+            [
+                EventKind::Mouse(MouseEvent::Drop { col: 9, row: 6 }),
+                EventKind::Mouse(MouseEvent::DragEnd {
+                    button: MouseButton::Left,
+                    col: 9,
+                    row: 6
+                }),
+                release.kind.clone(),
+            ]
+        );
+        assert!(!drags.is_dragging());
+    }
+
+    #[test]
+    fn test_click_without_motion_is_not_a_drag() {
+        let mut drags = DragTracker::new();
+        drags.process(&button(KeyState::Pressed, 5, 5));
+        assert_eq!(drags.process(&button(KeyState::Released, 5, 5)).len(), 1);
+
+        // Moving with nothing held isn't a drag either
+        let moved = mouse(MouseEvent::Moved { col: 20, row: 5 });
+        assert_eq!(drags.process(&moved).len(), 1);
+    }
+
+    #[test]
+    fn test_payload_handoff() {
+        let source = DragAndDrop::new();
+        let target = source.clone();
+        assert!(!target.is_active());
+
+        source.start(String::from("item"));
+        assert!(target.payload::<u32>().is_none());
+        assert_eq!(target.accept::<String>().as_deref().map(String::as_str), Some("item"));
+        assert!(!target.is_active());
+        assert!(source.finish());
+
+        source.start(1u32);
+        assert!(!source.finish());
+        assert!(!target.is_active());
+    }
+}
@@ -4,6 +4,9 @@
 //! virtualizes what terminal backends can't provide. This means components
 //! get a consistent, rich event model regardless of backend.
 
+use crate::style::Hyperlink;
+#[cfg(feature = "tui")]
+use crate::tui::guard::{self, Mode};
 use anyhow::Result;
 use std::time::Duration;
 
@@ -139,6 +142,77 @@ pub enum MouseEvent {
         /// Modifier keys held during scroll
         modifiers: Modifiers,
     },
+    /// Pointer moved onto a component (synthesized by `HitMap`)
+    Enter {
+        /// Column position
+        col: u16,
+        /// Row position
+        row: u16,
+    },
+    /// Pointer moved off a component (synthesized by `HitMap`)
+    Leave,
+    /// Pointer moved within a component (synthesized by `HitMap`)
+    Hover {
+        /// Column position
+        col: u16,
+        /// Row position
+        row: u16,
+    },
+    /// A held button moved past the drag threshold (synthesized by
+    /// `DragTracker`); the position is where the button was pressed
+    DragStart {
+        /// Button held
+        button: MouseButton,
+        /// Column position
+        col: u16,
+        /// Row position
+        row: u16,
+    },
+    /// Pointer moved during a drag (synthesized by `DragTracker`)
+    DragMove {
+        /// Button held
+        button: MouseButton,
+        /// Column position
+        col: u16,
+        /// Row position
+        row: u16,
+    },
+    /// Button released after a drag (synthesized by `DragTracker`, after
+    /// the `Drop` and ahead of the release itself)
+    DragEnd {
+        /// Button released
+        button: MouseButton,
+        /// Column position
+        col: u16,
+        /// Row position
+        row: u16,
+    },
+    /// A drag ended here (synthesized by `DragTracker`); `HitMap` routes it
+    /// to the drop target under the pointer, and `DragEnd` to the source
+    Drop {
+        /// Column position
+        col: u16,
+        /// Row position
+        row: u16,
+    },
+}
+
+impl MouseEvent {
+    /// Pointer position, or `None` for `Leave`
+    pub fn position(&self) -> Option<(u16, u16)> {
+        match *self {
+            MouseEvent::Button { col, row, .. }
+            | MouseEvent::Moved { col, row }
+            | MouseEvent::Scroll { col, row, .. }
+            | MouseEvent::Enter { col, row }
+            | MouseEvent::Hover { col, row }
+            | MouseEvent::DragStart { col, row, .. }
+            | MouseEvent::DragMove { col, row, .. }
+            | MouseEvent::DragEnd { col, row, .. }
+            | MouseEvent::Drop { col, row } => Some((col, row)),
+            MouseEvent::Leave => None,
+        }
+    }
 }
 
 /// The backend-specific original event, accessible if you need it
@@ -177,6 +251,10 @@ pub enum EventKind {
     /// Paste event (bracketed paste in terminals, clipboard in GUI)
     Paste(String),
     /// Redraw requested (frame tick — render your content)
+    ///
+    /// With `RunOptions::with_tick`, the run loops also send one after the
+    /// tick interval without input, so components can act on timeouts such
+    /// as a pending key sequence.
     Redraw,
     /// File(s) dropped onto the window
     Drop(Vec<std::path::PathBuf>),
@@ -184,6 +262,9 @@ pub enum EventKind {
     DragOver(Vec<std::path::PathBuf>),
     /// Drag cancelled (files left the window)
     DragLeave,
+    /// A cell styled with `Style::link` was clicked (GUI backend; terminals
+    /// open links themselves)
+    LinkClicked(Hyperlink),
 }
 
 /// A UI event with both an abstracted kind and the original backend event
@@ -294,17 +375,54 @@ pub trait EventHandler {
     fn on_blur(&mut self) {}
 }
 
+/// Anything that produces events for a run loop
+///
+/// Implemented by the live `EventPoller`, byte-stream readers, and
+/// `replay::ReplaySource`, so `App::run_with` can be driven by any of them.
+pub trait EventSource {
+    /// Block until the next event; `None` once the source is exhausted
+    fn next_event(&mut self) -> Result<Option<Event>>;
+
+    /// Like `next_event`, but give up after `timeout` with a `Redraw`
+    ///
+    /// Sources that don't wait on a live clock, like replays, just return
+    /// the next event.
+    fn next_event_timeout(&mut self, _timeout: Duration) -> Result<Option<Event>> {
+        self.next_event()
+    }
+
+    /// Whether events come from the terminal this process runs in
+    ///
+    /// The TUI loop only suspends on Ctrl-Z and re-reads the terminal size
+    /// on a resize for live sources. Otherwise Ctrl-Z is an ordinary key and
+    /// a resize applies the size it carries, so replays reproduce the
+    /// recorded layout.
+    fn is_live(&self) -> bool {
+        false
+    }
+}
+
 // -- TUI backend: crossterm conversion --
 
 #[cfg(feature = "tui")]
 /// Event polling and conversion from crossterm events
+///
+/// Where the terminal supports the kitty keyboard protocol, the poller
+/// pushes its enhancement flags while it lives, on whichever screen shows
+/// (`TerminalRenderer` keeps the alternate screen's stack in step), so keys
+/// arrive with release and repeat states and Ctrl-I, Tab, and Esc are told
+/// apart. Elsewhere only presses arrive. Key text is derived from the key,
+/// since crossterm drops kitty's associated text.
 pub struct EventPoller;
 
 #[cfg(feature = "tui")]
 impl EventPoller {
     /// Create a new event poller
     pub fn new() -> Result<Self> {
+        use std::io::Write;
+
         crossterm::terminal::enable_raw_mode()?;
+        guard::set_mode(Mode::Raw, true);
 
         // Use /dev/tty to avoid stdout lock contention with writer thread
         if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
@@ -313,6 +431,12 @@ impl EventPoller {
                 crossterm::event::EnableMouseCapture,
                 crossterm::event::EnableFocusChange,
             );
+            guard::set_mode(Mode::MouseCapture, true);
+            guard::set_mode(Mode::FocusChange, true);
+            // Release/repeat events and Ctrl-I apart from Tab, where supported
+            if guard::keyboard_enhancement_supported() {
+                let _ = tty.write_all(guard::want_keyboard_flags(true));
+            }
         }
 
         Ok(EventPoller)
@@ -345,17 +469,39 @@ impl EventPoller {
     }
 }
 
+#[cfg(feature = "tui")]
+impl EventSource for EventPoller {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        self.read().map(Some)
+    }
+
+    fn next_event_timeout(&mut self, timeout: Duration) -> Result<Option<Event>> {
+        let event = self.poll(timeout)?;
+        Ok(Some(event.unwrap_or_else(|| Event::new(EventKind::Redraw))))
+    }
+
+    fn is_live(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(feature = "tui")]
 impl Drop for EventPoller {
     fn drop(&mut self) {
+        use std::io::Write;
+
         if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
             let _ = crossterm::execute!(
                 tty,
                 crossterm::event::DisableMouseCapture,
                 crossterm::event::DisableFocusChange,
             );
+            guard::set_mode(Mode::MouseCapture, false);
+            guard::set_mode(Mode::FocusChange, false);
+            let _ = tty.write_all(guard::want_keyboard_flags(false));
         }
         let _ = crossterm::terminal::disable_raw_mode();
+        guard::set_mode(Mode::Raw, false);
     }
 }
 
@@ -452,9 +598,12 @@ fn convert_crossterm_event(event: crossterm::event::Event) -> Event {
             let mut modifiers = crossterm_modifiers(*mods);
             let key = convert_crossterm_key(*code);
 
-            // BackTab means shift was held
-            if *code == crossterm::event::KeyCode::BackTab {
-                modifiers.shift = true;
+            // BackTab means shift was held, and so does an uppercase letter;
+            // crossterm drops shift when kitty reports the shifted key
+            match *code {
+                crossterm::event::KeyCode::BackTab => modifiers.shift = true,
+                crossterm::event::KeyCode::Char(c) if c.is_uppercase() => modifiers.shift = true,
+                _ => {}
             }
 
             let state = match key_kind {
@@ -463,7 +612,9 @@ fn convert_crossterm_event(event: crossterm::event::Event) -> Event {
                 KeyEventKind::Repeat => KeyState::Repeat,
             };
 
+            // Releases type nothing
             let text = match key {
+                _ if state == KeyState::Released => None,
                 Key::Char(c) if !modifiers.ctrl && !modifiers.alt => Some(c.to_string()),
                 Key::Space if !modifiers.ctrl && !modifiers.alt => Some(" ".to_string()),
                 _ => None,
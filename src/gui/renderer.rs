@@ -1,7 +1,14 @@
 //! wgpu-based GUI renderer implementing the Renderer trait
-
-use crate::render::{DirtyRegion, ImageParams, Renderer};
-use crate::style::Style;
+//!
+//! Frames are drawn onto a canvas texture that persists between frames and
+//! is copied to the window surface on present. Only the dirty rectangles of
+//! the canvas are erased and repainted, so unchanged components can skip
+//! rendering the way they do on a terminal.
+
+use crate::layout::Rect;
+use crate::render::{ClipStack, CursorShape, CursorStyle, DirtyRegion, ImageParams, Renderer};
+use crate::style::{Hyperlink, Style, UnderlineStyle};
+use crate::unicode;
 use anyhow::Result;
 use glyphon::{
     Attrs, Buffer as GlyphonBuffer, Cache, Color as GlyphonColor, Family, FontSystem, Metrics,
@@ -9,9 +16,10 @@ use glyphon::{
     TextRenderer as GlyphonText, Viewport, Weight,
 };
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use wgpu;
 use wgpu::util::DeviceExt;
-use winit::window::Window;
+use winit::window::{UserAttentionType, Window};
 
 /// Cell dimensions in pixels for the monospace grid
 #[derive(Debug, Clone, Copy)]
@@ -30,8 +38,12 @@ struct ImageEntry {
     dst_w: f32,
     dst_h: f32,
     is_rgba: bool,
+    clip: Option<Rect>,
 }
 
+/// Time blinking text and the cursor spend in each phase of a blink
+const BLINK_PHASE: Duration = Duration::from_millis(530);
+
 /// Vertex for textured quad rendering
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -74,12 +86,29 @@ pub struct WgpuRenderer {
     cursor_col: u16,
     cursor_row: u16,
     cursor_visible: bool,
+    cursor_style: CursorStyle,
+    /// Cell where the current cursor blink cycle started, and when
+    blink_start: ((u16, u16), Instant),
+    /// Start of the blink cycle for text styled with `blink`
+    created: Instant,
+    /// Blink phase of the last presented frame
+    shown_phase: u128,
+    /// Blinking cell runs written this frame
+    frame_blinks: Vec<Rect>,
+    /// Blinking cell runs in the last presented frame
+    blinks: Vec<Rect>,
+    clips: ClipStack,
+    /// Linked cell runs written this frame
+    frame_links: Vec<(Rect, Hyperlink)>,
+    /// Linked cell runs in the last presented frame
+    links: Vec<(Rect, Hyperlink)>,
 
     // Frame state
     dirty: DirtyRegion,
+    /// Retained frame contents, created with the surface on the first resize
+    canvas: Option<wgpu::Texture>,
     current_texture: Option<wgpu::SurfaceTexture>,
     surface_configured: bool,
-    frame_has_content: bool,
 }
 
 /// A pending text draw operation accumulated during a frame
@@ -88,6 +117,7 @@ struct TextEntry {
     col: u16,
     row: u16,
     style: Style,
+    clip: Option<Rect>,
 }
 
 const BLIT_SHADER: &str = r#"
@@ -294,10 +324,19 @@ impl WgpuRenderer {
             cursor_col: 0,
             cursor_row: 0,
             cursor_visible: true,
+            cursor_style: CursorStyle::default(),
+            blink_start: ((0, 0), Instant::now()),
+            created: Instant::now(),
+            shown_phase: 0,
+            frame_blinks: Vec::new(),
+            blinks: Vec::new(),
+            clips: ClipStack::new(),
+            frame_links: Vec::new(),
+            links: Vec::new(),
             dirty: DirtyRegion::new(),
+            canvas: None,
             current_texture: None,
             surface_configured: false,
-            frame_has_content: false,
         })
     }
 
@@ -326,6 +365,20 @@ impl WgpuRenderer {
         self.surface_config.format
     }
 
+    /// Link under a window position (physical pixels) in the last frame
+    pub fn link_at(&self, x: f64, y: f64) -> Option<Hyperlink> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let col = (x / self.cell_size.width as f64) as u16;
+        let row = (y / self.cell_size.height as f64) as u16;
+        self.links
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(col, row))
+            .map(|(_, link)| *link)
+    }
+
     /// Handle window resize
     pub fn resize(&mut self, width: u32, height: u32) {
         if width == 0 || height == 0 {
@@ -337,6 +390,28 @@ impl WgpuRenderer {
         self.surface_configured = true;
         self.cols = (width as f32 / self.cell_size.width) as u16;
         self.rows = (height as f32 / self.cell_size.height) as u16;
+
+        self.canvas = Some(self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("canvas"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }));
+        self.dirty.mark_all(self.cols, self.rows);
+    }
+
+    /// Mark the cells a draw touches, within the active clip
+    fn mark_written(&mut self, area: Rect) {
+        let area = self.clips.apply(area);
+        self.dirty.mark_region(area.x, area.y, area.width, area.height);
     }
 
     /// Convert cell position to pixel position
@@ -347,6 +422,18 @@ impl WgpuRenderer {
         )
     }
 
+    /// Convert a cell clip rectangle to pixel bounds (x, y, width, height) on the surface
+    fn clip_to_pixels(&self, clip: Rect) -> (u32, u32, u32, u32) {
+        let (x0, y0) = self.cell_to_pixel(clip.x, clip.y);
+        let (x1, y1) = self.cell_to_pixel(clip.right(), clip.bottom());
+        let (max_w, max_h) = (self.surface_config.width, self.surface_config.height);
+        let x = (x0 as u32).min(max_w);
+        let y = (y0 as u32).min(max_h);
+        let right = (x1.ceil() as u32).clamp(x, max_w);
+        let bottom = (y1.ceil() as u32).clamp(y, max_h);
+        (x, y, right - x, bottom - y)
+    }
+
     /// Convert pixel position to NDC (normalized device coordinates)
     fn pixel_to_ndc(&self, x: f32, y: f32) -> (f32, f32) {
         let ndc_x = (x / self.surface_config.width as f32) * 2.0 - 1.0;
@@ -367,6 +454,11 @@ impl WgpuRenderer {
             });
 
         for entry in &self.image_buffers {
+            let scissor = entry.clip.map(|clip| self.clip_to_pixels(clip));
+            if scissor.is_some_and(|(_, _, w, h)| w == 0 || h == 0) {
+                continue;
+            }
+
             // Convert RGB to RGBA if needed
             let rgba_data = if entry.is_rgba {
                 entry.data.clone()
@@ -452,6 +544,9 @@ impl WgpuRenderer {
                     ..Default::default()
                 });
 
+                if let Some((x, y, w, h)) = scissor {
+                    pass.set_scissor_rect(x, y, w, h);
+                }
                 pass.set_pipeline(&self.blit_pipeline);
                 pass.set_bind_group(0, &bind_group, &[]);
                 pass.set_vertex_buffer(0, vertex_buffer.slice(..));
@@ -504,21 +599,34 @@ impl WgpuRenderer {
 
             let color = style_to_glyphon_color(&entry.style);
 
-            glyph_buffers.push((buffer, px, py, color));
+            let bounds = match entry.clip {
+                Some(clip) => {
+                    let (x, y, w, h) = self.clip_to_pixels(clip);
+                    TextBounds {
+                        left: x as i32,
+                        top: y as i32,
+                        right: (x + w) as i32,
+                        bottom: (y + h) as i32,
+                    }
+                }
+                None => TextBounds {
+                    left: 0,
+                    top: 0,
+                    right: self.surface_config.width as i32,
+                    bottom: self.surface_config.height as i32,
+                },
+            };
+
+            glyph_buffers.push((buffer, px, py, color, bounds));
         }
 
-        for (buffer, px, py, color) in &glyph_buffers {
+        for (buffer, px, py, color, bounds) in &glyph_buffers {
             text_areas.push(TextArea {
                 buffer,
                 left: *px,
                 top: *py,
                 scale: 1.0,
-                bounds: TextBounds {
-                    left: 0,
-                    top: 0,
-                    right: self.surface_config.width as i32,
-                    bottom: self.surface_config.height as i32,
-                },
+                bounds: *bounds,
                 default_color: *color,
                 custom_glyphs: &[],
             });
@@ -569,6 +677,214 @@ impl WgpuRenderer {
         Ok(())
     }
 
+    /// Phase count of the blink cycle for text styled with `blink`
+    fn blink_phase(&self) -> u128 {
+        self.created.elapsed().as_millis() / BLINK_PHASE.as_millis()
+    }
+
+    /// Queue a quad for the cursor at the logical cursor position
+    ///
+    /// Drawn over the presented canvas rather than into it, so it never
+    /// lingers after moving; a block cursor is translucent to leave its
+    /// glyph readable. Skipped while hidden or in the off phase of a blink.
+    fn queue_cursor(&mut self) {
+        let (col, row) = (self.cursor_col, self.cursor_row);
+        if !self.cursor_visible || col >= self.cols || row >= self.rows {
+            return;
+        }
+
+        // Moving restarts the blink, so the cursor is shown while typing
+        if self.blink_start.0 != (col, row) {
+            self.blink_start = ((col, row), Instant::now());
+        }
+        let style = self.cursor_style;
+        let phase = self.blink_start.1.elapsed().as_millis() / BLINK_PHASE.as_millis();
+        if style.blinking && phase % 2 == 1 {
+            return;
+        }
+
+        let (x, y) = self.cell_to_pixel(col, row);
+        let CellSize { width, height } = self.cell_size;
+        let thickness = (height / 10.0).round().max(2.0);
+        let (dst_x, dst_y, dst_w, dst_h) = match style.shape {
+            CursorShape::Block => (x, y, width, height),
+            CursorShape::Underline => (x, y + height - thickness, width, thickness),
+            CursorShape::Bar => (x, y, thickness, height),
+        };
+        let (r, g, b) = style.color.map_or((160, 160, 160), |color| color.to_rgb());
+        let alpha = if style.shape == CursorShape::Block { 128 } else { 255 };
+        self.image_buffers.push(ImageEntry {
+            data: vec![r, g, b, alpha],
+            width: 1,
+            height: 1,
+            dst_x,
+            dst_y,
+            dst_w,
+            dst_h,
+            is_rgba: true,
+            clip: None,
+        });
+    }
+
+    /// Queue black quads erasing the dirty rectangles of the canvas
+    ///
+    /// Queued ahead of everything drawn this frame, which lands on top.
+    fn queue_erase(&mut self) {
+        let erase: Vec<ImageEntry> = self
+            .dirty
+            .iter()
+            .map(|&rect| {
+                let (x, y, w, h) = self.clip_to_pixels(rect);
+                ImageEntry {
+                    data: vec![0, 0, 0, 255],
+                    width: 1,
+                    height: 1,
+                    dst_x: x as f32,
+                    dst_y: y as f32,
+                    dst_w: w as f32,
+                    dst_h: h as f32,
+                    is_rgba: true,
+                    clip: Some(rect),
+                }
+            })
+            .collect();
+        self.image_buffers.splice(0..0, erase);
+    }
+
+    /// Copy the canvas onto the surface as one full-screen quad
+    fn present_canvas(&self, canvas: &wgpu::TextureView, view: &wgpu::TextureView) {
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("canvas_bind_group"),
+            layout: &self.blit_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(canvas),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.blit_sampler),
+                },
+            ],
+        });
+
+        let vertices = [
+            BlitVertex { position: [-1.0, 1.0], tex_coord: [0.0, 0.0] },
+            BlitVertex { position: [1.0, 1.0], tex_coord: [1.0, 0.0] },
+            BlitVertex { position: [-1.0, -1.0], tex_coord: [0.0, 1.0] },
+            BlitVertex { position: [1.0, 1.0], tex_coord: [1.0, 0.0] },
+            BlitVertex { position: [1.0, -1.0], tex_coord: [1.0, 1.0] },
+            BlitVertex { position: [-1.0, -1.0], tex_coord: [0.0, 1.0] },
+        ];
+        let vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("canvas_vertices"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("canvas_encoder"),
+            });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("canvas_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.blit_pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            pass.draw(0..6, 0..1);
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Queue line quads for underline, strikethrough, and overline
+    ///
+    /// Lines are drawn beneath the glyphs, in the underline color or else
+    /// the text color.
+    fn queue_decorations(&mut self, style: &Style, col: u16, row: u16, cells: u16) {
+        let (x, y) = self.cell_to_pixel(col, row);
+        let CellSize {
+            width: cell_w,
+            height: cell_h,
+        } = self.cell_size;
+        let width = cells as f32 * cell_w;
+        let thickness = (cell_h / 16.0).round().max(1.0);
+        let text_color = style.fg.map_or((255, 255, 255), |color| color.to_rgb());
+        let clip = self.clips.current();
+
+        if style.overline == Some(true) {
+            self.push_quad((x, y, width, thickness), text_color, clip);
+        }
+        if style.strikethrough == Some(true) {
+            let middle = y + (cell_h - thickness) / 2.0;
+            self.push_quad((x, middle, width, thickness), text_color, clip);
+        }
+        if style.underline != Some(true) {
+            return;
+        }
+
+        let color = style.underline_color.map_or(text_color, |color| color.to_rgb());
+        let base = y + cell_h - 2.0 * thickness;
+        // Patterned lines are runs of (length, gap) or, for curly, stepped segments
+        let shape = style.underline_style.unwrap_or_default();
+        let (segment, gap, offsets): (f32, f32, &[f32]) = match shape {
+            UnderlineStyle::Single => (width, 0.0, &[0.0]),
+            UnderlineStyle::Double => {
+                self.push_quad((x, base - 2.0 * thickness, width, thickness), color, clip);
+                (width, 0.0, &[0.0])
+            }
+            UnderlineStyle::Dotted => (thickness, thickness, &[0.0]),
+            UnderlineStyle::Dashed => (3.0 * thickness, 2.0 * thickness, &[0.0]),
+            UnderlineStyle::Curly => ((cell_w / 4.0).max(1.0), 0.0, &[-1.0, 0.0, 1.0, 0.0]),
+        };
+        let mut offset = 0.0;
+        for step in 0.. {
+            if offset >= width {
+                break;
+            }
+            let length = segment.min(width - offset);
+            let dy = offsets[step % offsets.len()] * thickness;
+            self.push_quad((x + offset, base + dy, length, thickness), color, clip);
+            offset += segment + gap;
+        }
+    }
+
+    /// Queue a solid rectangle (x, y, width, height in pixels)
+    fn push_quad(
+        &mut self,
+        rect: (f32, f32, f32, f32),
+        (r, g, b): (u8, u8, u8),
+        clip: Option<Rect>,
+    ) {
+        let (dst_x, dst_y, dst_w, dst_h) = rect;
+        self.image_buffers.push(ImageEntry {
+            data: vec![r, g, b, 255],
+            width: 1,
+            height: 1,
+            dst_x,
+            dst_y,
+            dst_w,
+            dst_h,
+            is_rgba: true,
+            clip,
+        });
+    }
+
     fn queue_image(&mut self, params: &ImageParams, is_rgba: bool) {
         let (px, py) = self.cell_to_pixel(params.col, params.row);
         let dst_w = params
@@ -579,6 +895,9 @@ impl WgpuRenderer {
             .height_cells
             .map(|r| r as f32 * self.cell_size.height)
             .unwrap_or(params.height as f32);
+        let cells_w = (dst_w / self.cell_size.width).ceil() as u16;
+        let cells_h = (dst_h / self.cell_size.height).ceil() as u16;
+        self.mark_written(Rect::new(params.col, params.row, cells_w, cells_h));
 
         self.image_buffers.push(ImageEntry {
             data: params.data.to_vec(),
@@ -589,6 +908,7 @@ impl WgpuRenderer {
             dst_w,
             dst_h,
             is_rgba,
+            clip: self.clips.current(),
         });
     }
 }
@@ -603,24 +923,47 @@ impl Renderer for WgpuRenderer {
     }
 
     fn write_text(&mut self, text: &str) -> Result<()> {
+        let width = unicode::width(text) as u16;
+        self.mark_written(Rect::new(self.cursor_col, self.cursor_row, width, 1));
         self.text_buffers.push(TextEntry {
             text: text.to_string(),
             col: self.cursor_col,
             row: self.cursor_row,
             style: Style::new(),
+            clip: self.clips.current(),
         });
-        self.cursor_col += text.chars().count() as u16;
+        self.cursor_col += width;
         Ok(())
     }
 
     fn write_styled(&mut self, text: &str, style: &Style) -> Result<()> {
+        let width = unicode::width(text) as u16;
+        let (col, row) = (self.cursor_col, self.cursor_row);
+        self.cursor_col += width;
+        self.mark_written(Rect::new(col, row, width, 1));
+
+        let mut area = Rect::new(col, row, width, 1);
+        if let Some(clip) = self.clips.current() {
+            area = area.intersection(&clip);
+        }
+        if let Some(link) = style.link {
+            self.frame_links.push((area, link));
+        }
+
+        if style.blink == Some(true) {
+            self.frame_blinks.push(area);
+            if self.blink_phase() % 2 == 1 {
+                return Ok(());
+            }
+        }
+        self.queue_decorations(style, col, row, width);
         self.text_buffers.push(TextEntry {
             text: text.to_string(),
-            col: self.cursor_col,
-            row: self.cursor_row,
+            col,
+            row,
             style: *style,
+            clip: self.clips.current(),
         });
-        self.cursor_col += text.chars().count() as u16;
         Ok(())
     }
 
@@ -645,11 +988,30 @@ impl Renderer for WgpuRenderer {
         Ok(())
     }
 
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        self.cursor_style = style;
+        Ok(())
+    }
+
+    fn set_title(&mut self, title: &str) -> Result<()> {
+        self.window.set_title(title);
+        Ok(())
+    }
+
+    /// Windows have no notification channel; flag the window for attention
+    /// instead, unless the user is already looking at it
+    fn notify(&mut self, _title: &str, _body: &str) -> Result<()> {
+        if !self.window.has_focus() {
+            self.window
+                .request_user_attention(Some(UserAttentionType::Informational));
+        }
+        Ok(())
+    }
+
     fn clear(&mut self) -> Result<()> {
         self.text_buffers.clear();
         self.image_buffers.clear();
         self.dirty.mark_all(self.cols, self.rows);
-        self.frame_has_content = true;
         Ok(())
     }
 
@@ -657,7 +1019,16 @@ impl Renderer for WgpuRenderer {
         Ok(())
     }
 
-    fn fill_rect(&mut self, bounds: crate::layout::Rect, color: crate::theme::Color) -> Result<()> {
+    fn push_clip(&mut self, rect: Rect) {
+        self.clips.push(rect);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn fill_rect(&mut self, bounds: Rect, color: crate::theme::Color) -> Result<()> {
+        self.mark_written(bounds);
         let (r, g, b) = color.to_rgb();
         // 1x1 RGBA pixel, scaled to fill the rect via the blit pipeline
         let pixel = vec![r, g, b, 255];
@@ -674,6 +1045,7 @@ impl Renderer for WgpuRenderer {
             dst_w,
             dst_h,
             is_rgba: true,
+            clip: self.clips.current(),
         });
         Ok(())
     }
@@ -697,10 +1069,6 @@ impl Renderer for WgpuRenderer {
         (self.cols, self.rows)
     }
 
-    fn retains_content(&self) -> bool {
-        false
-    }
-
     fn dirty_region(&self) -> &DirtyRegion {
         &self.dirty
     }
@@ -718,7 +1086,19 @@ impl Renderer for WgpuRenderer {
         self.image_buffers.clear();
         self.cursor_col = 0;
         self.cursor_row = 0;
-        self.frame_has_content = false;
+        self.clips.clear();
+        self.frame_links.clear();
+        self.frame_blinks.clear();
+
+        // The canvas keeps blinking text in the phase it was drawn in, so
+        // its components re-render when the phase flips
+        let phase = self.blink_phase();
+        if phase != self.shown_phase {
+            self.shown_phase = phase;
+            for area in &self.blinks {
+                self.dirty.mark_region(area.x, area.y, area.width, area.height);
+            }
+        }
 
         if !self.surface_configured {
             return Ok(());
@@ -739,45 +1119,32 @@ impl Renderer for WgpuRenderer {
     }
 
     fn end_frame(&mut self) -> Result<()> {
-        if let Some(output) = self.current_texture.take() {
-            let has_content = self.frame_has_content
-                || !self.text_buffers.is_empty()
-                || !self.image_buffers.is_empty();
-
-            if !has_content {
-                return Ok(());
+        let canvas = self
+            .canvas
+            .as_ref()
+            .map(|canvas| canvas.create_view(&wgpu::TextureViewDescriptor::default()));
+        if let (Some(output), Some(canvas)) = (self.current_texture.take(), canvas) {
+            // Links under repainted cells belong to whatever was drawn before
+            let dirty = &self.dirty;
+            self.links
+                .retain(|(area, _)| !dirty.intersects(area.x, area.y, area.width, area.height));
+            self.links.append(&mut self.frame_links);
+            self.blinks
+                .retain(|area| !dirty.intersects(area.x, area.y, area.width, area.height));
+            self.blinks.append(&mut self.frame_blinks);
+
+            if self.dirty.is_dirty {
+                self.queue_erase();
+                self.flush_images(&canvas)?;
+                self.flush_text(&canvas)?;
             }
 
             let view = output
                 .texture
                 .create_view(&wgpu::TextureViewDescriptor::default());
-
-            // Clear the surface before drawing content
-            let mut encoder = self
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("clear_encoder"),
-                });
-            {
-                let _pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("clear_pass"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                            store: wgpu::StoreOp::Store,
-                        },
-                        depth_slice: None,
-                    })],
-                    depth_stencil_attachment: None,
-                    ..Default::default()
-                });
-            }
-            self.queue.submit(std::iter::once(encoder.finish()));
-
+            self.present_canvas(&canvas, &view);
+            self.queue_cursor();
             self.flush_images(&view)?;
-            self.flush_text(&view)?;
             output.present();
         }
 
@@ -0,0 +1,309 @@
+//! Hit testing - routing mouse events to the component under the pointer
+//!
+//! A parent records each child's bounds in a `HitMap` as it renders them,
+//! in paint order, so a child painted later sits on top of one painted
+//! earlier. Mouse events are then routed with the bounds from the last
+//! frame:
+//!
+//! - Buttons and scrolls go to the topmost child under the pointer
+//! - A press captures its target: moves and the release go there too, even
+//!   once the pointer has left it, so drags aren't cut short; whatever the
+//!   pointer passes over still sees it hover
+//! - Moves become `MouseEvent::Enter` and `MouseEvent::Leave` when the
+//!   child under the pointer changes, and `MouseEvent::Hover` within it
+//! - Drag events go to the captor, while `MouseEvent::Drop` goes to the
+//!   child under the pointer
+//!
+//! Synthesized events are routed like moves, so nested containers each
+//! track hover among their own children.
+//!
+//! # Example
+//!
+//! ```
+//! use mkui::event::MouseEvent;
+//! use mkui::{Event, EventKind, HitMap, Rect};
+//!
+//! let mut hits = HitMap::new();
+//! hits.record("sidebar", Rect::new(0, 0, 20, 24));
+//! hits.record("editor", Rect::new(20, 0, 60, 24));
+//!
+//! let moved = Event::new(EventKind::Mouse(MouseEvent::Moved { col: 30, row: 5 }));
+//! let routed = hits.route(&moved);
+//! assert_eq!(routed[0].0, "editor");
+//! assert_eq!(routed[0].1.kind, EventKind::Mouse(MouseEvent::Enter { col: 30, row: 5 }));
+//! ```
+
+use crate::event::{Event, EventKind, KeyState, MouseEvent};
+use crate::layout::Rect;
+
+/// Component bounds recorded during a frame, for routing mouse events
+///
+/// `K` identifies a component to its parent: a child index, a
+/// `ComponentId`, or anything else that can be compared.
+#[derive(Debug, Clone)]
+pub struct HitMap<K = usize> {
+    /// Recorded bounds in paint order; the last match is on top
+    regions: Vec<(K, Rect)>,
+    /// Component the pointer is over
+    hovered: Option<K>,
+    /// Component that took a button press and gets events until release
+    captured: Option<K>,
+}
+
+impl<K> Default for HitMap<K> {
+    fn default() -> Self {
+        HitMap {
+            regions: Vec::new(),
+            hovered: None,
+            captured: None,
+        }
+    }
+}
+
+impl<K: Clone + PartialEq> HitMap<K> {
+    /// Create an empty hit map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the recorded bounds, before rendering a new frame
+    ///
+    /// Hover and capture carry over, so a component that is still there
+    /// doesn't see a spurious leave.
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    /// Record a component's rendered bounds on top of those recorded so far
+    ///
+    /// Recording a key again replaces its earlier bounds.
+    pub fn record(&mut self, key: K, bounds: Rect) {
+        self.regions.retain(|(other, _)| *other != key);
+        self.regions.push((key, bounds));
+    }
+
+    /// Bounds recorded for a component
+    pub fn bounds(&self, key: &K) -> Option<Rect> {
+        self.regions
+            .iter()
+            .find(|(other, _)| other == key)
+            .map(|(_, bounds)| *bounds)
+    }
+
+    /// Topmost component at a cell
+    pub fn hit(&self, col: u16, row: u16) -> Option<&K> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(_, bounds)| bounds.contains(col, row))
+            .map(|(key, _)| key)
+    }
+
+    /// Component the pointer is over
+    pub fn hovered(&self) -> Option<&K> {
+        self.hovered.as_ref()
+    }
+
+    /// Component holding the pointer capture after a button press
+    pub fn captured(&self) -> Option<&K> {
+        self.captured.as_ref()
+    }
+
+    /// Work out which components a mouse event concerns
+    ///
+    /// Returns the events to deliver, in order, with the component each
+    /// goes to. Other kinds of event route nowhere.
+    pub fn route(&mut self, event: &Event) -> Vec<(K, Event)> {
+        let EventKind::Mouse(mouse) = event.kind else {
+            return Vec::new();
+        };
+        let synthesized = |mouse| Event::new(EventKind::Mouse(mouse));
+
+        let mut routed = Vec::new();
+        match mouse {
+            MouseEvent::Moved { col, row }
+            | MouseEvent::Enter { col, row }
+            | MouseEvent::Hover { col, row } => {
+                let under = self.hit(col, row).cloned();
+                if under != self.hovered {
+                    if let Some(left) = self.hovered.take() {
+                        routed.push((left, synthesized(MouseEvent::Leave)));
+                    }
+                    if let Some(entered) = &under {
+                        routed.push((entered.clone(), synthesized(MouseEvent::Enter { col, row })));
+                    }
+                    self.hovered = under.clone();
+                }
+                // A drag goes to whatever took the press, while whatever
+                // it passes over still sees the pointer hover
+                let captured = match mouse {
+                    MouseEvent::Moved { .. } => self.captured.clone(),
+                    _ => None,
+                };
+                if let Some(under) = under.filter(|under| Some(under) != captured.as_ref()) {
+                    routed.push((under, synthesized(MouseEvent::Hover { col, row })));
+                }
+                if let Some(captured) = captured {
+                    routed.push((captured, event.clone()));
+                }
+            }
+            MouseEvent::Leave => {
+                if let Some(left) = self.hovered.take() {
+                    routed.push((left, event.clone()));
+                }
+            }
+            MouseEvent::Button {
+                state, col, row, ..
+            } => {
+                let target = match state {
+                    KeyState::Released => self.captured.take(),
+                    _ => None,
+                };
+                if let Some(target) = target.or_else(|| self.hit(col, row).cloned()) {
+                    if state == KeyState::Pressed {
+                        self.captured = Some(target.clone());
+                    }
+                    routed.push((target, event.clone()));
+                }
+            }
+            MouseEvent::DragStart { col, row, .. }
+            | MouseEvent::DragMove { col, row, .. }
+            | MouseEvent::DragEnd { col, row, .. } => {
+                if let Some(target) = self.captured.clone().or_else(|| self.hit(col, row).cloned()) {
+                    routed.push((target, event.clone()));
+                }
+            }
+            MouseEvent::Scroll { col, row, .. } | MouseEvent::Drop { col, row } => {
+                if let Some(target) = self.hit(col, row).cloned() {
+                    routed.push((target, event.clone()));
+                }
+            }
+        }
+        routed
+    }
+
+    /// Route a mouse event and hand each result to `deliver`
+    ///
+    /// Returns `None` for events that aren't mouse events, so the caller
+    /// can fall back to its usual handling, and otherwise whether any
+    /// delivery consumed the event.
+    pub fn dispatch(
+        &mut self,
+        event: &Event,
+        mut deliver: impl FnMut(&K, &Event) -> bool,
+    ) -> Option<bool> {
+        if !matches!(event.kind, EventKind::Mouse(_)) {
+            return None;
+        }
+        let mut consumed = false;
+        for (key, event) in self.route(event) {
+            consumed |= deliver(&key, &event);
+        }
+        Some(consumed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Modifiers, MouseButton};
+
+    fn mouse(mouse: MouseEvent) -> Event {
+        Event::new(EventKind::Mouse(mouse))
+    }
+
+    fn button(state: KeyState, col: u16, row: u16) -> Event {
+        mouse(MouseEvent::Button {
+            button: MouseButton::Left,
+            state,
+            col,
+            row,
+            modifiers: Modifiers::none(),
+        })
+    }
+
+    fn kinds(routed: Vec<(usize, Event)>) -> Vec<(usize, MouseEvent)> {
+        routed
+            .into_iter()
+            .map(|(key, event)| match event.kind {
+                EventKind::Mouse(mouse) => (key, mouse),
+                other => panic!("routed {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_topmost_region_wins() {
+        let mut hits = HitMap::new();
+        hits.record(0, Rect::new(0, 0, 80, 24));
+        hits.record(1, Rect::new(10, 5, 20, 10));
+
+        assert_eq!(hits.hit(15, 8), Some(&1));
+        assert_eq!(hits.hit(5, 8), Some(&0));
+        assert_eq!(hits.hit(80, 0), None);
+
+        // Re-recording moves a region to the top
+        hits.record(0, Rect::new(0, 0, 80, 24));
+        assert_eq!(hits.hit(15, 8), Some(&0));
+        assert_eq!(hits.bounds(&1), Some(Rect::new(10, 5, 20, 10)));
+    }
+
+    #[test]
+    fn test_moves_synthesize_enter_leave_hover() {
+        let mut hits = HitMap::new();
+        hits.record(0, Rect::new(0, 0, 10, 1));
+        hits.record(1, Rect::new(10, 0, 10, 1));
+
+        let routed = hits.route(&mouse(MouseEvent::Moved { col: 2, row: 0 }));
+        assert_eq!(
+            kinds(routed),
+            [
+                (0, MouseEvent::Enter { col: 2, row: 0 }),
+                (0, MouseEvent::Hover { col: 2, row: 0 }),
+            ]
+        );
+
+        let routed = hits.route(&mouse(MouseEvent::Moved { col: 12, row: 0 }));
+        assert_eq!(
+            kinds(routed),
+            [
+                (0, MouseEvent::Leave),
+                (1, MouseEvent::Enter { col: 12, row: 0 }),
+                (1, MouseEvent::Hover { col: 12, row: 0 }),
+            ]
+        );
+
+        let routed = hits.route(&mouse(MouseEvent::Moved { col: 30, row: 0 }));
+        assert_eq!(kinds(routed), [(1, MouseEvent::Leave)]);
+        assert_eq!(hits.hovered(), None);
+    }
+
+    #[test]
+    fn test_press_captures_until_release() {
+        let mut hits = HitMap::new();
+        hits.record(0, Rect::new(0, 0, 10, 1));
+        hits.record(1, Rect::new(10, 0, 10, 1));
+
+        assert_eq!(hits.route(&button(KeyState::Pressed, 3, 0))[0].0, 0);
+        assert_eq!(hits.captured(), Some(&0));
+
+        // Dragging over the neighbour hovers it but keeps feeding the drag
+        // to the captor
+        let routed = kinds(hits.route(&mouse(MouseEvent::Moved { col: 15, row: 0 })));
+        assert_eq!(routed.last(), Some(&(0, MouseEvent::Moved { col: 15, row: 0 })));
+        assert!(routed.contains(&(1, MouseEvent::Hover { col: 15, row: 0 })));
+
+        assert_eq!(hits.route(&button(KeyState::Released, 15, 0))[0].0, 0);
+        assert_eq!(hits.captured(), None);
+        assert_eq!(hits.route(&button(KeyState::Released, 15, 0))[0].0, 1);
+    }
+
+    #[test]
+    fn test_dispatch_ignores_other_events() {
+        let mut hits = HitMap::new();
+        hits.record(0, Rect::new(0, 0, 10, 1));
+        assert_eq!(hits.dispatch(&Event::resize(80, 24), |_, _| true), None);
+        assert_eq!(hits.dispatch(&button(KeyState::Pressed, 20, 0), |_, _| true), Some(false));
+        assert_eq!(hits.dispatch(&button(KeyState::Pressed, 2, 0), |_, _| true), Some(true));
+    }
+}
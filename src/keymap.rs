@@ -0,0 +1,747 @@
+//! Keymaps - declarative bindings from key sequences to named actions
+//!
+//! Bindings live in scopes: global, per modal `Mode`, per focused component,
+//! or per component in one mode. The keymap tracks the active mode and
+//! focus, and a binding in a narrower scope overrides the same keys bound
+//! in a wider one. Sequences are written in [key notation](crate::notation)
+//! (`<C-w>h`, `gg`, `<S-Tab>`) and may span several keys: while the keys
+//! typed so far start a longer binding the keymap reports
+//! `Resolution::Pending` and waits, up to a configurable timeout, for the
+//! rest. When the next key breaks the sequence, or the timeout passes, the
+//! longest prefix that is bound fires and the keys after it are resolved
+//! again, the way vim does.
+//!
+//! Keymaps can be loaded from a config string:
+//!
+//! ```text
+//! # Applies everywhere
+//! timeout 800
+//! bind <C-q> app.quit
+//!
+//! [normal]
+//! bind gg list.first
+//!
+//! [@editor insert]
+//! bind <C-s> editor.save
+//! unbind <C-w>h
+//! ```
+//!
+//! Section headers name a mode (`normal`, `visual`, `visual-line`,
+//! `visual-block`, `insert`, `replace`, `command`, `search`,
+//! `search-backward`), a component (`@id`), both, or `global`.
+
+use crate::event::{EventKind, Key, KeyState, Modifiers};
+use crate::focus::ComponentId;
+use crate::modal::{Mode, SearchDirection, VisualMode};
+use crate::notation;
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How long a pending prefix waits for the next key by default
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// One key with its modifiers, e.g. `<C-w>` or `h`
+///
+/// Characters carry their own case, so shift is dropped for them, and
+/// Ctrl-letters are stored lowercase the way terminals report them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    /// Which key
+    pub key: Key,
+    /// Modifier keys held
+    pub modifiers: Modifiers,
+}
+
+impl KeyChord {
+    /// Create a normalized chord
+    pub fn new(key: Key, mut modifiers: Modifiers) -> Self {
+        let key = match key {
+            Key::Char(' ') => Key::Space,
+            Key::Char(c) if modifiers.ctrl => Key::Char(c.to_ascii_lowercase()),
+            key => key,
+        };
+        if matches!(key, Key::Char(_)) {
+            modifiers.shift = false;
+        }
+        KeyChord { key, modifiers }
+    }
+
+    /// Chord for a key press or repeat; `None` for releases and other events
+    pub fn from_event(event: &EventKind) -> Option<Self> {
+        match event {
+            EventKind::Key {
+                key,
+                state: KeyState::Pressed | KeyState::Repeat,
+                modifiers,
+                ..
+            } => Some(KeyChord::new(*key, *modifiers)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        notation::write_key(f, self.key, self.modifiers)
+    }
+}
+
+/// Parse a key sequence in vim notation, e.g. `<C-w>h` or `<S-Tab>`
+///
+/// See [`notation`] for the syntax.
+pub fn parse_keys(text: &str) -> Result<Vec<KeyChord>> {
+    let chords: Vec<KeyChord> = notation::parse_keys(text)
+        .with_context(|| format!("Invalid key sequence {:?}", text))?
+        .into_iter()
+        .map(|(key, modifiers)| KeyChord::new(key, modifiers))
+        .collect();
+    if chords.is_empty() {
+        bail!("Empty key sequence");
+    }
+    Ok(chords)
+}
+
+/// Format a key sequence in vim notation
+pub fn format_keys(keys: &[KeyChord]) -> String {
+    keys.iter().map(|chord| chord.to_string()).collect()
+}
+
+/// Where a binding applies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    /// Everywhere
+    Global,
+    /// In one modal mode
+    Mode(Mode),
+    /// While a component has focus
+    Component(ComponentId),
+    /// While a component has focus and the editor is in one mode
+    ComponentMode(ComponentId, Mode),
+}
+
+impl Scope {
+    /// Rank among the active scopes; higher overrides lower
+    fn precedence(&self, mode: Mode, focus: Option<&ComponentId>) -> Option<u8> {
+        match self {
+            Scope::Global => Some(0),
+            Scope::Mode(m) => (*m == mode).then_some(1),
+            Scope::Component(id) => (Some(id) == focus).then_some(2),
+            Scope::ComponentMode(id, m) => (Some(id) == focus && *m == mode).then_some(3),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scope::Global => f.write_str("global"),
+            Scope::Mode(mode) => f.write_str(mode_name(*mode)),
+            Scope::Component(id) => write!(f, "@{}", id),
+            Scope::ComponentMode(id, mode) => write!(f, "@{} {}", id, mode_name(*mode)),
+        }
+    }
+}
+
+/// A problem found while building a keymap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// The same keys were bound twice in one scope; the later binding won
+    Rebound {
+        /// Scope of both bindings
+        scope: Scope,
+        /// The keys bound twice
+        keys: Vec<KeyChord>,
+        /// Action that was replaced
+        previous: String,
+        /// Action now bound
+        action: String,
+    },
+    /// One binding's keys begin another's, so the shorter one only fires
+    /// after the timeout
+    Prefix {
+        /// Scope of the shorter binding
+        scope: Scope,
+        /// The shorter binding's keys
+        prefix: Vec<KeyChord>,
+        /// The longer binding's keys
+        keys: Vec<KeyChord>,
+    },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Conflict::Rebound {
+                scope,
+                keys,
+                previous,
+                action,
+            } => write!(
+                f,
+                "[{}] {} rebound from {} to {}",
+                scope,
+                format_keys(keys),
+                previous,
+                action
+            ),
+            Conflict::Prefix {
+                scope,
+                prefix,
+                keys,
+            } => write!(
+                f,
+                "[{}] {} is a prefix of {} and waits for the timeout",
+                scope,
+                format_keys(prefix),
+                format_keys(keys)
+            ),
+        }
+    }
+}
+
+/// Outcome of feeding a key to the keymap
+///
+/// One key can resolve several ways in turn, e.g. `gx` with `g` bound fires
+/// `g` and then hands back `x`, so the keymap returns them in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// The keys completed a binding
+    Action(String),
+    /// The keys so far begin a longer binding; waiting for more
+    Pending,
+    /// No binding matched; the keys, including any that were held as a
+    /// pending prefix, are handed back for normal handling
+    Unmatched(Vec<KeyChord>),
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    scope: Scope,
+    keys: Vec<KeyChord>,
+    action: String,
+}
+
+/// Layered key bindings with multi-key sequence matching
+///
+/// # Example
+///
+/// ```
+/// use mkui::keymap::{Keymap, Resolution, Scope};
+/// use mkui::{Event, Key};
+///
+/// let mut keymap = Keymap::parse("bind <C-w>h window.left").unwrap();
+/// let ctrl_w = Event::key_with_mods(Key::Char('w'), mkui::event::Modifiers {
+///     ctrl: true,
+///     ..Default::default()
+/// });
+/// assert_eq!(keymap.handle(&ctrl_w.kind), [Resolution::Pending]);
+/// let h = Event::key(Key::Char('h'));
+/// assert_eq!(keymap.handle(&h.kind), [Resolution::Action("window.left".into())]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    rebound: Vec<Conflict>,
+    timeout: Duration,
+    mode: Mode,
+    focus: Option<ComponentId>,
+    pending: Vec<KeyChord>,
+    pending_since: Option<Instant>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keymap {
+    /// Create an empty keymap in Normal mode with no focused component
+    pub fn new() -> Self {
+        Keymap {
+            bindings: Vec::new(),
+            rebound: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            mode: Mode::Normal,
+            focus: None,
+            pending: Vec::new(),
+            pending_since: None,
+        }
+    }
+
+    /// Parse a keymap from config text
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut keymap = Keymap::new();
+        keymap.load(text)?;
+        Ok(keymap)
+    }
+
+    /// Add the bindings in config text, overriding existing ones
+    pub fn load(&mut self, text: &str) -> Result<()> {
+        let mut scope = Scope::Global;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            self.load_line(line, &mut scope)
+                .with_context(|| format!("Keymap line {}", index + 1))?;
+        }
+        Ok(())
+    }
+
+    fn load_line(&mut self, line: &str, scope: &mut Scope) -> Result<()> {
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.strip_suffix(']').context("Unclosed section header")?;
+            *scope = parse_scope(header.trim())?;
+            return Ok(());
+        }
+
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next(), words.next(), words.next()) {
+            (Some("bind"), Some(keys), Some(action), None) => {
+                self.bind(scope.clone(), keys, action)?;
+            }
+            (Some("unbind"), Some(keys), None, None) => {
+                self.unbind(scope, keys)?;
+            }
+            (Some("timeout"), Some(millis), None, None) => {
+                let millis = millis
+                    .parse()
+                    .ok()
+                    .with_context(|| format!("Bad timeout {:?}", millis))?;
+                self.timeout = Duration::from_millis(millis);
+            }
+            _ => bail!("Expected `bind <keys> <action>`, `unbind <keys>`, or `timeout <ms>`"),
+        }
+        Ok(())
+    }
+
+    /// Set how long a pending prefix waits for the next key
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How long a pending prefix waits for the next key
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Bind a key sequence to an action in a scope
+    ///
+    /// Binding keys already bound in the same scope replaces the action and
+    /// is reported by `conflicts`.
+    pub fn bind(&mut self, scope: Scope, keys: &str, action: &str) -> Result<()> {
+        let keys = parse_keys(keys)?;
+        match self
+            .bindings
+            .iter_mut()
+            .find(|binding| binding.scope == scope && binding.keys == keys)
+        {
+            Some(binding) => {
+                if binding.action != action {
+                    self.rebound.push(Conflict::Rebound {
+                        scope,
+                        keys,
+                        previous: std::mem::replace(&mut binding.action, action.to_string()),
+                        action: action.to_string(),
+                    });
+                }
+            }
+            None => self.bindings.push(Binding {
+                scope,
+                keys,
+                action: action.to_string(),
+            }),
+        }
+        Ok(())
+    }
+
+    /// Remove a binding, returning whether one existed
+    pub fn unbind(&mut self, scope: &Scope, keys: &str) -> Result<bool> {
+        let keys = parse_keys(keys)?;
+        let before = self.bindings.len();
+        self.bindings
+            .retain(|binding| !(binding.scope == *scope && binding.keys == keys));
+        Ok(self.bindings.len() != before)
+    }
+
+    /// Action bound to a key sequence in a scope, if any
+    pub fn action(&self, scope: &Scope, keys: &[KeyChord]) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.scope == *scope && binding.keys == keys)
+            .map(|binding| binding.action.as_str())
+    }
+
+    /// Rebinds made so far, then every binding that is a prefix of another
+    /// active in the same mode and focus
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = self.rebound.clone();
+        for short in &self.bindings {
+            for long in &self.bindings {
+                if long.keys.len() > short.keys.len()
+                    && long.keys.starts_with(&short.keys)
+                    && scopes_overlap(&short.scope, &long.scope)
+                {
+                    conflicts.push(Conflict::Prefix {
+                        scope: short.scope.clone(),
+                        prefix: short.keys.clone(),
+                        keys: long.keys.clone(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The active modal mode
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Switch the mode whose bindings apply, dropping any pending keys
+    pub fn set_mode(&mut self, mode: Mode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.clear_pending();
+        }
+    }
+
+    /// The component whose bindings apply, if any
+    pub fn focus(&self) -> Option<&ComponentId> {
+        self.focus.as_ref()
+    }
+
+    /// Switch the focused component, dropping any pending keys
+    pub fn set_focus(&mut self, focus: Option<ComponentId>) {
+        if focus != self.focus {
+            self.focus = focus;
+            self.clear_pending();
+        }
+    }
+
+    /// Keys typed so far of an incomplete sequence
+    pub fn pending(&self) -> &[KeyChord] {
+        &self.pending
+    }
+
+    /// Pending keys in vim notation, for a status line
+    pub fn pending_display(&self) -> String {
+        format_keys(&self.pending)
+    }
+
+    /// Forget any pending keys
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+        self.pending_since = None;
+    }
+
+    /// Time left before the pending prefix expires, to bound an event poll
+    pub fn time_until_timeout(&self) -> Option<Duration> {
+        self.pending_since
+            .map(|since| self.timeout.saturating_sub(since.elapsed()))
+    }
+
+    /// Feed a key event
+    ///
+    /// Events other than key presses and repeats resolve to nothing.
+    pub fn handle(&mut self, event: &EventKind) -> Vec<Resolution> {
+        match KeyChord::from_event(event) {
+            Some(chord) => self.handle_chord(chord, Instant::now()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Feed one chord that arrived at `now`
+    ///
+    /// A pending prefix that has already timed out is resolved first, as
+    /// `expire` would have, so the chord starts a fresh sequence.
+    pub fn handle_chord(&mut self, chord: KeyChord, now: Instant) -> Vec<Resolution> {
+        let mut resolved = self.expire(now);
+        self.pending.push(chord);
+        for resolution in self.resolve(now, false) {
+            push_resolution(&mut resolved, resolution);
+        }
+        resolved
+    }
+
+    /// Resolve a pending prefix whose timeout has passed by `now`
+    ///
+    /// Fires the longest bound prefix of the keys typed so far and resolves
+    /// the rest again; keys that no binding starts with are handed back as
+    /// `Unmatched`. Returns nothing while still waiting or when nothing is
+    /// pending.
+    pub fn expire(&mut self, now: Instant) -> Vec<Resolution> {
+        if !self.expired(now) {
+            return Vec::new();
+        }
+        self.resolve(now, true)
+    }
+
+    /// Resolve the pending keys, waiting on a longer binding unless `flush`
+    fn resolve(&mut self, now: Instant, mut flush: bool) -> Vec<Resolution> {
+        let mut resolved = Vec::new();
+        while !self.pending.is_empty() {
+            let (exact, longer) = self.lookup(&self.pending);
+            if longer && !flush {
+                self.pending_since = Some(now);
+                push_resolution(&mut resolved, Resolution::Pending);
+                return resolved;
+            }
+            if let Some(action) = exact {
+                self.pending.clear();
+                push_resolution(&mut resolved, Resolution::Action(action));
+                break;
+            }
+
+            // The sequence broke: fire the longest prefix that is bound, or
+            // hand back the first key, and go again with the keys after it
+            let prefix = (1..self.pending.len()).rev().find_map(|len| {
+                let (exact, _) = self.lookup(&self.pending[..len]);
+                exact.map(|action| (len, action))
+            });
+            let (len, resolution) = match prefix {
+                Some((len, action)) => (len, Resolution::Action(action)),
+                None => (1, Resolution::Unmatched(self.pending[..1].to_vec())),
+            };
+            self.pending.drain(..len);
+            push_resolution(&mut resolved, resolution);
+            // The rest get a fresh timeout
+            flush = false;
+        }
+        self.pending_since = None;
+        resolved
+    }
+
+    fn expired(&self, now: Instant) -> bool {
+        self.pending_since
+            .is_some_and(|since| now.saturating_duration_since(since) >= self.timeout)
+    }
+
+    /// The highest-precedence action bound to exactly `keys`, and whether
+    /// any active binding continues past them
+    fn lookup(&self, keys: &[KeyChord]) -> (Option<String>, bool) {
+        let mut exact: Option<(u8, &str)> = None;
+        let mut longer = false;
+        for binding in &self.bindings {
+            let Some(rank) = binding.scope.precedence(self.mode, self.focus.as_ref()) else {
+                continue;
+            };
+            if binding.keys == keys {
+                if exact.is_none_or(|(best, _)| rank > best) {
+                    exact = Some((rank, &binding.action));
+                }
+            } else if binding.keys.len() > keys.len() && binding.keys.starts_with(keys) {
+                longer = true;
+            }
+        }
+        (exact.map(|(_, action)| action.to_string()), longer)
+    }
+}
+
+/// Append a resolution, merging keys handed back one after another
+fn push_resolution(resolved: &mut Vec<Resolution>, resolution: Resolution) {
+    // Only the last step can still be waiting
+    if resolved.last() == Some(&Resolution::Pending) {
+        resolved.pop();
+    }
+    match (resolved.last_mut(), resolution) {
+        (Some(Resolution::Unmatched(keys)), Resolution::Unmatched(more)) => keys.extend(more),
+        (_, resolution) => resolved.push(resolution),
+    }
+}
+
+/// Whether some mode and focus activate both scopes
+fn scopes_overlap(a: &Scope, b: &Scope) -> bool {
+    let (a_id, a_mode) = scope_parts(a);
+    let (b_id, b_mode) = scope_parts(b);
+    let ids = a_id.is_none() || b_id.is_none() || a_id == b_id;
+    let modes = a_mode.is_none() || b_mode.is_none() || a_mode == b_mode;
+    ids && modes
+}
+
+fn scope_parts(scope: &Scope) -> (Option<&ComponentId>, Option<Mode>) {
+    match scope {
+        Scope::Global => (None, None),
+        Scope::Mode(mode) => (None, Some(*mode)),
+        Scope::Component(id) => (Some(id), None),
+        Scope::ComponentMode(id, mode) => (Some(id), Some(*mode)),
+    }
+}
+
+/// Parse a section header: `global`, a mode, `@component`, or both
+fn parse_scope(header: &str) -> Result<Scope> {
+    let mut words = header.split_whitespace();
+    let scope = match (words.next(), words.next(), words.next()) {
+        (Some("global"), None, None) => Scope::Global,
+        (Some(first), second, None) => match (first.strip_prefix('@'), second) {
+            (Some(id), None) => Scope::Component(id.into()),
+            (Some(id), Some(mode)) => Scope::ComponentMode(id.into(), parse_mode(mode)?),
+            (None, None) => Scope::Mode(parse_mode(first)?),
+            (None, Some(_)) => bail!("Expected `[mode]`, `[@component]`, or `[@component mode]`"),
+        },
+        _ => bail!("Expected `[mode]`, `[@component]`, or `[@component mode]`"),
+    };
+    Ok(scope)
+}
+
+fn parse_mode(name: &str) -> Result<Mode> {
+    Ok(match name {
+        "normal" => Mode::Normal,
+        "visual" => Mode::Visual(VisualMode::Character),
+        "visual-line" => Mode::Visual(VisualMode::Line),
+        "visual-block" => Mode::Visual(VisualMode::Block),
+        "insert" => Mode::Insert,
+        "replace" => Mode::Replace,
+        "command" => Mode::Command,
+        "search" => Mode::Search(SearchDirection::Forward),
+        "search-backward" => Mode::Search(SearchDirection::Backward),
+        other => bail!("Unknown mode {:?}", other),
+    })
+}
+
+fn mode_name(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Normal => "normal",
+        Mode::Visual(VisualMode::Character) => "visual",
+        Mode::Visual(VisualMode::Line) => "visual-line",
+        Mode::Visual(VisualMode::Block) => "visual-block",
+        Mode::Insert => "insert",
+        Mode::Replace => "replace",
+        Mode::Command => "command",
+        Mode::Search(SearchDirection::Forward) => "search",
+        Mode::Search(SearchDirection::Backward) => "search-backward",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> KeyChord {
+        parse_keys(text).unwrap()[0]
+    }
+
+    /// Feed `keys`, returning what the last of them resolved to
+    fn feed(keymap: &mut Keymap, keys: &str, now: Instant) -> Vec<Resolution> {
+        let mut result = Vec::new();
+        for chord in parse_keys(keys).unwrap() {
+            result = keymap.handle_chord(chord, now);
+        }
+        result
+    }
+
+    fn action(name: &str) -> Resolution {
+        Resolution::Action(name.into())
+    }
+
+    #[test]
+    fn test_parse_and_format_keys() {
+        let keys = parse_keys("<C-w>h<S-Tab><lt><C-A-F5>G").unwrap();
+        assert_eq!(keys.len(), 6);
+        assert_eq!(keys[0], KeyChord::new(Key::Char('w'), Modifiers { ctrl: true, ..Modifiers::none() }));
+        assert_eq!(format_keys(&keys), "<C-w>h<S-Tab><lt><C-A-F5>G");
+        assert_eq!(chord("<C-W>"), chord("<c-w>"));
+        assert_eq!(chord(" "), chord("<Space>"));
+        assert!(parse_keys("<C-w").is_err());
+        assert!(parse_keys("<Nope>").is_err());
+    }
+
+    #[test]
+    fn test_sequence_resolves_after_prefix() {
+        let mut keymap = Keymap::parse("bind <C-w>h window.left").unwrap();
+        let now = Instant::now();
+        assert_eq!(keymap.handle_chord(chord("<C-w>"), now), [Resolution::Pending]);
+        assert_eq!(keymap.pending_display(), "<C-w>");
+        assert_eq!(keymap.handle_chord(chord("h"), now), [action("window.left")]);
+        assert!(keymap.pending().is_empty());
+
+        // A wrong second key hands both back
+        assert_eq!(
+            feed(&mut keymap, "<C-w>x", now),
+            [Resolution::Unmatched(parse_keys("<C-w>x").unwrap())]
+        );
+
+        // A key that breaks the sequence can start the next one
+        assert_eq!(
+            feed(&mut keymap, "<C-w><C-w>", now),
+            [Resolution::Unmatched(vec![chord("<C-w>")]), Resolution::Pending]
+        );
+        assert_eq!(keymap.handle_chord(chord("h"), now), [action("window.left")]);
+    }
+
+    #[test]
+    fn test_broken_sequence_fires_longest_prefix() {
+        let mut keymap = Keymap::parse("bind g go
+bind gg top
+bind gqq format
+bind x cut").unwrap();
+        let now = Instant::now();
+        assert_eq!(feed(&mut keymap, "gy", now), [action("go"), Resolution::Unmatched(vec![chord("y")])]);
+        assert_eq!(feed(&mut keymap, "gx", now), [action("go"), action("cut")]);
+        assert_eq!(feed(&mut keymap, "gqg", now), [action("go"), Resolution::Unmatched(vec![chord("q")]), Resolution::Pending]);
+        assert_eq!(keymap.handle_chord(chord("g"), now), [action("top")]);
+    }
+
+    #[test]
+    fn test_timeout_fires_shorter_binding() {
+        let mut keymap = Keymap::parse("bind g go\nbind gg top").unwrap().with_timeout(Duration::from_millis(500));
+        let start = Instant::now();
+        assert_eq!(keymap.handle_chord(chord("g"), start), [Resolution::Pending]);
+        assert_eq!(keymap.expire(start + Duration::from_millis(100)), []);
+        assert_eq!(keymap.expire(start + Duration::from_millis(500)), [action("go")]);
+        assert_eq!(keymap.expire(start + Duration::from_secs(1)), []);
+
+        // Without polling, a late key fires the prefix and then starts over
+        keymap.handle_chord(chord("g"), start);
+        assert_eq!(
+            keymap.handle_chord(chord("x"), start + Duration::from_secs(1)),
+            [action("go"), Resolution::Unmatched(vec![chord("x")])]
+        );
+    }
+
+    #[test]
+    fn test_layers_by_mode_and_focus() {
+        let mut keymap = Keymap::parse(
+            "bind <C-s> app.save\n[insert]\nbind <C-s> insert.save\n[@list]\nbind j list.down\n[@list insert]\nbind <C-s> list.save",
+        )
+        .unwrap();
+        let now = Instant::now();
+        assert_eq!(feed(&mut keymap, "<C-s>", now), [action("app.save")]);
+        assert_eq!(feed(&mut keymap, "j", now), [Resolution::Unmatched(vec![chord("j")])]);
+
+        keymap.set_mode(Mode::Insert);
+        assert_eq!(feed(&mut keymap, "<C-s>", now), [action("insert.save")]);
+        keymap.set_focus(Some("list".into()));
+        assert_eq!(feed(&mut keymap, "<C-s>", now), [action("list.save")]);
+        assert_eq!(feed(&mut keymap, "j", now), [action("list.down")]);
+    }
+
+    #[test]
+    fn test_conflicts_reported() {
+        let keymap = Keymap::parse("bind q quit\nbind q close\n[normal]\nbind qa quit-all\n[insert]\nbind qq nothing")
+            .unwrap();
+        let conflicts: Vec<String> = keymap.conflicts().iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            conflicts,
+            [
+                "[global] q rebound from quit to close",
+                "[global] q is a prefix of qa and waits for the timeout",
+                "[global] q is a prefix of qq and waits for the timeout",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_errors_name_the_line() {
+        let err = Keymap::parse("bind a b\n[sideways]").unwrap_err();
+        assert_eq!(err.to_string(), "Keymap line 2");
+        assert!(Keymap::parse("bind <C-x>").is_err());
+        let keymap = Keymap::parse("timeout 250\nbind a b\nunbind a").unwrap();
+        assert_eq!(keymap.timeout(), Duration::from_millis(250));
+        assert!(keymap.action(&Scope::Global, &parse_keys("a").unwrap()).is_none());
+    }
+}
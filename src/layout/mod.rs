@@ -48,6 +48,15 @@ impl Rect {
         x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
     }
 
+    /// Overlapping area of two rectangles (zero-sized when they don't overlap)
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right()).max(x);
+        let bottom = self.bottom().min(other.bottom()).max(y);
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
     /// Create a subrect with padding applied
     pub fn inner(&self, padding: u16) -> Self {
         let padding2 = padding.saturating_mul(2);
@@ -293,11 +302,7 @@ impl FlexLayout {
         }
 
         let flex_space = available.saturating_sub(fixed_space);
-        if flex_units > 0 {
-            flex_space / flex_units
-        } else {
-            0
-        }
+        flex_space.checked_div(flex_units).unwrap_or(0)
     }
 
     fn child_rect(&self, inner: &Rect, offset: u16, child_main_size: u16) -> Rect {
@@ -352,6 +357,14 @@ mod tests {
         assert!(!r.contains(5, 15)); // left of rect
     }
 
+    #[test]
+    fn test_rect_intersection() {
+        let a = Rect::new(0, 0, 10, 5);
+        assert_eq!(a.intersection(&Rect::new(4, 2, 10, 10)), Rect::new(4, 2, 6, 3));
+        let disjoint = a.intersection(&Rect::new(20, 0, 5, 5));
+        assert_eq!((disjoint.width, disjoint.height), (0, 5));
+    }
+
     #[test]
     fn test_rect_split_horizontal() {
         let r = Rect::new(0, 0, 80, 24);
@@ -15,27 +15,34 @@ pub mod component;
 pub mod component_state;
 pub mod components;
 pub mod context;
+pub mod drag;
 pub mod event;
 pub mod focus;
 #[cfg(feature = "tui")]
 pub mod graphics;
 #[cfg(feature = "gui")]
 pub mod gui;
+pub mod hit;
 pub mod i18n;
+pub mod keymap;
 pub mod layout;
 pub mod modal;
+pub mod notation;
 pub mod render;
+pub mod replay;
 pub mod signal;
 pub mod slots;
 pub mod style;
 #[cfg(feature = "tui")]
 pub mod terminal;
+pub mod testing;
 pub mod theme;
 #[cfg(feature = "tui")]
 pub mod tui;
+pub mod unicode;
 
 // Re-export commonly used types
-pub use app::App;
+pub use app::{App, RunOptions};
 pub use component::Component;
 pub use component_state::RenderTracker;
 pub use components::{
@@ -44,24 +51,36 @@ pub use components::{
     SelectionMode, SplitDirection, SplitView, TextInput,
 };
 pub use context::{RenderContext, UseAccessibility, UseLocale, UseTheme};
-pub use event::{DoubleClickDetector, Event, EventHandler, EventKind, Key, RawEvent};
+pub use drag::{DragAndDrop, DragTracker};
+pub use event::{DoubleClickDetector, Event, EventHandler, EventKind, EventSource, Key, RawEvent};
 #[cfg(feature = "tui")]
 pub use event::{EventPoller, FrameTimer};
 pub use focus::{ComponentId, FocusDirection, FocusManager, FocusableInfo};
 #[cfg(feature = "tui")]
 pub use graphics::GraphicsBackend;
+pub use hit::HitMap;
 pub use i18n::{AccessibilityRole, AccessibilitySettings, Locale, TextDirection};
+pub use keymap::{KeyChord, Keymap};
+pub use notation::{format_key, parse_key, NotationError};
 pub use layout::{LeafId, ObjectFit, Rect, SplitTree};
 pub use modal::{
     KeyResult, ModalHandler, ModalState, Mode, Motion, Operator, SearchDirection, VisualMode,
 };
-pub use render::{DirtyRegion, ImageParams, Renderer};
+pub use render::{
+    Cell, CellGrid, ClipStack, CursorShape, CursorStyle, DirtyRegion, ImageParams, LayerStack,
+    Progress, Renderer, Symbol, BASE_LAYER, MAX_DIRTY_RECTS,
+};
 pub use signal::{Binding, Signal, SignalBase};
 #[cfg(feature = "tui")]
-pub use tui::TerminalRenderer;
+pub use tui::{EventReader, FrameStats, InputParser, TerminalGuard, TerminalRenderer, Viewport};
 pub use slots::{header_slots, priority, status_slots, RegionSlots, SlotContent, Slots, UseSlots};
-pub use style::{Selector, Style, StyleProperty, StyleRule, StyleSheet, Styleable};
+pub use style::{
+    Hyperlink, Selector, Style, StyleProperty, StyleRule, StyleSheet, Styleable, UnderlineStyle,
+};
+pub use testing::{Snapshot, StyledSpan, TestRenderer};
 #[cfg(feature = "tui")]
-pub use terminal::{TerminalCapabilities, TerminalContext, TerminalGeometry, TmuxPaneInfo};
+pub use terminal::{
+    NotificationProtocol, TerminalCapabilities, TerminalContext, TerminalGeometry, TmuxPaneInfo,
+};
 pub use theme::{BorderChars, BorderStyle, Color, Theme};
 pub use theme::color::{parse_rgb, parse_rgba};
@@ -11,6 +11,9 @@ mod motions;
 
 pub use motions::{Motion, Operator};
 
+use crate::event::{Key, Modifiers};
+use crate::notation;
+use crate::render::CursorShape;
 use std::collections::HashMap;
 
 /// Operating mode for modal editing
@@ -23,6 +26,8 @@ pub enum Mode {
     Visual(VisualMode),
     /// Text insertion mode
     Insert,
+    /// Overtype mode (`R`)
+    Replace,
     /// Command-line input mode (`:` prefix)
     Command,
     /// Incremental search mode
@@ -43,11 +48,23 @@ impl Mode {
             Mode::Visual(VisualMode::Line) => "V-LINE",
             Mode::Visual(VisualMode::Block) => "V-BLOCK",
             Mode::Insert => "INSERT",
+            Mode::Replace => "REPLACE",
             Mode::Command => "COMMAND",
             Mode::Search(SearchDirection::Forward) => "SEARCH",
             Mode::Search(SearchDirection::Backward) => "SEARCH?",
         }
     }
+
+    /// Returns the cursor shape vim uses for this mode
+    ///
+    /// Pass it to `Renderer::set_cursor_style` whenever the mode changes.
+    pub fn cursor_shape(&self) -> CursorShape {
+        match self {
+            Mode::Normal | Mode::Visual(_) => CursorShape::Block,
+            Mode::Insert | Mode::Command | Mode::Search(_) => CursorShape::Bar,
+            Mode::Replace => CursorShape::Underline,
+        }
+    }
 }
 
 /// Visual selection mode type
@@ -132,6 +149,11 @@ impl ModalState {
         self.set_mode(Mode::Insert);
     }
 
+    /// Transitions to Replace mode
+    pub fn enter_replace(&mut self) {
+        self.set_mode(Mode::Replace);
+    }
+
     /// Transitions to character-wise Visual mode
     pub fn enter_visual(&mut self) {
         self.set_mode(Mode::Visual(VisualMode::Character));
@@ -200,14 +222,22 @@ impl ModalState {
         self.pending_operator.is_some()
     }
 
-    /// Returns the buffered incomplete key sequence
+    /// Returns the buffered incomplete key sequence in key notation
+    ///
+    /// Parse it back with [`notation::parse_keys`].
     pub fn pending_keys(&self) -> &str {
         &self.pending_keys
     }
 
     /// Appends a character to the pending key buffer
     pub fn push_pending_key(&mut self, c: char) {
-        self.pending_keys.push(c);
+        self.push_pending_chord(Key::Char(c), Modifiers::none());
+    }
+
+    /// Appends a key with modifiers (e.g. `<C-w>`) to the pending key buffer
+    pub fn push_pending_chord(&mut self, key: Key, modifiers: Modifiers) {
+        notation::write_key(&mut self.pending_keys, key, modifiers)
+            .expect("writing to a String cannot fail");
     }
 
     /// Clears the pending key buffer
@@ -396,6 +426,15 @@ mod tests {
         state.push_pending_key('i');
 
         assert_eq!(state.status(), "3di");
+
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::none()
+        };
+        state.push_pending_chord(Key::Char('w'), ctrl);
+        state.push_pending_key('<');
+        assert_eq!(state.pending_keys(), "i<C-w><lt>");
+        assert_eq!(notation::parse_keys(state.pending_keys()).unwrap().len(), 3);
     }
 
     #[test]
@@ -423,5 +462,16 @@ mod tests {
         assert_eq!(Mode::Visual(VisualMode::Line).name(), "V-LINE");
         assert_eq!(Mode::Visual(VisualMode::Block).name(), "V-BLOCK");
         assert_eq!(Mode::Command.name(), "COMMAND");
+        assert_eq!(Mode::Replace.name(), "REPLACE");
+    }
+
+    #[test]
+    fn test_mode_cursor_shapes() {
+        let mut state = ModalState::new();
+        assert_eq!(state.mode().cursor_shape(), CursorShape::Block);
+        state.enter_insert();
+        assert_eq!(state.mode().cursor_shape(), CursorShape::Bar);
+        state.enter_replace();
+        assert_eq!(state.mode().cursor_shape(), CursorShape::Underline);
     }
 }
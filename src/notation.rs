@@ -0,0 +1,366 @@
+//! Key notation - vim-style names for keys, e.g. `<C-w>`, `<S-Tab>`, `gg`
+//!
+//! This is the one textual form for keys: keymap configs are read with it,
+//! and help screens, status bars, and error messages show keys with it.
+//! Plain characters stand for themselves; everything else is written
+//! between angle brackets as optional modifier prefixes followed by a key
+//! name:
+//!
+//! | Prefix | Modifier |
+//! |--------|----------|
+//! | `C-`   | ctrl     |
+//! | `A-`, `M-` | alt  |
+//! | `S-`   | shift    |
+//! | `D-`   | super    |
+//! | `H-`   | hyper    |
+//!
+//! Key names are `CR`, `Esc`, `Tab`, `BS`, `Del`, `Insert`, `Home`, `End`,
+//! `PageUp`, `PageDown`, `Up`, `Down`, `Left`, `Right`, `Space`, `Nul`, and
+//! `F1`...; `lt`, `Bar`, and `Bslash` name `<`, `|`, and `\`, and `Char-N`
+//! names any character by code point (decimal or `0x` hex). Names and
+//! prefixes are case-insensitive, but a single character keeps its case.
+//!
+//! [`format_key`] always produces the canonical spelling, and parsing it
+//! gives back exactly the key and modifiers it was made from.
+
+use crate::event::{Key, Modifiers};
+use std::fmt;
+
+/// A key sequence that could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotationError {
+    /// Character offset of the problem in the parsed text
+    pub position: usize,
+    /// What went wrong
+    pub message: String,
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+/// Parse a key sequence, e.g. `<C-w>h` or `gg`
+///
+/// A literal space reads as `Key::Space`, the key terminals report for the
+/// space bar.
+pub fn parse_keys(text: &str) -> Result<Vec<(Key, Modifiers)>, NotationError> {
+    let mut parser = Parser { text, offset: 0 };
+    let mut keys = Vec::new();
+    while !parser.at_end() {
+        keys.push(parser.key()?);
+    }
+    Ok(keys)
+}
+
+/// Parse exactly one key, e.g. `<S-Tab>`
+pub fn parse_key(text: &str) -> Result<(Key, Modifiers), NotationError> {
+    let mut parser = Parser { text, offset: 0 };
+    if parser.at_end() {
+        return Err(parser.error(0, "Empty key"));
+    }
+    let key = parser.key()?;
+    if !parser.at_end() {
+        return Err(parser.error(parser.offset, "Expected a single key"));
+    }
+    Ok(key)
+}
+
+/// Canonical notation for one key
+pub fn format_key(key: Key, modifiers: Modifiers) -> String {
+    let mut out = String::new();
+    write_key(&mut out, key, modifiers).expect("writing to a String cannot fail");
+    out
+}
+
+/// Canonical notation for a key sequence
+pub fn format_keys<'a>(keys: impl IntoIterator<Item = &'a (Key, Modifiers)>) -> String {
+    let mut out = String::new();
+    for &(key, modifiers) in keys {
+        write_key(&mut out, key, modifiers).expect("writing to a String cannot fail");
+    }
+    out
+}
+
+/// Write the canonical notation for one key
+pub fn write_key(out: &mut impl fmt::Write, key: Key, modifiers: Modifiers) -> fmt::Result {
+    let name = match key {
+        Key::Char(c) if !modifiers.any() && is_literal(c) => return out.write_char(c),
+        Key::Char('<') => "lt".to_string(),
+        Key::Char(c) if is_literal(c) => c.to_string(),
+        Key::Char(c) => format!("Char-{}", c as u32),
+        Key::F(n) => format!("F{}", n),
+        Key::Up => "Up".into(),
+        Key::Down => "Down".into(),
+        Key::Left => "Left".into(),
+        Key::Right => "Right".into(),
+        Key::Home => "Home".into(),
+        Key::End => "End".into(),
+        Key::PageUp => "PageUp".into(),
+        Key::PageDown => "PageDown".into(),
+        Key::Backspace => "BS".into(),
+        Key::Delete => "Del".into(),
+        Key::Insert => "Insert".into(),
+        Key::Enter => "CR".into(),
+        Key::Tab => "Tab".into(),
+        Key::Esc => "Esc".into(),
+        Key::Space => "Space".into(),
+        Key::Null => "Nul".into(),
+    };
+    out.write_char('<')?;
+    for (prefix, held) in [
+        ("C-", modifiers.ctrl),
+        ("A-", modifiers.alt),
+        ("S-", modifiers.shift),
+        ("D-", modifiers.super_key),
+        ("H-", modifiers.hyper),
+    ] {
+        if held {
+            out.write_str(prefix)?;
+        }
+    }
+    write!(out, "{}>", name)
+}
+
+/// Whether a character can stand for itself outside `<...>`
+///
+/// `<` would open a key name, and whitespace and control characters would
+/// be invisible or split config lines.
+fn is_literal(c: char) -> bool {
+    c != '<' && !c.is_whitespace() && !c.is_control()
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    /// Byte offset of the next unread character
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn at_end(&self) -> bool {
+        self.offset >= self.text.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.offset..].chars().next()
+    }
+
+    fn error(&self, offset: usize, message: impl Into<String>) -> NotationError {
+        NotationError {
+            position: self.text[..offset].chars().count(),
+            message: message.into(),
+        }
+    }
+
+    /// Read one plain character or `<...>` key
+    fn key(&mut self) -> Result<(Key, Modifiers), NotationError> {
+        let c = self.peek().expect("key() called at end of input");
+        let start = self.offset;
+        self.offset += c.len_utf8();
+        match c {
+            '<' => self.bracketed(start),
+            ' ' => Ok((Key::Space, Modifiers::none())),
+            c => Ok((Key::Char(c), Modifiers::none())),
+        }
+    }
+
+    /// Read the rest of a `<...>` key whose `<` is at `start`
+    fn bracketed(&mut self, start: usize) -> Result<(Key, Modifiers), NotationError> {
+        let mut modifiers = Modifiers::none();
+        loop {
+            let rest = &self.text[self.offset..];
+            let mut chars = rest.chars();
+            let (Some(letter), Some('-'), Some(_)) = (chars.next(), chars.next(), chars.next())
+            else {
+                break;
+            };
+            if !letter.is_ascii_alphabetic() {
+                break;
+            }
+            let held = match letter.to_ascii_lowercase() {
+                'c' => &mut modifiers.ctrl,
+                'a' | 'm' => &mut modifiers.alt,
+                's' => &mut modifiers.shift,
+                'd' => &mut modifiers.super_key,
+                'h' => &mut modifiers.hyper,
+                _ => {
+                    return Err(self.error(self.offset, format!("Unknown modifier '{}-'", letter)))
+                }
+            };
+            if *held {
+                return Err(self.error(self.offset, format!("Repeated modifier '{}-'", letter)));
+            }
+            *held = true;
+            self.offset += 2;
+        }
+
+        // The first character always belongs to the name, so `<C->>` is
+        // Ctrl with a literal '>'
+        let name_start = self.offset;
+        let first = match self.peek() {
+            Some('>') if self.text[name_start + 1..].find('>').is_none() => None,
+            Some(c) => Some(c),
+            None => None,
+        };
+        let Some(first) = first else {
+            return match self.peek() {
+                Some(_) => Err(self.error(name_start, "Empty key name")),
+                None => Err(self.error(start, "Unclosed '<'")),
+            };
+        };
+        let Some(len) = self.text[name_start + first.len_utf8()..].find('>') else {
+            return Err(self.error(start, "Unclosed '<'"));
+        };
+        let name = &self.text[name_start..name_start + first.len_utf8() + len];
+        self.offset = name_start + name.len() + 1;
+
+        let key = self
+            .named_key(name)
+            .ok_or_else(|| self.error(name_start, format!("Unknown key name '{}'", name)))?;
+        Ok((key, modifiers))
+    }
+
+    fn named_key(&self, name: &str) -> Option<Key> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(Key::Char(c));
+        }
+
+        let lower = name.to_ascii_lowercase();
+        let key = match lower.as_str() {
+            "cr" | "enter" | "return" => Key::Enter,
+            "esc" | "escape" => Key::Esc,
+            "tab" => Key::Tab,
+            "bs" | "backspace" => Key::Backspace,
+            "del" | "delete" => Key::Delete,
+            "insert" | "ins" => Key::Insert,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" => Key::PageUp,
+            "pagedown" => Key::PageDown,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "space" => Key::Space,
+            "nul" => Key::Null,
+            "lt" => Key::Char('<'),
+            "bar" => Key::Char('|'),
+            "bslash" => Key::Char('\\'),
+            _ => {
+                if let Some(code) = lower.strip_prefix("char-") {
+                    let code = match code.strip_prefix("0x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    return char::from_u32(code).map(Key::Char);
+                }
+                let n: u8 = lower.strip_prefix('f')?.parse().ok()?;
+                return (n > 0).then_some(Key::F(n));
+            }
+        };
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mods(ctrl: bool, alt: bool, shift: bool) -> Modifiers {
+        Modifiers {
+            ctrl,
+            alt,
+            shift,
+            ..Modifiers::none()
+        }
+    }
+
+    #[test]
+    fn test_parse_common_forms() {
+        assert_eq!(
+            parse_keys("<C-w>gg<S-Tab>").unwrap(),
+            [
+                (Key::Char('w'), mods(true, false, false)),
+                (Key::Char('g'), Modifiers::none()),
+                (Key::Char('g'), Modifiers::none()),
+                (Key::Tab, mods(false, false, true)),
+            ]
+        );
+        assert_eq!(parse_key("<a-cr>").unwrap(), (Key::Enter, mods(false, true, false)));
+        assert_eq!(parse_key("<M-Enter>").unwrap(), parse_key("<A-CR>").unwrap());
+        assert_eq!(parse_key("<F12>").unwrap(), (Key::F(12), Modifiers::none()));
+        assert_eq!(parse_key("<C->>").unwrap(), (Key::Char('>'), mods(true, false, false)));
+        assert_eq!(parse_key("<C-->").unwrap(), (Key::Char('-'), mods(true, false, false)));
+        assert_eq!(parse_key("<Char-0x41>").unwrap(), (Key::Char('A'), Modifiers::none()));
+        assert_eq!(parse_key(" ").unwrap(), (Key::Space, Modifiers::none()));
+        assert!(parse_keys("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors_report_position() {
+        let err = |text: &str| parse_keys(text).unwrap_err();
+        assert_eq!(err("ab<C-w").position, 2);
+        assert_eq!(err("ab<C-w").to_string(), "Unclosed '<' at position 2");
+        assert_eq!(err("x<C-Nope>").position, 4);
+        assert_eq!(err("é<Q-a>").to_string(), "Unknown modifier 'Q-' at position 2");
+        assert_eq!(err("<C-C-a>").position, 3);
+        assert_eq!(err("<>").message, "Empty key name");
+        assert_eq!(err("<F0>").position, 1);
+        assert_eq!(parse_key("ab").unwrap_err().position, 1);
+        assert_eq!(parse_key("").unwrap_err().position, 0);
+    }
+
+    #[test]
+    fn test_format_round_trips() {
+        let all = Modifiers {
+            shift: true,
+            ctrl: true,
+            alt: true,
+            super_key: true,
+            hyper: true,
+        };
+        let keys = [
+            Key::Char('a'),
+            Key::Char('A'),
+            Key::Char('<'),
+            Key::Char('>'),
+            Key::Char('-'),
+            Key::Char('|'),
+            Key::Char(' '),
+            Key::Char('\t'),
+            Key::Char('é'),
+            Key::F(1),
+            Key::F(24),
+            Key::Up,
+            Key::PageDown,
+            Key::Backspace,
+            Key::Delete,
+            Key::Insert,
+            Key::Enter,
+            Key::Tab,
+            Key::Esc,
+            Key::Space,
+            Key::Null,
+        ];
+        for key in keys {
+            for modifiers in [Modifiers::none(), mods(true, false, false), mods(false, true, true), all] {
+                let text = format_key(key, modifiers);
+                assert_eq!(parse_key(&text), Ok((key, modifiers)), "{:?}", text);
+            }
+        }
+
+        let sequence = [
+            (Key::Char('w'), mods(true, false, false)),
+            (Key::Char('<'), Modifiers::none()),
+            (Key::Char(' '), Modifiers::none()),
+            (Key::Enter, mods(false, true, false)),
+        ];
+        let text = format_keys(&sequence);
+        assert_eq!(text, "<C-w><lt><Char-32><A-CR>");
+        assert_eq!(parse_keys(&text).unwrap(), sequence);
+    }
+}
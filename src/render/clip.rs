@@ -0,0 +1,76 @@
+//! Clip stack - nested drawing bounds shared by rendering backends
+//!
+//! Each pushed rectangle is intersected with the one below it, so a child
+//! can never draw outside the area its parent was clipped to.
+
+use crate::layout::Rect;
+
+/// Stack of nested clip rectangles
+#[derive(Debug, Clone, Default)]
+pub struct ClipStack {
+    stack: Vec<Rect>,
+}
+
+impl ClipStack {
+    /// Create an empty stack (nothing is clipped)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push a clip rectangle, intersected with the current clip
+    pub fn push(&mut self, rect: Rect) {
+        let clip = match self.current() {
+            Some(top) => top.intersection(&rect),
+            None => rect,
+        };
+        self.stack.push(clip);
+    }
+
+    /// Pop the innermost clip rectangle
+    pub fn pop(&mut self) -> Option<Rect> {
+        self.stack.pop()
+    }
+
+    /// The active clip rectangle, if any
+    pub fn current(&self) -> Option<Rect> {
+        self.stack.last().copied()
+    }
+
+    /// Whether a cell lies inside the active clip
+    pub fn contains(&self, col: u16, row: u16) -> bool {
+        self.current().is_none_or(|clip| clip.contains(col, row))
+    }
+
+    /// Restrict a rectangle to the active clip
+    pub fn apply(&self, rect: Rect) -> Rect {
+        match self.current() {
+            Some(clip) => clip.intersection(&rect),
+            None => rect,
+        }
+    }
+
+    /// Drop every clip rectangle
+    pub fn clear(&mut self) {
+        self.stack.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_clips_intersect() {
+        let mut clips = ClipStack::new();
+        assert!(clips.contains(100, 100));
+
+        clips.push(Rect::new(0, 0, 10, 10));
+        clips.push(Rect::new(5, 5, 10, 10));
+        assert_eq!(clips.current(), Some(Rect::new(5, 5, 5, 5)));
+        assert!(!clips.contains(12, 6));
+
+        clips.pop();
+        assert!(clips.contains(2, 2));
+        assert_eq!(clips.apply(Rect::new(8, 0, 4, 4)), Rect::new(8, 0, 2, 4));
+    }
+}
@@ -0,0 +1,78 @@
+//! Cursor appearance - shape, blink, and color shared by rendering backends
+//!
+//! The terminal backend sends these as DECSCUSR and OSC 12 sequences; the
+//! GUI backend draws the shape itself.
+
+use crate::theme::Color;
+
+/// Shape of the text cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorShape {
+    /// Full-cell block, as in vim's Normal mode
+    #[default]
+    Block,
+    /// Line under the cell, as in vim's Replace mode
+    Underline,
+    /// Vertical bar at the left edge of the cell, as in vim's Insert mode
+    Bar,
+}
+
+/// Cursor shape, blink, and optional color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CursorStyle {
+    /// Shape of the cursor
+    pub shape: CursorShape,
+    /// Whether the cursor blinks
+    pub blinking: bool,
+    /// Cursor color, or `None` for the backend's default
+    pub color: Option<Color>,
+}
+
+impl CursorStyle {
+    /// Create a steady cursor of the given shape in the default color
+    pub fn new(shape: CursorShape) -> Self {
+        CursorStyle {
+            shape,
+            blinking: false,
+            color: None,
+        }
+    }
+
+    /// Set whether the cursor blinks
+    pub fn blinking(mut self, blinking: bool) -> Self {
+        self.blinking = blinking;
+        self
+    }
+
+    /// Set the cursor color
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// DECSCUSR parameter selecting this shape and blink state
+    pub fn decscusr(&self) -> u8 {
+        let steady = match self.shape {
+            CursorShape::Block => 2,
+            CursorShape::Underline => 4,
+            CursorShape::Bar => 6,
+        };
+        if self.blinking {
+            steady - 1
+        } else {
+            steady
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decscusr() {
+        assert_eq!(CursorStyle::new(CursorShape::Block).decscusr(), 2);
+        assert_eq!(CursorStyle::new(CursorShape::Bar).blinking(true).decscusr(), 5);
+        assert_eq!(CursorStyle::new(CursorShape::Underline).decscusr(), 4);
+    }
+}
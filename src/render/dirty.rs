@@ -0,0 +1,189 @@
+//! Dirty region - the set of cell rectangles that need redrawing
+//!
+//! Marked rectangles are kept separately, so updates in opposite corners of
+//! the screen don't invalidate everything between them. Rectangles that
+//! overlap or touch are merged, and once there are more than
+//! `MAX_DIRTY_RECTS` the set collapses into its bounding box to keep
+//! lookups cheap.
+
+use crate::layout::Rect;
+
+/// Rectangles tracked before the set collapses into one bounding box
+pub const MAX_DIRTY_RECTS: usize = 16;
+
+/// Dirty region for optimized rendering
+#[derive(Debug, Clone, Default)]
+pub struct DirtyRegion {
+    /// Minimum column of the bounding box that needs redraw
+    pub min_col: u16,
+    /// Minimum row of the bounding box that needs redraw
+    pub min_row: u16,
+    /// Maximum column (exclusive) of the bounding box that needs redraw
+    pub max_col: u16,
+    /// Maximum row (exclusive) of the bounding box that needs redraw
+    pub max_row: u16,
+    /// Whether any region is dirty
+    pub is_dirty: bool,
+    /// Disjoint dirty rectangles, none of which touch
+    rects: Vec<Rect>,
+}
+
+impl DirtyRegion {
+    /// Create a new empty (clean) region
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark the entire screen as dirty
+    pub fn mark_all(&mut self, cols: u16, rows: u16) {
+        self.rects.clear();
+        self.mark_region(0, 0, cols, rows);
+    }
+
+    /// Mark a specific region as dirty
+    ///
+    /// Empty rectangles are ignored.
+    pub fn mark_region(&mut self, col: u16, row: u16, width: u16, height: u16) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // Absorb every rect the new one touches; a merge can grow it into
+        // rects it didn't touch before, so repeat until nothing changes
+        let mut rect = Rect::new(col, row, width, height);
+        while let Some(idx) = self.rects.iter().position(|other| touches(&rect, other)) {
+            rect = union(&rect, &self.rects.swap_remove(idx));
+        }
+        self.rects.push(rect);
+
+        if self.rects.len() > MAX_DIRTY_RECTS {
+            let bounds = self.rects.iter().fold(rect, |acc, other| union(&acc, other));
+            self.rects.clear();
+            self.rects.push(bounds);
+        }
+        self.update_bounds();
+    }
+
+    /// Clear the dirty region (mark as clean)
+    pub fn clear(&mut self) {
+        self.rects.clear();
+        self.update_bounds();
+    }
+
+    /// Check if a region overlaps any dirty rectangle
+    pub fn intersects(&self, col: u16, row: u16, width: u16, height: u16) -> bool {
+        let area = Rect::new(col, row, width, height);
+        self.rects.iter().any(|rect| overlaps(rect, &area))
+    }
+
+    /// The dirty rectangles, which never overlap or touch each other
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Iterate over the dirty rectangles
+    pub fn iter(&self) -> std::slice::Iter<'_, Rect> {
+        self.rects.iter()
+    }
+
+    /// Smallest rectangle containing every dirty cell, if any
+    pub fn bounds(&self) -> Option<Rect> {
+        self.is_dirty.then(|| {
+            Rect::new(
+                self.min_col,
+                self.min_row,
+                self.max_col - self.min_col,
+                self.max_row - self.min_row,
+            )
+        })
+    }
+
+    fn update_bounds(&mut self) {
+        let mut rects = self.rects.iter();
+        let bounds = match rects.next() {
+            Some(first) => rects.fold(*first, |acc, rect| union(&acc, rect)),
+            None => Rect::new(0, 0, 0, 0),
+        };
+        self.min_col = bounds.x;
+        self.min_row = bounds.y;
+        self.max_col = bounds.right();
+        self.max_row = bounds.bottom();
+        self.is_dirty = !self.rects.is_empty();
+    }
+}
+
+impl<'a> IntoIterator for &'a DirtyRegion {
+    type Item = &'a Rect;
+    type IntoIter = std::slice::Iter<'a, Rect>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Whether two rectangles share at least one cell
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.right() && b.x < a.right() && a.y < b.bottom() && b.y < a.bottom()
+}
+
+/// Whether two rectangles overlap or share an edge
+fn touches(a: &Rect, b: &Rect) -> bool {
+    let cols = a.x <= b.right() && b.x <= a.right();
+    let rows = a.y <= b.bottom() && b.y <= a.bottom();
+    let cols_overlap = a.x < b.right() && b.x < a.right();
+    let rows_overlap = a.y < b.bottom() && b.y < a.bottom();
+    (cols && rows_overlap) || (rows && cols_overlap)
+}
+
+/// Smallest rectangle containing both
+fn union(a: &Rect, b: &Rect) -> Rect {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    Rect::new(x, y, a.right().max(b.right()) - x, a.bottom().max(b.bottom()) - y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distant_regions_stay_separate() {
+        let mut dirty = DirtyRegion::new();
+        dirty.mark_region(0, 0, 10, 1);
+        dirty.mark_region(70, 23, 10, 1);
+
+        assert_eq!(dirty.rects().len(), 2);
+        assert!(!dirty.intersects(20, 5, 40, 10));
+        assert!(dirty.intersects(75, 20, 5, 4));
+        assert_eq!(dirty.bounds(), Some(Rect::new(0, 0, 80, 24)));
+    }
+
+    #[test]
+    fn test_touching_regions_merge() {
+        let mut dirty = DirtyRegion::new();
+        dirty.mark_region(0, 0, 5, 1);
+        dirty.mark_region(10, 0, 5, 1);
+        // Bridges the gap, so all three become one rect
+        dirty.mark_region(5, 0, 5, 1);
+        assert_eq!(dirty.rects(), &[Rect::new(0, 0, 15, 1)]);
+
+        // Diagonal neighbours only share a corner
+        dirty.mark_region(15, 1, 1, 1);
+        assert_eq!(dirty.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_cap_collapses_to_bounding_box() {
+        let mut dirty = DirtyRegion::new();
+        for i in 0..=MAX_DIRTY_RECTS as u16 {
+            dirty.mark_region(i * 2, i * 2, 1, 1);
+        }
+        let end = MAX_DIRTY_RECTS as u16 * 2 + 1;
+        assert_eq!(dirty.rects(), &[Rect::new(0, 0, end, end)]);
+
+        dirty.clear();
+        assert!(!dirty.is_dirty);
+        assert!(dirty.bounds().is_none());
+        assert!(!dirty.intersects(0, 0, 1, 1));
+    }
+}
@@ -0,0 +1,321 @@
+//! Cell grid - a fixed-size matrix of styled character cells
+//!
+//! Backends that retain screen content keep one of these per surface so
+//! writes land in memory first and only changed cells reach the output.
+
+use crate::layout::Rect;
+use crate::style::Style;
+use crate::unicode;
+
+/// Longest grapheme cluster (in UTF-8 bytes) a cell stores inline
+const SYMBOL_CAPACITY: usize = 31;
+
+/// A grapheme cluster stored inline so cells stay `Copy`
+///
+/// Clusters longer than the inline capacity keep only their first char.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    len: u8,
+    bytes: [u8; SYMBOL_CAPACITY],
+}
+
+impl Symbol {
+    /// Store a grapheme cluster
+    pub fn new(grapheme: &str) -> Self {
+        let text = if grapheme.len() <= SYMBOL_CAPACITY {
+            grapheme
+        } else {
+            grapheme
+                .char_indices()
+                .nth(1)
+                .map_or(grapheme, |(end, _)| &grapheme[..end])
+        };
+        let mut bytes = [0; SYMBOL_CAPACITY];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        Symbol { len: text.len() as u8, bytes }
+    }
+
+    /// The stored cluster
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+}
+
+impl std::fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single character cell
+///
+/// Wide graphemes occupy a leading cell of width 2 followed by a
+/// continuation cell of width 0 with an empty symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    /// Grapheme cluster displayed in this cell
+    pub symbol: Symbol,
+    /// Resolved style of the cell
+    pub style: Style,
+    /// Display width in columns
+    pub width: u8,
+}
+
+impl Cell {
+    /// Create a cell holding a character with the given style
+    pub fn new(ch: char, style: Style) -> Self {
+        Cell::grapheme(ch.encode_utf8(&mut [0; 4]), style)
+    }
+
+    /// Create a cell holding a grapheme cluster with the given style
+    pub fn grapheme(grapheme: &str, style: Style) -> Self {
+        let width = unicode::grapheme_width(grapheme).max(1) as u8;
+        Cell { symbol: Symbol::new(grapheme), style, width }
+    }
+
+    /// Create the cell covered by the right half of a wide grapheme
+    pub fn continuation(style: Style) -> Self {
+        Cell { symbol: Symbol::new(""), style, width: 0 }
+    }
+
+    /// Create an unstyled blank cell
+    pub fn blank() -> Self {
+        Cell::new(' ', Style::new())
+    }
+
+    /// The grapheme cluster in this cell (empty for continuation cells)
+    pub fn symbol(&self) -> &str {
+        self.symbol.as_str()
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::blank()
+    }
+}
+
+/// Row-major grid of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellGrid {
+    cols: u16,
+    rows: u16,
+    cells: Vec<Cell>,
+}
+
+impl CellGrid {
+    /// Create a grid filled with blank cells
+    pub fn new(cols: u16, rows: u16) -> Self {
+        CellGrid {
+            cols,
+            rows,
+            cells: vec![Cell::blank(); cols as usize * rows as usize],
+        }
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> u16 {
+        self.cols
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    fn index(&self, col: u16, row: u16) -> Option<usize> {
+        if col < self.cols && row < self.rows {
+            Some(row as usize * self.cols as usize + col as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Get the cell at a position
+    pub fn get(&self, col: u16, row: u16) -> Option<&Cell> {
+        self.index(col, row).map(|i| &self.cells[i])
+    }
+
+    /// Replace the cell at a position (ignored when out of bounds)
+    pub fn set(&mut self, col: u16, row: u16, cell: Cell) {
+        if let Some(i) = self.index(col, row) {
+            self.cells[i] = cell;
+        }
+    }
+
+    /// Get all cells in a row
+    pub fn row(&self, row: u16) -> &[Cell] {
+        if row >= self.rows {
+            return &[];
+        }
+        let start = row as usize * self.cols as usize;
+        &self.cells[start..start + self.cols as usize]
+    }
+
+    /// Fill every cell with the given cell
+    pub fn fill(&mut self, cell: Cell) {
+        self.cells.fill(cell);
+    }
+
+    /// Reset every cell to blank
+    pub fn clear(&mut self) {
+        self.fill(Cell::blank());
+    }
+
+    /// Fill a rectangle (clipped to the grid) with the given cell
+    pub fn fill_rect(&mut self, rect: Rect, cell: Cell) {
+        let right = rect.right().min(self.cols);
+        let bottom = rect.bottom().min(self.rows);
+        for row in rect.y..bottom {
+            for col in rect.x..right {
+                self.set(col, row, cell);
+            }
+        }
+    }
+
+    /// Write text starting at a position, returning the column after the last cell
+    ///
+    /// Characters past the right edge are dropped and control characters are skipped.
+    pub fn put_str(&mut self, col: u16, row: u16, text: &str, style: Style) -> u16 {
+        self.put_str_clipped((col, row), text, style, None)
+    }
+
+    /// Write text like `put_str` at `(col, row)`, dropping graphemes outside `clip`
+    ///
+    /// A wide grapheme that would be split by the clip or the right edge is
+    /// replaced with blanks.
+    pub fn put_str_clipped(
+        &mut self,
+        (col, row): (u16, u16),
+        text: &str,
+        style: Style,
+        clip: Option<Rect>,
+    ) -> u16 {
+        let visible = |col: u16| clip.is_none_or(|clip| clip.contains(col, row));
+        let mut col = col;
+        for grapheme in unicode::graphemes(text) {
+            if unicode::grapheme_width(grapheme) == 0 {
+                continue;
+            }
+            let cell = Cell::grapheme(grapheme, style);
+            let next = col.saturating_add(cell.width as u16);
+            let fits = next <= self.cols && (col..next).all(visible);
+            for c in col..next {
+                if !visible(c) {
+                    continue;
+                }
+                if !fits {
+                    self.put_cell(c, row, Cell::new(' ', style));
+                } else if c == col {
+                    self.put_cell(c, row, cell);
+                } else {
+                    self.put_cell(c, row, Cell::continuation(style));
+                }
+            }
+            col = next;
+        }
+        col
+    }
+
+    /// Replace a cell, blanking the other half of any wide grapheme it overlaps
+    fn put_cell(&mut self, col: u16, row: u16, cell: Cell) {
+        let Some(old) = self.get(col, row).copied() else {
+            return;
+        };
+        if old.width == 0 && cell.width != 0 && col > 0 {
+            self.set(col - 1, row, Cell::new(' ', old.style));
+        }
+        if old.width == 2 && cell.width != 2 {
+            if let Some(next) = self.get(col + 1, row).filter(|next| next.width == 0) {
+                let style = next.style;
+                self.set(col + 1, row, Cell::new(' ', style));
+            }
+        }
+        self.set(col, row, cell);
+    }
+
+    /// Resize the grid, keeping content that still fits
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        if cols == self.cols && rows == self.rows {
+            return;
+        }
+        let mut resized = CellGrid::new(cols, rows);
+        for row in 0..rows.min(self.rows) {
+            for col in 0..cols.min(self.cols) {
+                resized.set(col, row, self.cells[row as usize * self.cols as usize + col as usize]);
+            }
+        }
+        *self = resized;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Color;
+
+    #[test]
+    fn test_put_str_clips_at_edge() {
+        let mut grid = CellGrid::new(4, 1);
+        let end = grid.put_str(2, 0, "abc", Style::new());
+        assert_eq!(end, 5);
+        assert_eq!(grid.get(2, 0).map(|c| c.symbol()), Some("a"));
+        assert_eq!(grid.get(3, 0).map(|c| c.symbol()), Some("b"));
+        assert!(grid.get(4, 0).is_none());
+    }
+
+    #[test]
+    fn test_put_str_clipped() {
+        let mut grid = CellGrid::new(6, 1);
+        let end = grid.put_str_clipped((0, 0), "abcdef", Style::new(), Some(Rect::new(2, 0, 2, 1)));
+        assert_eq!(end, 6);
+        assert_eq!(grid.row(0).iter().map(|c| c.symbol()).collect::<String>(), "  cd  ");
+    }
+
+    #[test]
+    fn test_wide_graphemes() {
+        let mut grid = CellGrid::new(5, 1);
+        let end = grid.put_str(0, 0, "日本語", Style::new());
+        assert_eq!(end, 6);
+        assert_eq!(grid.get(0, 0).map(|c| c.width), Some(2));
+        assert_eq!(grid.get(1, 0).map(|c| c.width), Some(0));
+        // The last ideograph doesn't fit and is blanked
+        assert_eq!(grid.row(0).iter().map(|c| c.symbol()).collect::<String>(), "日本 ");
+
+        // Overwriting half of a wide cell blanks the other half
+        grid.put_str(1, 0, "x", Style::new());
+        assert_eq!(grid.row(0).iter().map(|c| c.symbol()).collect::<String>(), " x本 ");
+    }
+
+    #[test]
+    fn test_combining_marks_stay_in_cell() {
+        let mut grid = CellGrid::new(3, 1);
+        let end = grid.put_str(0, 0, "e\u{301}x", Style::new());
+        assert_eq!(end, 2);
+        assert_eq!(grid.get(0, 0).map(|c| c.symbol()), Some("e\u{301}"));
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut grid = CellGrid::new(5, 3);
+        let style = Style::new().bg(Color::Rgb(1, 2, 3));
+        grid.fill_rect(Rect::new(1, 1, 10, 10), Cell::new(' ', style));
+        assert_eq!(grid.get(0, 0).map(|c| c.style), Some(Style::new()));
+        assert_eq!(grid.get(4, 2).map(|c| c.style), Some(style));
+    }
+
+    #[test]
+    fn test_resize_keeps_content() {
+        let mut grid = CellGrid::new(3, 2);
+        grid.put_str(0, 1, "xyz", Style::new());
+        grid.resize(2, 3);
+        assert_eq!(grid.row(1).iter().map(|c| c.symbol()).collect::<String>(), "xy");
+        assert_eq!(grid.row(2).iter().map(|c| c.symbol()).collect::<String>(), "  ");
+    }
+}
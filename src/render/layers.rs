@@ -0,0 +1,219 @@
+//! Overlay layers - z-ordered cell grids composited over a base grid
+//!
+//! Retained backends keep the base layer (layer 0) in their own back grid.
+//! Overlays drawn with `Renderer::push_layer` land in separate grids where
+//! unwritten cells stay transparent, so the composited screen shows the
+//! topmost written cell. Clearing an overlay reveals what lies beneath
+//! without the lower layers re-rendering.
+
+use super::grid::{Cell, CellGrid, Symbol};
+use crate::layout::Rect;
+use crate::style::Style;
+
+/// Layer index of the base surface
+pub const BASE_LAYER: u16 = 0;
+
+/// Marker for overlay cells nothing was drawn into
+///
+/// Never produced by writes: text skips empty graphemes.
+fn transparent() -> Cell {
+    Cell {
+        symbol: Symbol::new(""),
+        style: Style::new(),
+        width: 1,
+    }
+}
+
+/// Overlay grids above a base grid, plus the stack of layers being drawn
+#[derive(Debug, Clone, Default)]
+pub struct LayerStack {
+    /// Overlays in ascending z-order
+    overlays: Vec<(u16, CellGrid)>,
+    /// Layers entered with `push`, innermost last
+    active: Vec<u16>,
+}
+
+impl LayerStack {
+    /// Create a stack with no overlays
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether any overlay holds content
+    pub fn is_empty(&self) -> bool {
+        self.overlays.is_empty()
+    }
+
+    /// Start drawing into layer `z`, creating a cols×rows overlay on first use
+    pub fn push(&mut self, z: u16, cols: u16, rows: u16) {
+        self.active.push(z);
+        if z == BASE_LAYER {
+            return;
+        }
+        if let Err(index) = self.overlays.binary_search_by_key(&z, |(layer, _)| *layer) {
+            let mut grid = CellGrid::new(cols, rows);
+            grid.fill(transparent());
+            self.overlays.insert(index, (z, grid));
+        }
+    }
+
+    /// Return to the layer that was active before the last `push`
+    pub fn pop(&mut self) {
+        self.active.pop();
+    }
+
+    /// Layer currently receiving writes
+    pub fn current(&self) -> u16 {
+        self.active.last().copied().unwrap_or(BASE_LAYER)
+    }
+
+    /// Grid that writes should go to: the active overlay or `base`
+    pub fn target<'a>(&'a mut self, base: &'a mut CellGrid) -> &'a mut CellGrid {
+        let z = self.current();
+        match self.overlays.iter_mut().find(|(layer, _)| *layer == z) {
+            Some((_, grid)) => grid,
+            None => base,
+        }
+    }
+
+    /// Discard overlay `z`, returning the area its content covered
+    pub fn clear(&mut self, z: u16) -> Option<Rect> {
+        let index = self.overlays.iter().position(|(layer, _)| *layer == z)?;
+        let (_, grid) = self.overlays.remove(index);
+        covered_area(&grid)
+    }
+
+    /// Discard every overlay
+    pub fn clear_all(&mut self) {
+        self.overlays.clear();
+    }
+
+    /// Resize every overlay, keeping content that still fits
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        for (_, grid) in &mut self.overlays {
+            let (old_cols, old_rows) = (grid.cols(), grid.rows());
+            grid.resize(cols, rows);
+            // Newly exposed cells start transparent, not blank
+            for row in 0..rows {
+                for col in 0..cols {
+                    if col >= old_cols || row >= old_rows {
+                        grid.set(col, row, transparent());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Composite one row of `base` and the overlays into `out`
+    ///
+    /// A wide grapheme whose other half is covered by a different layer is
+    /// replaced with a blank so no half-glyphs reach the screen.
+    pub fn compose_row(&self, base: &CellGrid, row: u16, out: &mut Vec<Cell>) {
+        out.clear();
+        out.extend_from_slice(base.row(row));
+        if self.overlays.is_empty() {
+            return;
+        }
+
+        let mut source = vec![BASE_LAYER; out.len()];
+        for (z, grid) in &self.overlays {
+            for (col, cell) in grid.row(row).iter().enumerate().take(out.len()) {
+                if *cell != transparent() {
+                    out[col] = *cell;
+                    source[col] = *z;
+                }
+            }
+        }
+
+        for col in 0..out.len() {
+            let split = match out[col].width {
+                2 => source.get(col + 1) != Some(&source[col]),
+                0 => col == 0 || source[col - 1] != source[col],
+                _ => false,
+            };
+            if split {
+                out[col] = Cell::new(' ', out[col].style);
+            }
+        }
+    }
+
+    /// Composite `base` and the overlays into a new grid
+    pub fn compose(&self, base: &CellGrid) -> CellGrid {
+        let mut composed = base.clone();
+        let mut row_cells = Vec::with_capacity(base.cols() as usize);
+        for row in 0..base.rows() {
+            self.compose_row(base, row, &mut row_cells);
+            for (col, cell) in row_cells.iter().enumerate() {
+                composed.set(col as u16, row, *cell);
+            }
+        }
+        composed
+    }
+}
+
+/// Bounding box of the non-transparent cells in an overlay
+fn covered_area(grid: &CellGrid) -> Option<Rect> {
+    let mut area: Option<(u16, u16, u16, u16)> = None;
+    for row in 0..grid.rows() {
+        for (col, cell) in grid.row(row).iter().enumerate() {
+            if *cell == transparent() {
+                continue;
+            }
+            let col = col as u16;
+            area = Some(match area {
+                None => (col, row, col, row),
+                Some((x0, y0, x1, y1)) => (x0.min(col), y0.min(row), x1.max(col), y1.max(row)),
+            });
+        }
+    }
+    area.map(|(x0, y0, x1, y1)| Rect::new(x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_text(grid: &CellGrid, row: u16) -> String {
+        grid.row(row).iter().map(|c| c.symbol()).collect()
+    }
+
+    #[test]
+    fn test_overlay_covers_and_clear_reveals_base() {
+        let mut base = CellGrid::new(6, 1);
+        base.put_str(0, 0, "abcdef", Style::new());
+
+        let mut layers = LayerStack::new();
+        layers.push(5, 6, 1);
+        layers.target(&mut base).put_str(2, 0, "XY", Style::new());
+        layers.pop();
+        assert_eq!(row_text(&layers.compose(&base), 0), "abXYef");
+        assert_eq!(row_text(&base, 0), "abcdef");
+
+        assert_eq!(layers.clear(5), Some(Rect::new(2, 0, 2, 1)));
+        assert_eq!(row_text(&layers.compose(&base), 0), "abcdef");
+    }
+
+    #[test]
+    fn test_higher_layer_wins() {
+        let mut base = CellGrid::new(3, 1);
+        let mut layers = LayerStack::new();
+        layers.push(9, 3, 1);
+        layers.target(&mut base).put_str(0, 0, "hi", Style::new());
+        layers.pop();
+        layers.push(2, 3, 1);
+        layers.target(&mut base).put_str(0, 0, "low", Style::new());
+        layers.pop();
+        assert_eq!(row_text(&layers.compose(&base), 0), "hiw");
+    }
+
+    #[test]
+    fn test_split_wide_grapheme_is_blanked() {
+        let mut base = CellGrid::new(4, 1);
+        base.put_str(0, 0, "日本", Style::new());
+        let mut layers = LayerStack::new();
+        layers.push(1, 4, 1);
+        layers.target(&mut base).put_str(1, 0, "x", Style::new());
+        layers.pop();
+        assert_eq!(row_text(&layers.compose(&base), 0), " x本");
+    }
+}
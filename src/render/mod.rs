@@ -3,6 +3,18 @@
 //! The `Renderer` trait defines the interface that all rendering backends implement.
 //! Backend implementations live in `tui::TerminalRenderer` and `gui::WgpuRenderer`.
 
+mod clip;
+mod cursor;
+mod dirty;
+mod grid;
+mod layers;
+
+pub use clip::ClipStack;
+pub use cursor::{CursorShape, CursorStyle};
+pub use dirty::{DirtyRegion, MAX_DIRTY_RECTS};
+pub use grid::{Cell, CellGrid, Symbol};
+pub use layers::{LayerStack, BASE_LAYER};
+
 use crate::style::Style;
 use anyhow::Result;
 
@@ -25,71 +37,19 @@ pub struct ImageParams<'a> {
     pub height_cells: Option<u16>,
 }
 
-/// Dirty region for optimized rendering
-#[derive(Debug, Clone, Copy, Default)]
-pub struct DirtyRegion {
-    /// Minimum column that needs redraw
-    pub min_col: u16,
-    /// Minimum row that needs redraw
-    pub min_row: u16,
-    /// Maximum column that needs redraw
-    pub max_col: u16,
-    /// Maximum row that needs redraw
-    pub max_row: u16,
-    /// Whether any region is dirty
-    pub is_dirty: bool,
-}
-
-impl DirtyRegion {
-    /// Create a new empty (clean) region
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    /// Mark the entire screen as dirty
-    pub fn mark_all(&mut self, cols: u16, rows: u16) {
-        self.min_col = 0;
-        self.min_row = 0;
-        self.max_col = cols;
-        self.max_row = rows;
-        self.is_dirty = true;
-    }
-
-    /// Mark a specific region as dirty
-    pub fn mark_region(&mut self, col: u16, row: u16, width: u16, height: u16) {
-        if !self.is_dirty {
-            self.min_col = col;
-            self.min_row = row;
-            self.max_col = col + width;
-            self.max_row = row + height;
-            self.is_dirty = true;
-        } else {
-            self.min_col = self.min_col.min(col);
-            self.min_row = self.min_row.min(row);
-            self.max_col = self.max_col.max(col + width);
-            self.max_row = self.max_row.max(row + height);
-        }
-    }
-
-    /// Clear the dirty region (mark as clean)
-    pub fn clear(&mut self) {
-        self.min_col = 0;
-        self.min_row = 0;
-        self.max_col = 0;
-        self.max_row = 0;
-        self.is_dirty = false;
-    }
-
-    /// Check if a region intersects with the dirty area
-    pub fn intersects(&self, col: u16, row: u16, width: u16, height: u16) -> bool {
-        if !self.is_dirty {
-            return false;
-        }
-        !(col + width < self.min_col
-            || col > self.max_col
-            || row + height < self.min_row
-            || row > self.max_row)
-    }
+/// Task progress shown in the terminal tab or taskbar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// No progress indicator
+    Hidden,
+    /// Running, with percent complete (0-100)
+    Normal(u8),
+    /// Failed, with percent complete when it stopped
+    Error(u8),
+    /// Running with no known completion
+    Indeterminate,
+    /// Paused or waiting, with percent complete
+    Paused(u8),
 }
 
 /// Backend-agnostic rendering interface
@@ -120,12 +80,67 @@ pub trait Renderer: std::any::Any {
     /// Show cursor
     fn show_cursor(&mut self) -> Result<()>;
 
+    /// Set the cursor shape, blink, and color
+    ///
+    /// The style persists across frames until changed. Backends without
+    /// cursor styling ignore it.
+    fn set_cursor_style(&mut self, _style: CursorStyle) -> Result<()> {
+        Ok(())
+    }
+
     /// Clear the screen
     fn clear(&mut self) -> Result<()>;
 
     /// Flush output buffer
     fn flush(&mut self) -> Result<()>;
 
+    /// Set the window or tab title
+    ///
+    /// Terminal backends restore the user's previous title on exit.
+    fn set_title(&mut self, _title: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Show a desktop notification, e.g. when a long task finishes
+    ///
+    /// A no-op where the backend has no way to notify.
+    fn notify(&mut self, _title: &str, _body: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Report task progress in the tab or taskbar
+    ///
+    /// A no-op where the backend has no progress indicator.
+    fn set_progress(&mut self, _progress: Progress) -> Result<()> {
+        Ok(())
+    }
+
+    /// Restrict drawing to `rect` until the matching `pop_clip`
+    ///
+    /// Clips nest: the new clip is intersected with the active one. Text and
+    /// fills outside it are discarded; images are not clipped. Backends
+    /// that can't clip ignore it and draw everything.
+    fn push_clip(&mut self, _rect: crate::layout::Rect) {}
+
+    /// Restore the clip that was active before the last `push_clip`
+    fn pop_clip(&mut self) {}
+
+    /// Draw into layer `z` until the matching `pop_layer`
+    ///
+    /// Layer 0 is the base surface; higher layers cover lower ones, and cells
+    /// an overlay never wrote show what lies beneath. Backends that redraw
+    /// everything each frame ignore layers and draw in call order.
+    fn push_layer(&mut self, _z: u16) {}
+
+    /// Return to the layer that was active before the last `push_layer`
+    fn pop_layer(&mut self) {}
+
+    /// Erase everything drawn on overlay `z`, revealing the layers below
+    ///
+    /// Lower layers don't need to re-render: their cells are restored from
+    /// what they last drew.
+    fn clear_layer(&mut self, _z: u16) {}
+
     /// Fill a rectangle with a solid color
     fn fill_rect(&mut self, bounds: crate::layout::Rect, color: crate::theme::Color) -> Result<()>;
 
@@ -153,8 +168,8 @@ pub trait Renderer: std::any::Any {
     }
 
     /// Whether the surface retains content between frames.
-    /// Terminals retain (text stays until overwritten). GPU surfaces don't
-    /// (each frame starts blank). Render trackers should force full repaints
+    /// Terminals retain (text stays until overwritten), and the GPU backend
+    /// keeps its own canvas. Render trackers should force full repaints
     /// when this returns false.
     fn retains_content(&self) -> bool {
         true
@@ -164,6 +179,8 @@ pub trait Renderer: std::any::Any {
     fn dirty_region(&self) -> &DirtyRegion;
 
     /// Mark a region as needing redraw
+    ///
+    /// The GPU backend repaints only the dirty rectangles of its canvas.
     fn mark_dirty(&mut self, col: u16, row: u16, width: u16, height: u16);
 
     /// Clear dirty region tracking (call after full render)
@@ -0,0 +1,713 @@
+//! Event logs - record input events and replay them deterministically
+//!
+//! A log is plain text, one event per line, prefixed with the milliseconds
+//! since recording started. Strings are quoted with backslash escapes;
+//! everything else is bare words and numbers:
+//!
+//! ```text
+//! # mkui event log v1
+//! 0 resize 80 24
+//! 420 key "a" press - "a"
+//! 510 key up press ctrl+shift
+//! 900 mouse press left 10 4 -
+//! 1200 paste "hello\nworld"
+//! 1500 link "https://example.com" "id1"
+//! ```
+//!
+//! `EventLog` writes logs (wrap a live source in `RecordingSource` to log
+//! a session), and `ReplaySource` plays one back through `App::run_with`
+//! or `App::run_tui_with`, either on the original timeline or instantly.
+
+use crate::event::{
+    Event, EventKind, EventSource, Key, KeyState, Modifiers, MouseButton, MouseEvent,
+};
+use crate::style::Hyperlink;
+use anyhow::{bail, Context, Result};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const HEADER: &str = "# mkui event log v1";
+
+/// Writer for event logs
+pub struct EventLog {
+    out: Box<dyn Write>,
+    start: Instant,
+}
+
+impl EventLog {
+    /// Start a log on any output
+    pub fn new(mut out: impl Write + 'static) -> Result<Self> {
+        writeln!(out, "{}", HEADER)?;
+        Ok(EventLog {
+            out: Box::new(out),
+            start: Instant::now(),
+        })
+    }
+
+    /// Start a log in a new file
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::fs::File::create(path.as_ref())
+            .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+        Self::new(std::io::BufWriter::new(file))
+    }
+
+    /// Append an event, timestamped relative to the start of the log
+    pub fn record(&mut self, kind: &EventKind) -> Result<()> {
+        let millis = self.start.elapsed().as_millis();
+        writeln!(self.out, "{} {}", millis, format_event(kind))?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Event source that logs every event it passes through
+pub struct RecordingSource<S> {
+    source: S,
+    log: EventLog,
+}
+
+impl<S: EventSource> RecordingSource<S> {
+    /// Log the events `source` produces
+    pub fn new(source: S, log: EventLog) -> Self {
+        RecordingSource { source, log }
+    }
+
+    fn record(&mut self, event: Option<Event>) -> Result<Option<Event>> {
+        if let Some(event) = &event {
+            self.log.record(&event.kind)?;
+        }
+        Ok(event)
+    }
+}
+
+impl<S: EventSource> EventSource for RecordingSource<S> {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        let event = self.source.next_event()?;
+        self.record(event)
+    }
+
+    fn next_event_timeout(&mut self, timeout: Duration) -> Result<Option<Event>> {
+        let event = self.source.next_event_timeout(timeout)?;
+        self.record(event)
+    }
+
+    fn is_live(&self) -> bool {
+        self.source.is_live()
+    }
+}
+
+/// Event source that plays back a log
+pub struct ReplaySource {
+    events: VecDeque<(Duration, EventKind)>,
+    start: Option<Instant>,
+    realtime: bool,
+}
+
+impl ReplaySource {
+    /// Parse a log, replaying on its original timeline
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut events = VecDeque::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let event = parse_line(line).with_context(|| format!("Event log line {}", index + 1))?;
+            events.push_back(event);
+        }
+        Ok(ReplaySource {
+            events,
+            start: None,
+            realtime: true,
+        })
+    }
+
+    /// Read and parse a log file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let text = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Failed to read {}", path.as_ref().display()))?;
+        Self::parse(&text)
+    }
+
+    /// Deliver events immediately instead of waiting for their timestamps
+    pub fn instant(mut self) -> Self {
+        self.realtime = false;
+        self
+    }
+
+    /// Number of events left to replay
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl EventSource for ReplaySource {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        let Some((at, kind)) = self.events.pop_front() else {
+            return Ok(None);
+        };
+        if self.realtime {
+            let start = *self.start.get_or_insert_with(Instant::now);
+            let due = start + at;
+            let now = Instant::now();
+            if due > now {
+                std::thread::sleep(due - now);
+            }
+        }
+        Ok(Some(Event::new(kind)))
+    }
+}
+
+// -- Formatting --
+
+fn format_event(kind: &EventKind) -> String {
+    match kind {
+        EventKind::Key {
+            key,
+            state,
+            modifiers,
+            text,
+        } => {
+            let mut line = format!(
+                "key {} {} {}",
+                format_key(key),
+                format_state(*state),
+                format_modifiers(modifiers)
+            );
+            if let Some(text) = text {
+                line.push(' ');
+                line.push_str(&quote(text));
+            }
+            line
+        }
+        EventKind::Mouse(MouseEvent::Button {
+            button,
+            state,
+            col,
+            row,
+            modifiers,
+        }) => format!(
+            "mouse {} {} {} {} {}",
+            format_state(*state),
+            format_button(button),
+            col,
+            row,
+            format_modifiers(modifiers)
+        ),
+        EventKind::Mouse(MouseEvent::Moved { col, row }) => format!("mouse move {} {}", col, row),
+        EventKind::Mouse(MouseEvent::Enter { col, row }) => format!("mouse enter {} {}", col, row),
+        EventKind::Mouse(MouseEvent::Leave) => "mouse leave".into(),
+        EventKind::Mouse(MouseEvent::Hover { col, row }) => format!("mouse hover {} {}", col, row),
+        EventKind::Mouse(MouseEvent::DragStart { button, col, row }) => {
+            format!("mouse drag-start {} {} {}", format_button(button), col, row)
+        }
+        EventKind::Mouse(MouseEvent::DragMove { button, col, row }) => {
+            format!("mouse drag {} {} {}", format_button(button), col, row)
+        }
+        EventKind::Mouse(MouseEvent::DragEnd { button, col, row }) => {
+            format!("mouse drag-end {} {} {}", format_button(button), col, row)
+        }
+        EventKind::Mouse(MouseEvent::Drop { col, row }) => format!("mouse drop {} {}", col, row),
+        EventKind::Mouse(MouseEvent::Scroll {
+            delta_x,
+            delta_y,
+            col,
+            row,
+            modifiers,
+        }) => format!(
+            "mouse scroll {} {} {} {} {}",
+            delta_x,
+            delta_y,
+            col,
+            row,
+            format_modifiers(modifiers)
+        ),
+        EventKind::Resize(cols, rows) => format!("resize {} {}", cols, rows),
+        EventKind::FocusGained => "focus in".into(),
+        EventKind::FocusLost => "focus out".into(),
+        EventKind::Paste(text) => format!("paste {}", quote(text)),
+        EventKind::Redraw => "redraw".into(),
+        EventKind::Drop(paths) => format!("drop{}", format_paths(paths)),
+        EventKind::DragOver(paths) => format!("dragover{}", format_paths(paths)),
+        EventKind::DragLeave => "dragleave".into(),
+        EventKind::LinkClicked(link) => match link.id() {
+            Some(id) => format!("link {} {}", quote(&link.uri()), quote(&id)),
+            None => format!("link {}", quote(&link.uri())),
+        },
+    }
+}
+
+fn format_key(key: &Key) -> String {
+    let name = match key {
+        Key::Char(c) => return quote(&c.to_string()),
+        Key::F(n) => return format!("f{}", n),
+        Key::Up => "up",
+        Key::Down => "down",
+        Key::Left => "left",
+        Key::Right => "right",
+        Key::Home => "home",
+        Key::End => "end",
+        Key::PageUp => "pageup",
+        Key::PageDown => "pagedown",
+        Key::Backspace => "backspace",
+        Key::Delete => "delete",
+        Key::Insert => "insert",
+        Key::Enter => "enter",
+        Key::Tab => "tab",
+        Key::Esc => "esc",
+        Key::Space => "space",
+        Key::Null => "null",
+    };
+    name.into()
+}
+
+fn format_state(state: KeyState) -> &'static str {
+    match state {
+        KeyState::Pressed => "press",
+        KeyState::Released => "release",
+        KeyState::Repeat => "repeat",
+    }
+}
+
+fn format_modifiers(modifiers: &Modifiers) -> String {
+    let names: Vec<&str> = [
+        ("shift", modifiers.shift),
+        ("ctrl", modifiers.ctrl),
+        ("alt", modifiers.alt),
+        ("super", modifiers.super_key),
+        ("hyper", modifiers.hyper),
+    ]
+    .iter()
+    .filter(|(_, held)| *held)
+    .map(|(name, _)| *name)
+    .collect();
+
+    if names.is_empty() {
+        "-".into()
+    } else {
+        names.join("+")
+    }
+}
+
+fn format_button(button: &MouseButton) -> String {
+    match button {
+        MouseButton::Left => "left".into(),
+        MouseButton::Right => "right".into(),
+        MouseButton::Middle => "middle".into(),
+        MouseButton::Back => "back".into(),
+        MouseButton::Forward => "forward".into(),
+        MouseButton::Other(n) => format!("other{}", n),
+    }
+}
+
+fn format_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| format!(" {}", quote(&path.to_string_lossy())))
+        .collect()
+}
+
+/// Quote a string with backslash escapes so it stays on one line
+fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// -- Parsing --
+
+/// A word or quoted string from a log line
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c != '"' {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token { text, quoted: false });
+            continue;
+        }
+
+        chars.next();
+        let mut text = String::new();
+        loop {
+            match chars.next() {
+                None => bail!("Unterminated string"),
+                Some('"') => break,
+                Some('\\') => text.push(match chars.next() {
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => parse_unicode_escape(&mut chars)?,
+                    Some(c) => c,
+                    None => bail!("Unterminated string"),
+                }),
+                Some(c) => text.push(c),
+            }
+        }
+        tokens.push(Token { text, quoted: true });
+    }
+    Ok(tokens)
+}
+
+/// Parse the `{hex}` part of a `\u{hex}` escape
+fn parse_unicode_escape(chars: &mut impl Iterator<Item = char>) -> Result<char> {
+    if chars.next() != Some('{') {
+        bail!("Malformed unicode escape");
+    }
+    let hex: String = chars.by_ref().take_while(|&c| c != '}').collect();
+    u32::from_str_radix(&hex, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .context("Malformed unicode escape")
+}
+
+fn parse_line(line: &str) -> Result<(Duration, EventKind)> {
+    let tokens = tokenize(line)?;
+    let mut words = tokens.iter().peekable();
+    let millis: u64 = next_word(&mut words)?.parse().context("Bad timestamp")?;
+    let kind = parse_kind(&mut words)?;
+    if words.next().is_some() {
+        bail!("Trailing fields");
+    }
+    Ok((Duration::from_millis(millis), kind))
+}
+
+fn next_token<'a>(words: &mut impl Iterator<Item = &'a Token>) -> Result<&'a Token> {
+    words.next().context("Missing field")
+}
+
+fn next_word<'a>(words: &mut impl Iterator<Item = &'a Token>) -> Result<&'a str> {
+    Ok(&next_token(words)?.text)
+}
+
+fn next_number<'a, T: std::str::FromStr>(words: &mut impl Iterator<Item = &'a Token>) -> Result<T> {
+    let word = next_word(words)?;
+    word.parse().ok().with_context(|| format!("Bad number {:?}", word))
+}
+
+fn next_string<'a>(words: &mut impl Iterator<Item = &'a Token>) -> Result<String> {
+    let token = next_token(words)?;
+    if !token.quoted {
+        bail!("Expected a quoted string, found {:?}", token.text);
+    }
+    Ok(token.text.clone())
+}
+
+fn parse_kind<'a>(words: &mut std::iter::Peekable<impl Iterator<Item = &'a Token>>) -> Result<EventKind> {
+    let kind = match next_word(words)? {
+        "key" => {
+            let key = parse_key(next_token(words)?)?;
+            let state = parse_state(next_word(words)?)?;
+            let modifiers = parse_modifiers(next_word(words)?)?;
+            let text = match words.peek() {
+                Some(_) => Some(next_string(words)?),
+                None => None,
+            };
+            EventKind::Key {
+                key,
+                state,
+                modifiers,
+                text,
+            }
+        }
+        "mouse" => EventKind::Mouse(match next_word(words)? {
+            "move" => MouseEvent::Moved {
+                col: next_number(words)?,
+                row: next_number(words)?,
+            },
+            "enter" => MouseEvent::Enter {
+                col: next_number(words)?,
+                row: next_number(words)?,
+            },
+            "leave" => MouseEvent::Leave,
+            "hover" => MouseEvent::Hover {
+                col: next_number(words)?,
+                row: next_number(words)?,
+            },
+            "drag-start" => MouseEvent::DragStart {
+                button: parse_button(next_word(words)?)?,
+                col: next_number(words)?,
+                row: next_number(words)?,
+            },
+            "drag" => MouseEvent::DragMove {
+                button: parse_button(next_word(words)?)?,
+                col: next_number(words)?,
+                row: next_number(words)?,
+            },
+            "drag-end" => MouseEvent::DragEnd {
+                button: parse_button(next_word(words)?)?,
+                col: next_number(words)?,
+                row: next_number(words)?,
+            },
+            "drop" => MouseEvent::Drop {
+                col: next_number(words)?,
+                row: next_number(words)?,
+            },
+            "scroll" => MouseEvent::Scroll {
+                delta_x: next_number(words)?,
+                delta_y: next_number(words)?,
+                col: next_number(words)?,
+                row: next_number(words)?,
+                modifiers: parse_modifiers(next_word(words)?)?,
+            },
+            state => MouseEvent::Button {
+                state: parse_state(state)?,
+                button: parse_button(next_word(words)?)?,
+                col: next_number(words)?,
+                row: next_number(words)?,
+                modifiers: parse_modifiers(next_word(words)?)?,
+            },
+        }),
+        "resize" => EventKind::Resize(next_number(words)?, next_number(words)?),
+        "focus" => match next_word(words)? {
+            "in" => EventKind::FocusGained,
+            "out" => EventKind::FocusLost,
+            other => bail!("Unknown focus change {:?}", other),
+        },
+        "paste" => EventKind::Paste(next_string(words)?),
+        "redraw" => EventKind::Redraw,
+        "drop" => EventKind::Drop(parse_paths(words)?),
+        "dragover" => EventKind::DragOver(parse_paths(words)?),
+        "dragleave" => EventKind::DragLeave,
+        "link" => {
+            let uri = next_string(words)?;
+            EventKind::LinkClicked(match words.peek() {
+                Some(_) => Hyperlink::with_id(&uri, &next_string(words)?),
+                None => Hyperlink::new(&uri),
+            })
+        }
+        other => bail!("Unknown event {:?}", other),
+    };
+    Ok(kind)
+}
+
+fn parse_key(token: &Token) -> Result<Key> {
+    if token.quoted {
+        let mut chars = token.text.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(Key::Char(c)),
+            _ => bail!("Key {:?} is not a single character", token.text),
+        };
+    }
+    Ok(match token.text.as_str() {
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "enter" => Key::Enter,
+        "tab" => Key::Tab,
+        "esc" => Key::Esc,
+        "space" => Key::Space,
+        "null" => Key::Null,
+        name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+            Some(n) => Key::F(n),
+            None => bail!("Unknown key {:?}", name),
+        },
+    })
+}
+
+fn parse_state(word: &str) -> Result<KeyState> {
+    Ok(match word {
+        "press" => KeyState::Pressed,
+        "release" => KeyState::Released,
+        "repeat" => KeyState::Repeat,
+        other => bail!("Unknown state {:?}", other),
+    })
+}
+
+fn parse_modifiers(word: &str) -> Result<Modifiers> {
+    let mut modifiers = Modifiers::none();
+    if word == "-" {
+        return Ok(modifiers);
+    }
+    for name in word.split('+') {
+        match name {
+            "shift" => modifiers.shift = true,
+            "ctrl" => modifiers.ctrl = true,
+            "alt" => modifiers.alt = true,
+            "super" => modifiers.super_key = true,
+            "hyper" => modifiers.hyper = true,
+            other => bail!("Unknown modifier {:?}", other),
+        }
+    }
+    Ok(modifiers)
+}
+
+fn parse_button(word: &str) -> Result<MouseButton> {
+    Ok(match word {
+        "left" => MouseButton::Left,
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        "back" => MouseButton::Back,
+        "forward" => MouseButton::Forward,
+        name => match name.strip_prefix("other").and_then(|n| n.parse().ok()) {
+            Some(n) => MouseButton::Other(n),
+            None => bail!("Unknown mouse button {:?}", name),
+        },
+    })
+}
+
+fn parse_paths<'a>(words: &mut impl Iterator<Item = &'a Token>) -> Result<Vec<PathBuf>> {
+    words
+        .map(|token| {
+            if token.quoted {
+                Ok(PathBuf::from(&token.text))
+            } else {
+                bail!("Expected a quoted path, found {:?}", token.text)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(kind: EventKind) {
+        let line = format!("5 {}", format_event(&kind));
+        let (at, parsed) = parse_line(&line).unwrap();
+        assert_eq!(at, Duration::from_millis(5));
+        assert_eq!(parsed, kind, "line: {}", line);
+    }
+
+    #[test]
+    fn test_events_round_trip() {
+        round_trip(EventKind::Key {
+            key: Key::Char('"'),
+            state: KeyState::Pressed,
+            modifiers: Modifiers::none(),
+            text: Some("\"".into()),
+        });
+        round_trip(EventKind::Key {
+            key: Key::F(12),
+            state: KeyState::Repeat,
+            modifiers: Modifiers {
+                ctrl: true,
+                shift: true,
+                ..Modifiers::none()
+            },
+            text: None,
+        });
+        round_trip(EventKind::Mouse(MouseEvent::Button {
+            button: MouseButton::Other(9),
+            state: KeyState::Released,
+            col: 3,
+            row: 7,
+            modifiers: Modifiers::none(),
+        }));
+        round_trip(EventKind::Mouse(MouseEvent::Scroll {
+            delta_x: 0.0,
+            delta_y: -1.5,
+            col: 1,
+            row: 2,
+            modifiers: Modifiers::none(),
+        }));
+        round_trip(EventKind::Mouse(MouseEvent::Enter { col: 4, row: 9 }));
+        round_trip(EventKind::Mouse(MouseEvent::Leave));
+        round_trip(EventKind::Mouse(MouseEvent::DragStart {
+            button: MouseButton::Left,
+            col: 1,
+            row: 2,
+        }));
+        round_trip(EventKind::Mouse(MouseEvent::Drop { col: 3, row: 4 }));
+        round_trip(EventKind::Resize(120, 40));
+        round_trip(EventKind::Paste("line one\nline \\two\u{1}".into()));
+        round_trip(EventKind::Drop(vec!["/tmp/a b.png".into()]));
+        round_trip(EventKind::FocusLost);
+        round_trip(EventKind::LinkClicked(Hyperlink::with_id("file:///var/log/app.log", "7")));
+    }
+
+    #[test]
+    fn test_replay_source_reads_log() {
+        let log = "# mkui event log v1\n0 resize 80 24\n\n20 key \"q\" press - \"q\"\n";
+        let mut source = ReplaySource::parse(log).unwrap().instant();
+        assert_eq!(source.remaining(), 2);
+        assert_eq!(source.next_event().unwrap().unwrap().kind, EventKind::Resize(80, 24));
+        assert!(source.next_event().unwrap().unwrap().kind.is_key_press(Key::Char('q')));
+        assert!(source.next_event().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_replay_drives_app_without_terminal() {
+        use crate::testing::TestRenderer;
+
+        let log = "0 key \"h\" press - \"h\"\n1 key \"i\" press - \"i\"\n2 key esc press -\n3 key \"x\" press - \"x\"\n";
+        let source = ReplaySource::parse(log).unwrap().instant();
+        let mut renderer = TestRenderer::new(10, 1);
+        crate::App::run_with(source, crate::RunOptions::new(), &mut renderer, |event, renderer| {
+            if let EventKind::Key { text: Some(text), .. } = &event.kind {
+                renderer.write_text(text).unwrap();
+            }
+            !event.kind.is_key_press(Key::Esc)
+        })
+        .unwrap();
+        assert_eq!(renderer.row_text(0).trim_end(), "hi");
+    }
+
+    #[test]
+    fn test_recording_source_logs_events() {
+        #[derive(Clone, Default)]
+        struct Shared(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+        impl Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.borrow_mut().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let output = Shared::default();
+        let replay = ReplaySource::parse("0 resize 80 24\n0 focus in\n").unwrap().instant();
+        let mut source = RecordingSource::new(replay, EventLog::new(output.clone()).unwrap());
+        while source.next_event().unwrap().is_some() {}
+
+        let text = String::from_utf8(output.0.borrow().clone()).unwrap();
+        assert!(text.starts_with(HEADER));
+        assert_eq!(ReplaySource::parse(&text).unwrap().remaining(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let error = ReplaySource::parse("0 resize 80 24\n5 wobble\n").err().unwrap();
+        assert!(format!("{:#}", error).contains("line 2"));
+    }
+}
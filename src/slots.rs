@@ -55,7 +55,7 @@ impl PrioritySlot {
         // Insert new entry
         self.entries.push(SlotEntry { content, priority });
         // Sort by priority descending (highest first)
-        self.entries.sort_by(|a, b| b.priority.cmp(&a.priority));
+        self.entries.sort_by_key(|e| std::cmp::Reverse(e.priority));
     }
 
     /// Clear content at a given priority
@@ -7,6 +7,8 @@
 use crate::components::text::TextAlign;
 use crate::theme::Color;
 use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 
 /// Visual style properties for text rendering
 ///
@@ -26,8 +28,20 @@ pub struct Style {
     pub italic: Option<bool>,
     /// Underlined text
     pub underline: Option<bool>,
+    /// Underline shape, when underlined (single if unset)
+    pub underline_style: Option<UnderlineStyle>,
+    /// Underline color, when underlined (the text color if unset)
+    pub underline_color: Option<Color>,
+    /// Line through the text
+    pub strikethrough: Option<bool>,
+    /// Line above the text
+    pub overline: Option<bool>,
+    /// Blinking text
+    pub blink: Option<bool>,
     /// Reverse video (swap foreground and background)
     pub reverse: Option<bool>,
+    /// Hyperlink target, clickable in terminals with OSC 8 support
+    pub link: Option<Hyperlink>,
 }
 
 impl Style {
@@ -44,7 +58,13 @@ impl Style {
             && self.dim.is_none()
             && self.italic.is_none()
             && self.underline.is_none()
+            && self.underline_style.is_none()
+            && self.underline_color.is_none()
+            && self.strikethrough.is_none()
+            && self.overline.is_none()
+            && self.blink.is_none()
             && self.reverse.is_none()
+            && self.link.is_none()
     }
 
     /// Set foreground color
@@ -83,12 +103,49 @@ impl Style {
         self
     }
 
+    /// Underline with a specific shape
+    pub fn underline_style(mut self, style: UnderlineStyle) -> Self {
+        self.underline = Some(true);
+        self.underline_style = Some(style);
+        self
+    }
+
+    /// Set underline color
+    pub fn underline_color(mut self, color: Color) -> Self {
+        self.underline_color = Some(color);
+        self
+    }
+
+    /// Set strikethrough
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = Some(strikethrough);
+        self
+    }
+
+    /// Set overline
+    pub fn overline(mut self, overline: bool) -> Self {
+        self.overline = Some(overline);
+        self
+    }
+
+    /// Set blink
+    pub fn blink(mut self, blink: bool) -> Self {
+        self.blink = Some(blink);
+        self
+    }
+
     /// Set reverse video
     pub fn reverse(mut self, reverse: bool) -> Self {
         self.reverse = Some(reverse);
         self
     }
 
+    /// Set hyperlink target
+    pub fn link(mut self, link: Hyperlink) -> Self {
+        self.link = Some(link);
+        self
+    }
+
     /// Merge another style into this one (other's set properties take precedence)
     pub fn merge(mut self, other: &Style) -> Self {
         if other.fg.is_some() {
@@ -109,9 +166,27 @@ impl Style {
         if other.underline.is_some() {
             self.underline = other.underline;
         }
+        if other.underline_style.is_some() {
+            self.underline_style = other.underline_style;
+        }
+        if other.underline_color.is_some() {
+            self.underline_color = other.underline_color;
+        }
+        if other.strikethrough.is_some() {
+            self.strikethrough = other.strikethrough;
+        }
+        if other.overline.is_some() {
+            self.overline = other.overline;
+        }
+        if other.blink.is_some() {
+            self.blink = other.blink;
+        }
         if other.reverse.is_some() {
             self.reverse = other.reverse;
         }
+        if other.link.is_some() {
+            self.link = other.link;
+        }
         self
     }
 
@@ -128,12 +203,21 @@ impl Style {
         if self.italic == Some(true) {
             codes.push("3".to_string());
         }
+        if self.blink == Some(true) {
+            codes.push("5".to_string());
+        }
         if self.underline == Some(true) {
-            codes.push("4".to_string());
+            codes.push(self.underline_style.unwrap_or_default().sgr().to_string());
         }
         if self.reverse == Some(true) {
             codes.push("7".to_string());
         }
+        if self.strikethrough == Some(true) {
+            codes.push("9".to_string());
+        }
+        if self.overline == Some(true) {
+            codes.push("53".to_string());
+        }
 
         if let Some(color) = &self.fg {
             codes.push(color_to_ansi_fg(color));
@@ -141,6 +225,9 @@ impl Style {
         if let Some(color) = &self.bg {
             codes.push(color_to_ansi_bg(color));
         }
+        if let (Some(true), Some(color)) = (self.underline, &self.underline_color) {
+            codes.push(color_to_ansi_underline(color));
+        }
 
         if codes.is_empty() {
             String::new()
@@ -150,6 +237,191 @@ impl Style {
     }
 }
 
+/// Shape of an underline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnderlineStyle {
+    /// One straight line
+    #[default]
+    Single,
+    /// Two straight lines
+    Double,
+    /// Wavy line, as used for spelling and validation errors
+    Curly,
+    /// Dotted line
+    Dotted,
+    /// Dashed line
+    Dashed,
+}
+
+impl UnderlineStyle {
+    /// SGR parameter selecting this shape (`4:x`; plain `4` for single)
+    pub fn sgr(&self) -> &'static str {
+        match self {
+            UnderlineStyle::Single => "4",
+            UnderlineStyle::Double => "4:2",
+            UnderlineStyle::Curly => "4:3",
+            UnderlineStyle::Dotted => "4:4",
+            UnderlineStyle::Dashed => "4:5",
+        }
+    }
+}
+
+/// Most link targets kept interned at once
+///
+/// Past this, creating a new link evicts the one used least recently.
+pub const MAX_LINKS: usize = 4096;
+
+/// Hyperlink target (URI plus optional id) for `Style::link`
+///
+/// Targets are interned so `Style` stays `Copy`; creating the same link
+/// twice returns the same handle. At most `MAX_LINKS` targets are kept, so
+/// an app that builds URLs as it goes doesn't grow without bound: a handle
+/// whose target was evicted reads as an empty URI and draws as plain text.
+/// Cells sharing an id are treated by terminals as one link even when it
+/// wraps or is split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hyperlink {
+    slot: u32,
+    /// Bumped each time the slot is reused, so stale handles don't
+    /// resolve to the new target
+    generation: u32,
+}
+
+type LinkTarget = (Arc<str>, Option<Arc<str>>);
+
+struct LinkEntry {
+    target: LinkTarget,
+    generation: u32,
+    /// `LinkTable::clock` when the entry was last created or resolved
+    last_used: u64,
+}
+
+struct LinkTable {
+    entries: Vec<LinkEntry>,
+    slots: HashMap<LinkTarget, u32>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl LinkTable {
+    fn new(capacity: usize) -> Self {
+        LinkTable {
+            entries: Vec::new(),
+            slots: HashMap::new(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    fn intern(&mut self, target: LinkTarget) -> Hyperlink {
+        self.clock += 1;
+        if let Some(&slot) = self.slots.get(&target) {
+            let entry = &mut self.entries[slot as usize];
+            entry.last_used = self.clock;
+            return Hyperlink {
+                slot,
+                generation: entry.generation,
+            };
+        }
+
+        let slot = if self.entries.len() < self.capacity {
+            self.entries.push(LinkEntry {
+                target: target.clone(),
+                generation: 0,
+                last_used: self.clock,
+            });
+            self.entries.len() - 1
+        } else {
+            let (slot, entry) = self
+                .entries
+                .iter_mut()
+                .enumerate()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .expect("link table has capacity");
+            self.slots.remove(&entry.target);
+            entry.target = target.clone();
+            entry.generation = entry.generation.wrapping_add(1);
+            entry.last_used = self.clock;
+            slot
+        };
+        self.slots.insert(target, slot as u32);
+        Hyperlink {
+            slot: slot as u32,
+            generation: self.entries[slot].generation,
+        }
+    }
+
+    fn resolve(&mut self, link: Hyperlink) -> Option<LinkTarget> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(link.slot as usize)?;
+        if entry.generation != link.generation {
+            return None;
+        }
+        entry.last_used = self.clock;
+        Some(entry.target.clone())
+    }
+}
+
+fn link_table() -> std::sync::MutexGuard<'static, LinkTable> {
+    static LINKS: OnceLock<Mutex<LinkTable>> = OnceLock::new();
+    LINKS
+        .get_or_init(|| Mutex::new(LinkTable::new(MAX_LINKS)))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Hyperlink {
+    /// Link to a URI, e.g. `https://…` or `file:///path`
+    pub fn new(uri: &str) -> Self {
+        Self::intern(uri, None)
+    }
+
+    /// Link to a URI with an id grouping separate runs of text into one link
+    pub fn with_id(uri: &str, id: &str) -> Self {
+        Self::intern(uri, Some(id))
+    }
+
+    /// The link's URI, percent-encoded to printable ASCII
+    ///
+    /// Empty once the target has been evicted.
+    pub fn uri(&self) -> Arc<str> {
+        self.target().0
+    }
+
+    /// The link's id, if one was given
+    pub fn id(&self) -> Option<Arc<str>> {
+        self.target().1
+    }
+
+    /// URI and id together, under one lock of the intern table
+    pub(crate) fn target(&self) -> (Arc<str>, Option<Arc<str>>) {
+        link_table()
+            .resolve(*self)
+            .unwrap_or_else(|| (Arc::from(""), None))
+    }
+
+    fn intern(uri: &str, id: Option<&str>) -> Self {
+        // OSC 8 only carries printable ASCII, and `:`/`;` delimit parameters
+        let mut encoded = String::with_capacity(uri.len());
+        for c in uri.chars().filter(|c| !c.is_control()) {
+            if c.is_ascii() {
+                encoded.push(c);
+            } else {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    encoded.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+        let id = id.map(|id| {
+            id.chars()
+                .filter(|c| c.is_ascii_graphic() && !matches!(c, ':' | ';'))
+                .collect::<String>()
+        });
+        link_table().intern((encoded.into(), id.map(Into::into)))
+    }
+}
+
 /// Convert a Color to an ANSI foreground color code (number portion only)
 fn color_to_ansi_fg(color: &Color) -> String {
     match color {
@@ -170,6 +442,18 @@ fn color_to_ansi_bg(color: &Color) -> String {
     }
 }
 
+/// Convert a Color to an ANSI underline color code (SGR 58, colon form)
+fn color_to_ansi_underline(color: &Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("58:2::{}:{}:{}", r, g, b),
+        Color::Palette256(idx) => format!("58:5:{}", idx),
+        Color::Ansi16(_) | Color::Basic(_) => {
+            let (r, g, b) = color.to_rgb();
+            format!("58:2::{}:{}:{}", r, g, b)
+        }
+    }
+}
+
 /// A layout/style property for the stylesheet system
 #[derive(Debug, Clone)]
 pub enum StyleProperty {
@@ -283,7 +567,7 @@ impl StyleSheet {
             .filter(|rule| &rule.selector == selector)
             .collect();
 
-        matching.sort_by(|a, b| b.priority.cmp(&a.priority));
+        matching.sort_by_key(|r| std::cmp::Reverse(r.priority));
 
         matching.iter().map(|rule| &rule.style).collect()
     }
@@ -381,6 +665,36 @@ mod tests {
         assert_eq!(merged.fg, Some(Color::Rgb(0, 255, 0)));
     }
 
+    #[test]
+    fn test_hyperlink_interning_and_merge() {
+        let link = Hyperlink::with_id("file:///tmp/é b", "log:1");
+        assert_eq!(link, Hyperlink::with_id("file:///tmp/é b", "log:1"));
+        assert_ne!(link, Hyperlink::new("file:///tmp/é b"));
+        assert_eq!(&*link.uri(), "file:///tmp/%C3%A9 b");
+        assert_eq!(link.id().as_deref(), Some("log1"));
+
+        let merged = Style::new().link(link).merge(&Style::new().bold(true));
+        assert_eq!(merged.link, Some(link));
+        assert!(!Style::new().link(link).is_empty());
+    }
+
+    #[test]
+    fn test_link_table_evicts_least_recently_used() {
+        let target = |uri: &str| -> LinkTarget { (uri.into(), None) };
+        let mut table = LinkTable::new(2);
+        let a = table.intern(target("a"));
+        let b = table.intern(target("b"));
+        assert!(table.resolve(a).is_some());
+
+        // `b` is the least recently used, so `c` takes its slot
+        let c = table.intern(target("c"));
+        assert_eq!(table.entries.len(), 2);
+        assert_eq!(table.resolve(b), None);
+        assert_eq!(table.resolve(a), Some(target("a")));
+        assert_eq!(table.resolve(c), Some(target("c")));
+        assert_ne!(table.intern(target("b")), b);
+    }
+
     #[test]
     fn test_stylesheet_type_selector() {
         let stylesheet = StyleSheet::new().style_type::<Text>(Style::new().bold(true));
@@ -472,5 +786,11 @@ mod tests {
 
         let color = Style::new().fg(Color::Rgb(255, 0, 0));
         assert_eq!(color.to_ansi(), "\x1b[38;2;255;0;0m");
+
+        let error = Style::new()
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::Rgb(255, 0, 0))
+            .strikethrough(true);
+        assert_eq!(error.to_ansi(), "\x1b[4:3;9;58:2::255:0:0m");
     }
 }
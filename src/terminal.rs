@@ -106,6 +106,17 @@ impl TerminalGeometry {
     }
 }
 
+/// Escape sequence family a terminal accepts for desktop notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationProtocol {
+    /// Kitty's OSC 99, with separate title and body
+    Kitty,
+    /// OSC 777 `notify` (foot, urxvt), with separate title and body
+    Osc777,
+    /// OSC 9 (iTerm2, WezTerm, Windows Terminal), a single message
+    Osc9,
+}
+
 /// Terminal capability detection
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TerminalCapabilities {
@@ -121,6 +132,18 @@ pub struct TerminalCapabilities {
     pub in_multiplexer: bool,
     /// Supports mouse events
     pub mouse: bool,
+    /// Supports synchronized output (DEC private mode 2026)
+    pub synchronized_output: bool,
+    /// Supports setting the window title (OSC 0/2)
+    pub title: bool,
+    /// Desktop notification protocol, if any is known to work
+    pub notifications: Option<NotificationProtocol>,
+    /// Supports taskbar progress reporting (OSC 9;4)
+    pub progress: bool,
+    /// Supports hyperlinks (OSC 8); terminals without it ignore the sequence
+    pub hyperlinks: bool,
+    /// Supports underline shapes and colors (SGR 4:x and 58)
+    pub styled_underlines: bool,
 }
 
 impl TerminalCapabilities {
@@ -147,6 +170,35 @@ impl TerminalCapabilities {
         // Mouse support via crossterm
         let mouse = true; // Most modern terminals support this
 
+        let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+        let term_program_version = std::env::var("TERM_PROGRAM_VERSION").unwrap_or_default();
+        let synchronized_output = kitty_window
+            || std::env::var("WEZTERM_EXECUTABLE").is_ok()
+            || Self::detect_synchronized_output(&term, &term_program, &term_program_version);
+
+        // The Linux console and dumb terminals print OSC strings as garbage
+        let title = !term.is_empty() && term != "dumb" && !term.starts_with("linux");
+
+        // Terminals that set their own variable are still identified inside tmux
+        let windows_terminal = std::env::var("WT_SESSION").is_ok();
+        let conemu = std::env::var("ConEmuANSI").is_ok_and(|value| value == "ON");
+        let notifications = if kitty_window {
+            Some(NotificationProtocol::Kitty)
+        } else if windows_terminal || conemu {
+            Some(NotificationProtocol::Osc9)
+        } else {
+            Self::detect_notifications(&term, &term_program)
+        };
+        let progress = windows_terminal || conemu || Self::detect_progress(&term, &term_program);
+
+        // VTE (GNOME Terminal, Tilix, ...) added colored curly underlines in 0.52
+        let vte = std::env::var("VTE_VERSION")
+            .ok()
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_some_and(|version| version >= 5200);
+        let styled_underlines =
+            kitty_window || vte || Self::detect_styled_underlines(&term, &term_program);
+
         TerminalCapabilities {
             kitty_graphics,
             sixel,
@@ -154,9 +206,68 @@ impl TerminalCapabilities {
             colors_256,
             in_multiplexer: tmux,
             mouse,
+            synchronized_output,
+            title,
+            notifications,
+            progress,
+            hyperlinks: title,
+            styled_underlines,
+        }
+    }
+
+    /// Check whether a terminal identity is known to support SGR 4:x and 58
+    pub fn detect_styled_underlines(term: &str, term_program: &str) -> bool {
+        let known_term = ["kitty", "foot", "wezterm", "ghostty", "alacritty", "contour"]
+            .iter()
+            .any(|name| term.contains(name));
+        let known_program = ["WezTerm", "ghostty", "iTerm.app", "contour"].contains(&term_program);
+        known_term || known_program
+    }
+
+    /// Check which notification protocol a terminal identity is known to support
+    pub fn detect_notifications(term: &str, term_program: &str) -> Option<NotificationProtocol> {
+        if term.contains("kitty") {
+            Some(NotificationProtocol::Kitty)
+        } else if term.contains("foot") || term.contains("rxvt") {
+            Some(NotificationProtocol::Osc777)
+        } else if term.contains("ghostty")
+            || ["iTerm.app", "WezTerm", "ghostty"].contains(&term_program)
+        {
+            Some(NotificationProtocol::Osc9)
+        } else {
+            None
         }
     }
 
+    /// Check whether a terminal identity is known to show OSC 9;4 progress
+    pub fn detect_progress(term: &str, term_program: &str) -> bool {
+        term.contains("ghostty") || ["ghostty", "iTerm.app"].contains(&term_program)
+    }
+
+    /// Check whether a terminal identity is known to support mode 2026
+    pub fn detect_synchronized_output(term: &str, term_program: &str, version: &str) -> bool {
+        let known_term = ["kitty", "foot", "alacritty", "ghostty", "contour"]
+            .iter()
+            .any(|name| term.contains(name));
+        let known_program = ["WezTerm", "ghostty", "contour", "iTerm.app"].contains(&term_program);
+
+        // tmux gained synchronized output support in 3.4
+        let tmux_supports = term_program == "tmux" && {
+            let mut parts = version.split('.').map(|p| {
+                p.chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>()
+                    .parse::<u32>()
+                    .unwrap_or(0)
+            });
+            let major = parts.next().unwrap_or(0);
+            let minor = parts.next().unwrap_or(0);
+            (major, minor) >= (3, 4)
+        };
+
+        known_term || known_program || tmux_supports
+    }
+
     /// Check if we need tmux passthrough for Kitty graphics
     pub fn needs_kitty_passthrough(&self) -> bool {
         self.kitty_graphics && self.in_multiplexer
@@ -223,6 +334,31 @@ mod tests {
         assert_eq!(geom.pixel_height, Some(480));
     }
 
+    #[test]
+    fn test_synchronized_output_detection() {
+        assert!(TerminalCapabilities::detect_synchronized_output("xterm-kitty", "", ""));
+        assert!(TerminalCapabilities::detect_synchronized_output("foot", "", ""));
+        assert!(TerminalCapabilities::detect_synchronized_output("xterm-256color", "WezTerm", ""));
+        assert!(TerminalCapabilities::detect_synchronized_output("screen", "tmux", "3.4"));
+        assert!(TerminalCapabilities::detect_synchronized_output("screen", "tmux", "3.5a"));
+        assert!(!TerminalCapabilities::detect_synchronized_output("screen", "tmux", "3.3a"));
+        assert!(!TerminalCapabilities::detect_synchronized_output("xterm-256color", "", ""));
+    }
+
+    #[test]
+    fn test_notification_detection() {
+        use NotificationProtocol::*;
+        let detect = TerminalCapabilities::detect_notifications;
+        assert_eq!(detect("xterm-kitty", ""), Some(Kitty));
+        assert_eq!(detect("foot", ""), Some(Osc777));
+        assert_eq!(detect("xterm-256color", "iTerm.app"), Some(Osc9));
+        assert_eq!(detect("xterm-256color", ""), None);
+        assert!(TerminalCapabilities::detect_progress("xterm-ghostty", ""));
+        assert!(!TerminalCapabilities::detect_progress("foot", ""));
+        assert!(TerminalCapabilities::detect_styled_underlines("xterm-kitty", ""));
+        assert!(!TerminalCapabilities::detect_styled_underlines("xterm-256color", ""));
+    }
+
     #[test]
     fn test_capabilities_detect() {
         let caps = TerminalCapabilities::detect();
@@ -0,0 +1,37 @@
+//! Testing utilities - render components without a terminal or GPU
+//!
+//! `TestRenderer` keeps every frame in an in-memory cell grid so tests can
+//! assert on text, layout, and resolved styles. `Snapshot` turns that grid
+//! into a golden text file for `assert_snapshot!`.
+//!
+//! ```
+//! use mkui::{Renderer, Style, TestRenderer};
+//!
+//! let mut renderer = TestRenderer::new(20, 2);
+//! renderer.move_cursor(2, 1).unwrap();
+//! renderer.write_styled("hello", &Style::new().bold(true)).unwrap();
+//!
+//! assert_eq!(renderer.row_text(1), "  hello");
+//! assert_eq!(renderer.find("hello"), Some((2, 1)));
+//! ```
+
+mod renderer;
+mod snapshot;
+
+pub use renderer::{StyledSpan, TestRenderer};
+pub use snapshot::{describe_style, snapshot_path, Snapshot, UPDATE_SNAPSHOTS_ENV};
+
+use crate::i18n::{AccessibilitySettings, Locale, TextDirection};
+use crate::theme::Theme;
+
+/// Default theme with environment-derived settings pinned
+///
+/// `Theme::new()` reads the locale and accessibility settings from the
+/// environment; snapshots need the same output on every machine.
+pub fn snapshot_theme() -> Theme {
+    let mut theme = Theme::new();
+    theme.locale = Locale::default();
+    theme.text_direction = TextDirection::LeftToRight;
+    theme.accessibility = AccessibilitySettings::new();
+    theme
+}
@@ -0,0 +1,427 @@
+//! In-memory renderer that records a cell grid for assertions
+
+use crate::layout::Rect;
+use crate::render::{
+    Cell, CellGrid, ClipStack, CursorStyle, DirtyRegion, ImageParams, LayerStack, Progress,
+    Renderer,
+};
+use crate::style::Style;
+use crate::theme::Color;
+use anyhow::Result;
+use std::cell::OnceCell;
+
+/// A run of consecutive cells sharing one style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    /// Column of the first cell in the run
+    pub col: u16,
+    /// Characters in the run
+    pub text: String,
+    /// Style shared by every cell in the run
+    pub style: Style,
+}
+
+/// Renderer that draws into an in-memory cell grid
+///
+/// Always compiled and independent of any tty, so components can be
+/// rendered and inspected from plain unit tests. Overlay layers are
+/// composited the same way the terminal backend does it, on the first
+/// read after a change rather than on every write.
+pub struct TestRenderer {
+    /// Base layer content, read directly while there are no overlays
+    base: CellGrid,
+    /// `base` with the overlays on top, built lazily and reset on writes
+    composed: OnceCell<CellGrid>,
+    layers: LayerStack,
+    cursor: (u16, u16),
+    cursor_visible: bool,
+    cursor_style: CursorStyle,
+    title: Option<String>,
+    notifications: Vec<(String, String)>,
+    progress: Progress,
+    clips: ClipStack,
+    images: Vec<Rect>,
+    dirty: DirtyRegion,
+    frames: usize,
+}
+
+impl TestRenderer {
+    /// Create a renderer with a blank cols×rows surface
+    pub fn new(cols: u16, rows: u16) -> Self {
+        TestRenderer {
+            base: CellGrid::new(cols, rows),
+            composed: OnceCell::new(),
+            layers: LayerStack::new(),
+            cursor: (0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::default(),
+            title: None,
+            notifications: Vec::new(),
+            progress: Progress::Hidden,
+            clips: ClipStack::new(),
+            images: Vec::new(),
+            dirty: DirtyRegion::new(),
+            frames: 0,
+        }
+    }
+
+    /// Get the underlying cell grid
+    pub fn grid(&self) -> &CellGrid {
+        if self.layers.is_empty() {
+            return &self.base;
+        }
+        self.composed.get_or_init(|| self.layers.compose(&self.base))
+    }
+
+    /// Get the cell at a position
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.grid().get(x, y)
+    }
+
+    /// Get the text of a row with trailing blanks removed
+    pub fn row_text(&self, y: u16) -> String {
+        let text: String = self.grid().row(y).iter().map(|c| c.symbol()).collect();
+        text.trim_end().to_string()
+    }
+
+    /// Get the whole surface as text, one line per row
+    pub fn text(&self) -> String {
+        (0..self.grid().rows())
+            .map(|y| self.row_text(y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Find the first occurrence of text, returning its (col, row)
+    pub fn find(&self, needle: &str) -> Option<(u16, u16)> {
+        if needle.is_empty() {
+            return None;
+        }
+        (0..self.grid().rows()).find_map(|y| {
+            let cells = self.grid().row(y);
+            (0..cells.len())
+                .filter(|&x| cells[x].width > 0)
+                .find(|&x| {
+                    let mut rest = needle;
+                    for cell in &cells[x..] {
+                        match rest.strip_prefix(cell.symbol()) {
+                            Some("") => return true,
+                            Some(tail) => rest = tail,
+                            None => return false,
+                        }
+                    }
+                    false
+                })
+                .map(|x| (x as u16, y))
+        })
+    }
+
+    /// Split a row into runs of identically styled cells
+    pub fn styled_spans(&self, y: u16) -> Vec<StyledSpan> {
+        let mut spans: Vec<StyledSpan> = Vec::new();
+        for (x, cell) in self.grid().row(y).iter().enumerate() {
+            match spans.last_mut() {
+                Some(span) if span.style == cell.style => span.text.push_str(cell.symbol()),
+                _ => spans.push(StyledSpan {
+                    col: x as u16,
+                    text: cell.symbol().to_string(),
+                    style: cell.style,
+                }),
+            }
+        }
+        spans
+    }
+
+    /// Current cursor position (col, row)
+    pub fn cursor(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    /// Whether the cursor is currently shown
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Cursor style last set with `set_cursor_style`
+    pub fn cursor_style(&self) -> CursorStyle {
+        self.cursor_style
+    }
+
+    /// Title last set with `set_title`
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Every notification sent, as (title, body)
+    pub fn notifications(&self) -> &[(String, String)] {
+        &self.notifications
+    }
+
+    /// Progress last reported with `set_progress`
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Cell areas covered by images rendered since the last `clear_images`
+    pub fn images(&self) -> &[Rect] {
+        &self.images
+    }
+
+    /// Number of completed frames (`end_frame` calls)
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    /// Resize the surface, keeping content that still fits
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.base.resize(cols, rows);
+        self.layers.resize(cols, rows);
+        self.invalidate();
+    }
+
+    /// Drop the composited surface after a change to any layer
+    fn invalidate(&mut self) {
+        self.composed.take();
+    }
+
+    fn record_image(&mut self, params: &ImageParams) {
+        let w = params.width_cells.unwrap_or((params.width / 10) as u16);
+        let h = params.height_cells.unwrap_or((params.height / 20) as u16);
+        self.images.push(Rect::new(params.col, params.row, w, h));
+        self.dirty.mark_region(params.col, params.row, w, h);
+    }
+}
+
+impl Renderer for TestRenderer {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn write_text(&mut self, text: &str) -> Result<()> {
+        self.write_styled(text, &Style::new())
+    }
+
+    fn write_styled(&mut self, text: &str, style: &Style) -> Result<()> {
+        self.cursor.0 = self
+            .layers
+            .target(&mut self.base)
+            .put_str_clipped(self.cursor, text, *style, self.clips.current());
+        self.invalidate();
+        Ok(())
+    }
+
+    fn write_repeated(&mut self, ch: char, count: usize) -> Result<()> {
+        let text: String = std::iter::repeat_n(ch, count).collect();
+        self.write_text(&text)
+    }
+
+    fn move_cursor(&mut self, col: u16, row: u16) -> Result<()> {
+        self.cursor = (col, row);
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> Result<()> {
+        self.cursor_visible = false;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> Result<()> {
+        self.cursor_visible = true;
+        Ok(())
+    }
+
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        self.cursor_style = style;
+        Ok(())
+    }
+
+    fn set_title(&mut self, title: &str) -> Result<()> {
+        self.title = Some(title.to_string());
+        Ok(())
+    }
+
+    fn notify(&mut self, title: &str, body: &str) -> Result<()> {
+        self.notifications.push((title.to_string(), body.to_string()));
+        Ok(())
+    }
+
+    fn set_progress(&mut self, progress: Progress) -> Result<()> {
+        self.progress = progress;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.base.clear();
+        self.layers.clear_all();
+        self.invalidate();
+        self.dirty.mark_all(self.base.cols(), self.base.rows());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn push_clip(&mut self, rect: Rect) {
+        self.clips.push(rect);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, z: u16) {
+        self.layers.push(z, self.base.cols(), self.base.rows());
+        self.invalidate();
+    }
+
+    fn pop_layer(&mut self) {
+        self.layers.pop();
+    }
+
+    fn clear_layer(&mut self, z: u16) {
+        if let Some(area) = self.layers.clear(z) {
+            self.dirty.mark_region(area.x, area.y, area.width, area.height);
+            self.invalidate();
+        }
+    }
+
+    fn fill_rect(&mut self, bounds: Rect, color: Color) -> Result<()> {
+        let bounds = self.clips.apply(bounds);
+        self.layers
+            .target(&mut self.base)
+            .fill_rect(bounds, Cell::new(' ', Style::new().bg(color)));
+        self.invalidate();
+        Ok(())
+    }
+
+    fn render_image(&mut self, params: &ImageParams) -> Result<()> {
+        self.record_image(params);
+        Ok(())
+    }
+
+    fn render_image_rgba(&mut self, params: &ImageParams) -> Result<()> {
+        self.record_image(params);
+        Ok(())
+    }
+
+    fn clear_images(&mut self) -> Result<()> {
+        self.images.clear();
+        Ok(())
+    }
+
+    fn dimensions(&self) -> (u16, u16) {
+        (self.base.cols(), self.base.rows())
+    }
+
+    fn dirty_region(&self) -> &DirtyRegion {
+        &self.dirty
+    }
+
+    fn mark_dirty(&mut self, col: u16, row: u16, width: u16, height: u16) {
+        self.dirty.mark_region(col, row, width, height);
+    }
+
+    fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+
+    fn begin_frame(&mut self) -> Result<()> {
+        self.hide_cursor()
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        self.show_cursor()?;
+        self.frames += 1;
+        self.clear_dirty();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::Component;
+    use crate::components::Text;
+    use crate::context::RenderContext;
+    use crate::slots::Slots;
+    use crate::theme::Theme;
+
+    #[test]
+    fn test_row_text_and_find() {
+        let mut renderer = TestRenderer::new(10, 3);
+        renderer.move_cursor(3, 2).unwrap();
+        renderer.write_text("abc").unwrap();
+
+        assert_eq!(renderer.row_text(2), "   abc");
+        assert_eq!(renderer.row_text(0), "");
+        assert_eq!(renderer.find("bc"), Some((4, 2)));
+        assert_eq!(renderer.find("zz"), None);
+        assert_eq!(renderer.cursor(), (6, 2));
+    }
+
+    #[test]
+    fn test_find_counts_columns() {
+        let mut renderer = TestRenderer::new(10, 1);
+        renderer.write_text("日本 ok").unwrap();
+        assert_eq!(renderer.find("ok"), Some((5, 0)));
+        assert_eq!(renderer.find("本"), Some((2, 0)));
+        assert_eq!(renderer.cursor(), (7, 0));
+    }
+
+    #[test]
+    fn test_styled_spans() {
+        let bold = Style::new().bold(true);
+        let mut renderer = TestRenderer::new(6, 1);
+        renderer.move_cursor(1, 0).unwrap();
+        renderer.write_styled("ab", &bold).unwrap();
+
+        let spans = renderer.styled_spans(0);
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1], StyledSpan { col: 1, text: "ab".into(), style: bold });
+        assert_eq!(spans[2].col, 3);
+    }
+
+    #[test]
+    fn test_fill_rect_sets_background() {
+        let color = Color::Rgb(1, 2, 3);
+        let mut renderer = TestRenderer::new(4, 4);
+        renderer.fill_rect(Rect::new(1, 1, 2, 2), color).unwrap();
+
+        assert_eq!(renderer.cell(1, 1).and_then(|c| c.style.bg), Some(color));
+        assert_eq!(renderer.cell(0, 0).and_then(|c| c.style.bg), None);
+    }
+
+    #[test]
+    fn test_overlay_reads_follow_later_writes() {
+        let mut renderer = TestRenderer::new(6, 1);
+        renderer.write_text("base").unwrap();
+        renderer.push_layer(1);
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("x").unwrap();
+        assert_eq!(renderer.row_text(0), "xase");
+
+        renderer.write_text("y").unwrap();
+        renderer.pop_layer();
+        assert_eq!(renderer.row_text(0), "xyse");
+
+        renderer.clear_layer(1);
+        assert_eq!(renderer.row_text(0), "base");
+    }
+
+    #[test]
+    fn test_renders_component() {
+        let theme = Theme::new();
+        let slots = Slots::new();
+        let ctx = RenderContext::new(&theme, &slots);
+        let mut renderer = TestRenderer::new(12, 1);
+        let mut text = Text::new("Hi there");
+
+        text.render(&mut renderer, Rect::new(0, 0, 12, 1), &ctx).unwrap();
+        assert_eq!(renderer.find("Hi there"), Some((0, 0)));
+    }
+}
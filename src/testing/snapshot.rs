@@ -0,0 +1,279 @@
+//! Golden snapshot testing for rendered components
+//!
+//! A snapshot is a plain-text picture of a fixed-size surface followed by the
+//! style runs of each row, so both layout and colours show up in review:
+//!
+//! ```text
+//! size 12x2
+//! |Hello       |
+//! |  world     |
+//! styles
+//! 0 0..5 bold fg=#ffffff
+//! ```
+//!
+//! Set `MKUI_UPDATE_SNAPSHOTS=1` to write the current output to the snapshot
+//! files instead of comparing against them.
+
+use super::TestRenderer;
+use crate::component::Component;
+use crate::context::RenderContext;
+use crate::layout::Rect;
+use crate::slots::Slots;
+use crate::style::Style;
+use crate::theme::{Color, Theme};
+use anyhow::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that switches assertions to rewriting snapshots
+pub const UPDATE_SNAPSHOTS_ENV: &str = "MKUI_UPDATE_SNAPSHOTS";
+
+/// Assert that a snapshot matches `snapshots/<name>.snap` next to the calling file
+///
+/// ```ignore
+/// let snapshot = Snapshot::render(&mut popup, 30, 8, &ctx)?;
+/// mkui::assert_snapshot!("popup_centered", snapshot);
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $snapshot:expr) => {
+        $snapshot.assert_matches($crate::testing::snapshot_path(
+            env!("CARGO_MANIFEST_DIR"),
+            file!(),
+            $name,
+        ))
+    };
+}
+
+/// Resolve the snapshot file for a test in `source_file`
+pub fn snapshot_path(manifest_dir: &str, source_file: &str, name: &str) -> PathBuf {
+    let source = Path::new(manifest_dir).join(source_file);
+    source
+        .parent()
+        .unwrap_or(Path::new(manifest_dir))
+        .join("snapshots")
+        .join(format!("{}.snap", name))
+}
+
+/// Text rendering of a surface used for golden comparisons
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    contents: String,
+}
+
+impl Snapshot {
+    /// Render a component into a fresh cols×rows surface and capture it
+    pub fn render(
+        component: &mut dyn Component,
+        cols: u16,
+        rows: u16,
+        ctx: &RenderContext,
+    ) -> Result<Self> {
+        let mut renderer = TestRenderer::new(cols, rows);
+        component.render(&mut renderer, Rect::fullscreen(cols, rows), ctx)?;
+        Ok(Self::capture(&renderer))
+    }
+
+    /// Render a component with a theme and no slot content
+    pub fn render_with_theme(
+        component: &mut dyn Component,
+        cols: u16,
+        rows: u16,
+        theme: &Theme,
+    ) -> Result<Self> {
+        let slots = Slots::new();
+        Self::render(component, cols, rows, &RenderContext::new(theme, &slots))
+    }
+
+    /// Capture the current contents of a test renderer
+    pub fn capture(renderer: &TestRenderer) -> Self {
+        let grid = renderer.grid();
+        let mut contents = format!("size {}x{}\n", grid.cols(), grid.rows());
+
+        for y in 0..grid.rows() {
+            let row: String = grid.row(y).iter().map(|c| c.symbol()).collect();
+            contents.push_str(&format!("|{}|\n", row));
+        }
+
+        contents.push_str("styles\n");
+        for y in 0..grid.rows() {
+            for span in renderer.styled_spans(y) {
+                if span.style.is_empty() {
+                    continue;
+                }
+                let end = span.col as usize + crate::unicode::width(&span.text);
+                contents.push_str(&format!(
+                    "{} {}..{} {}\n",
+                    y,
+                    span.col,
+                    end,
+                    describe_style(&span.style)
+                ));
+            }
+        }
+
+        Snapshot { contents }
+    }
+
+    /// Get the snapshot text
+    pub fn as_str(&self) -> &str {
+        &self.contents
+    }
+
+    /// Compare against a snapshot file, panicking with a diff on mismatch
+    ///
+    /// Writes the file instead when `MKUI_UPDATE_SNAPSHOTS` is set.
+    pub fn assert_matches(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+
+        if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).expect("create snapshot directory");
+            }
+            std::fs::write(path, &self.contents).expect("write snapshot");
+            return;
+        }
+
+        let Ok(expected) = std::fs::read_to_string(path) else {
+            panic!(
+                "missing snapshot {}\n(set {}=1 to create it)\n\n{}",
+                path.display(),
+                UPDATE_SNAPSHOTS_ENV,
+                self.contents
+            );
+        };
+
+        if expected != self.contents {
+            panic!(
+                "snapshot mismatch: {}\n(set {}=1 to accept the new output)\n\n{}",
+                path.display(),
+                UPDATE_SNAPSHOTS_ENV,
+                diff_lines(&expected, &self.contents)
+            );
+        }
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.contents)
+    }
+}
+
+/// Describe a style as space-separated attributes (e.g. `bold fg=#ffffff`)
+pub fn describe_style(style: &Style) -> String {
+    let flags = [
+        ("bold", style.bold),
+        ("dim", style.dim),
+        ("italic", style.italic),
+        ("underline", style.underline),
+        ("strikethrough", style.strikethrough),
+        ("overline", style.overline),
+        ("blink", style.blink),
+        ("reverse", style.reverse),
+    ];
+
+    let mut parts: Vec<String> = flags
+        .iter()
+        .filter_map(|(name, value)| match value {
+            Some(true) => Some(name.to_string()),
+            Some(false) => Some(format!("-{}", name)),
+            None => None,
+        })
+        .collect();
+
+    if let Some(fg) = &style.fg {
+        parts.push(format!("fg={}", describe_color(fg)));
+    }
+    if let Some(bg) = &style.bg {
+        parts.push(format!("bg={}", describe_color(bg)));
+    }
+    if let Some(shape) = &style.underline_style {
+        parts.push(format!("underline-style={:?}", shape).to_lowercase());
+    }
+    if let Some(color) = &style.underline_color {
+        parts.push(format!("underline-color={}", describe_color(color)));
+    }
+    if let Some(link) = &style.link {
+        parts.push(format!("link={}", link.uri()));
+    }
+
+    parts.join(" ")
+}
+
+fn describe_color(color: &Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::Palette256(index) => format!("palette({})", index),
+        Color::Ansi16(ansi) => format!("{:?}", ansi).to_lowercase(),
+        Color::Basic(basic) => format!("{:?}", basic).to_lowercase(),
+    }
+}
+
+/// Line diff of two snapshots (LCS based), prefixing lines with `-`/`+`/` `
+fn diff_lines(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] = length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut out = String::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::Renderer;
+
+    #[test]
+    fn test_capture_format() {
+        let mut renderer = TestRenderer::new(6, 2);
+        renderer.move_cursor(1, 1).unwrap();
+        renderer
+            .write_styled("ab", &Style::new().bold(true).fg(Color::Rgb(255, 0, 16)))
+            .unwrap();
+
+        let snapshot = Snapshot::capture(&renderer);
+        assert_eq!(
+            snapshot.as_str(),
+            "size 6x2\n|      |\n| ab   |\nstyles\n1 1..3 bold fg=#ff0010\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_marks_changed_lines() {
+        let diff = diff_lines("a\nb\nc\n", "a\nx\nc\n");
+        assert_eq!(diff, "  a\n- b\n+ x\n  c\n");
+    }
+
+    #[test]
+    fn test_snapshot_path() {
+        let path = snapshot_path("/repo", "src/components/list.rs", "list_basic");
+        assert_eq!(path, PathBuf::from("/repo/src/components/snapshots/list_basic.snap"));
+    }
+}
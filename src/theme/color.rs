@@ -4,7 +4,7 @@
 use crate::terminal::TerminalCapabilities;
 
 /// Color representation with automatic degradation support
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Color {
     /// True color RGB
     Rgb(u8, u8, u8),
@@ -17,7 +17,7 @@ pub enum Color {
 }
 
 /// 16-color ANSI colors
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AnsiColor {
     /// ANSI black (color 0)
     Black,
@@ -54,7 +54,7 @@ pub enum AnsiColor {
 }
 
 /// Basic 8 colors
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BasicColor {
     /// Basic black
     Black,
@@ -521,6 +521,12 @@ mod tests {
             colors_256: true,
             in_multiplexer: false,
             mouse: true,
+            synchronized_output: false,
+            title: false,
+            notifications: None,
+            progress: false,
+            hyperlinks: false,
+            styled_underlines: false,
         };
 
         let white = Color::white();
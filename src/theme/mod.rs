@@ -225,6 +225,8 @@ impl Theme {
     }
 
     /// Style for underlined link text
+    ///
+    /// Add a target with `Style::link` to make the text clickable.
     pub fn link_style(&self) -> crate::style::Style {
         crate::style::Style::new().fg(self.link_fg).underline(true)
     }
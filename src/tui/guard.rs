@@ -0,0 +1,295 @@
+//! Terminal restoration - undo mkui's terminal modes on exit or panic
+//!
+//! `EventPoller` and `TerminalRenderer` record every mode they switch on, so
+//! restoration only touches what mkui changed. `restore_terminal` writes
+//! straight to /dev/tty and needs no renderer, which lets the panic hook
+//! run it even when destructors never will (`panic = "abort"`).
+
+use crate::tui::osc;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::{Once, OnceLock};
+
+/// Terminal modes mkui can switch on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    /// Raw (non-canonical, no echo) input
+    Raw = 1,
+    /// Mouse event reporting
+    MouseCapture = 1 << 1,
+    /// Focus in/out reporting
+    FocusChange = 1 << 2,
+    /// Alternate screen buffer
+    AltScreen = 1 << 3,
+    /// Cursor shape or color changed from the terminal's default
+    CursorStyle = 1 << 4,
+    /// User's title saved on the title stack
+    Title = 1 << 5,
+    /// Progress indicator shown
+    Progress = 1 << 6,
+    /// Kitty keyboard enhancement flags pushed on the normal screen
+    KeyboardEnhancement = 1 << 7,
+    /// Kitty keyboard enhancement flags pushed on the alternate screen
+    AltKeyboardEnhancement = 1 << 8,
+}
+
+/// Push the kitty keyboard flags mkui uses: disambiguate escape codes,
+/// report event types, report alternate keys, and report all keys as
+/// escape codes (so text keys send releases too)
+///
+/// Associated text (flag 16) isn't requested: crossterm drops it, so
+/// `EventPoller` derives key text from the key instead.
+pub(crate) const PUSH_KEYBOARD_FLAGS: &[u8] = b"\x1b[>15u";
+
+/// Pop the flags pushed by `PUSH_KEYBOARD_FLAGS`
+pub(crate) const POP_KEYBOARD_FLAGS: &[u8] = b"\x1b[<u";
+
+static ENABLED: AtomicU16 = AtomicU16::new(0);
+static KEYBOARD_WANTED: AtomicBool = AtomicBool::new(false);
+static HOOK: Once = Once::new();
+
+/// Whether the terminal speaks the kitty keyboard protocol, asked once
+pub(crate) fn keyboard_enhancement_supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false))
+}
+
+/// Record that a mode was switched on or off
+pub(crate) fn set_mode(mode: Mode, enabled: bool) {
+    if enabled {
+        ENABLED.fetch_or(mode as u16, Ordering::SeqCst);
+    } else {
+        ENABLED.fetch_and(!(mode as u16), Ordering::SeqCst);
+    }
+}
+
+/// Bit set of the modes currently switched on
+pub(crate) fn enabled_modes() -> u16 {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Ask for the kitty keyboard flags, or stop asking, returning the bytes
+/// that bring the current screen in line
+///
+/// `EventPoller` asks while it lives. Kitty keeps a flag stack per screen,
+/// so the other screen catches up when `TerminalRenderer` enters or leaves
+/// the alternate screen (see `sync_keyboard_flags`).
+pub(crate) fn want_keyboard_flags(wanted: bool) -> &'static [u8] {
+    KEYBOARD_WANTED.store(wanted, Ordering::SeqCst);
+    sync_keyboard_flags()
+}
+
+/// Push or pop the kitty keyboard flags on the screen now showing, so they
+/// are set exactly while wanted, returning the bytes to write
+pub(crate) fn sync_keyboard_flags() -> &'static [u8] {
+    let wanted = KEYBOARD_WANTED.load(Ordering::SeqCst);
+    match keyboard_change(enabled_modes(), wanted) {
+        Some((mode, push)) => {
+            set_mode(mode, push);
+            if push {
+                PUSH_KEYBOARD_FLAGS
+            } else {
+                POP_KEYBOARD_FLAGS
+            }
+        }
+        None => b"",
+    }
+}
+
+/// Pop the flags off the alternate screen's stack, returning the bytes to
+/// write just before leaving it
+pub(crate) fn release_alt_keyboard_flags() -> &'static [u8] {
+    if enabled_modes() & Mode::AltKeyboardEnhancement as u16 == 0 {
+        return b"";
+    }
+    set_mode(Mode::AltKeyboardEnhancement, false);
+    POP_KEYBOARD_FLAGS
+}
+
+/// The flag mode of the current screen and whether to push (or pop) it
+fn keyboard_change(enabled: u16, wanted: bool) -> Option<(Mode, bool)> {
+    let mode = if enabled & Mode::AltScreen as u16 != 0 {
+        Mode::AltKeyboardEnhancement
+    } else {
+        Mode::KeyboardEnhancement
+    };
+    let pushed = enabled & mode as u16 != 0;
+    (pushed != wanted).then_some((mode, wanted))
+}
+
+/// Switch the given modes back on, e.g. after a job-control suspend
+pub(crate) fn enable_modes(modes: u16) -> std::io::Result<()> {
+    if modes & Mode::Raw as u16 != 0 {
+        crossterm::terminal::enable_raw_mode()?;
+    }
+
+    let mut tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    if modes & Mode::MouseCapture as u16 != 0 {
+        crossterm::queue!(tty, crossterm::event::EnableMouseCapture)?;
+    }
+    if modes & Mode::FocusChange as u16 != 0 {
+        crossterm::queue!(tty, crossterm::event::EnableFocusChange)?;
+    }
+    if modes & Mode::KeyboardEnhancement as u16 != 0 {
+        tty.write_all(PUSH_KEYBOARD_FLAGS)?;
+    }
+    if modes & Mode::AltScreen as u16 != 0 {
+        tty.write_all(b"\x1b[?1049h")?;
+        if modes & Mode::AltKeyboardEnhancement as u16 != 0 {
+            tty.write_all(PUSH_KEYBOARD_FLAGS)?;
+        }
+    }
+    tty.flush()?;
+
+    ENABLED.fetch_or(modes, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Escape sequences that undo the given modes, innermost first
+///
+/// Leaves the alternate screen before anything is printed so output lands
+/// on the normal screen; raw mode is handled separately through termios.
+fn restore_sequence(enabled: u16) -> Vec<u8> {
+    // End any half-written synchronized frame, reset the pen, show the cursor
+    let mut out = b"\x1b[?2026l\x1b[0m\x1b[?25h".to_vec();
+    if enabled & Mode::AltScreen as u16 != 0 {
+        if enabled & Mode::AltKeyboardEnhancement as u16 != 0 {
+            out.extend_from_slice(POP_KEYBOARD_FLAGS);
+        }
+        out.extend_from_slice(b"\x1b[?1049l");
+    }
+    if enabled & Mode::KeyboardEnhancement as u16 != 0 {
+        out.extend_from_slice(POP_KEYBOARD_FLAGS);
+    }
+    if enabled & Mode::CursorStyle as u16 != 0 {
+        out.extend_from_slice(b"\x1b[0 q\x1b]112\x07");
+    }
+    if enabled & Mode::Title as u16 != 0 {
+        out.extend_from_slice(osc::POP_TITLE);
+    }
+    if enabled & Mode::Progress as u16 != 0 {
+        out.extend_from_slice(osc::CLEAR_PROGRESS);
+    }
+    if enabled & Mode::FocusChange as u16 != 0 {
+        out.extend_from_slice(b"\x1b[?1004l");
+    }
+    if enabled & Mode::MouseCapture as u16 != 0 {
+        out.extend_from_slice(b"\x1b[?1006l\x1b[?1015l\x1b[?1003l\x1b[?1002l\x1b[?1000l");
+    }
+    out
+}
+
+/// Undo every terminal mode mkui switched on
+///
+/// Safe to call more than once; modes already restored are skipped.
+pub fn restore_terminal() -> std::io::Result<()> {
+    let enabled = ENABLED.swap(0, Ordering::SeqCst);
+    if enabled == 0 {
+        return Ok(());
+    }
+
+    let mut tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    tty.write_all(&restore_sequence(enabled))?;
+    tty.flush()?;
+
+    if enabled & Mode::Raw as u16 != 0 {
+        crossterm::terminal::disable_raw_mode()?;
+    }
+    Ok(())
+}
+
+/// Install a panic hook that restores the terminal before reporting
+///
+/// The previously installed hook runs afterwards, so the panic message is
+/// printed on the normal screen in cooked mode. Installing twice is a no-op.
+pub fn install_panic_hook() {
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal();
+            previous(info);
+        }));
+    });
+}
+
+/// Restores the terminal when dropped and on panic
+///
+/// Create one at the top of `main` and keep it alive for the whole program.
+///
+/// # Example
+/// ```no_run
+/// let _guard = mkui::tui::TerminalGuard::new();
+/// // ... set up EventPoller and TerminalRenderer, run the app ...
+/// ```
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    /// Install the panic hook and return a guard that restores on drop
+    pub fn new() -> Self {
+        install_panic_hook();
+        TerminalGuard { _private: () }
+    }
+}
+
+impl Default for TerminalGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_sequence_order() {
+        let all = Mode::AltScreen as u16 | Mode::FocusChange as u16 | Mode::MouseCapture as u16;
+        let out = String::from_utf8(restore_sequence(all)).unwrap();
+        let alt = out.find("\x1b[?1049l").unwrap();
+        let focus = out.find("\x1b[?1004l").unwrap();
+        let mouse = out.find("\x1b[?1000l").unwrap();
+        assert!(out.find("\x1b[?25h").unwrap() < alt);
+        assert!(alt < focus && focus < mouse);
+    }
+
+    #[test]
+    fn test_keyboard_flags_popped_on_each_screen() {
+        let both = Mode::KeyboardEnhancement as u16 | Mode::AltKeyboardEnhancement as u16;
+        let out = String::from_utf8(restore_sequence(Mode::AltScreen as u16 | both)).unwrap();
+        let leave = out.find("\x1b[?1049l").unwrap();
+        assert_eq!(out.matches("\x1b[<u").count(), 2);
+        assert!(out.find("\x1b[<u").unwrap() < leave && out.rfind("\x1b[<u").unwrap() > leave);
+
+        let out = restore_sequence(Mode::KeyboardEnhancement as u16);
+        assert_eq!(String::from_utf8(out).unwrap().matches("\x1b[<u").count(), 1);
+    }
+
+    #[test]
+    fn test_keyboard_flags_follow_the_current_screen() {
+        let normal = Mode::KeyboardEnhancement;
+        let alt = Mode::AltKeyboardEnhancement;
+        assert_eq!(keyboard_change(0, true), Some((normal, true)));
+        assert_eq!(keyboard_change(normal as u16, true), None);
+        assert_eq!(keyboard_change(normal as u16, false), Some((normal, false)));
+
+        // Entering the alternate screen leaves the normal screen's flags be
+        let on_alt = Mode::AltScreen as u16 | normal as u16;
+        assert_eq!(keyboard_change(on_alt, true), Some((alt, true)));
+        assert_eq!(keyboard_change(on_alt | alt as u16, false), Some((alt, false)));
+    }
+
+    #[test]
+    fn test_restore_sequence_skips_unused_modes() {
+        let out = restore_sequence(Mode::Raw as u16);
+        assert!(!out.windows(8).any(|w| w == b"\x1b[?1049l"));
+        assert!(!out.windows(8).any(|w| w == b"\x1b[?1004l"));
+    }
+}
@@ -0,0 +1,590 @@
+//! Terminal input decoding - events from a raw byte stream
+//!
+//! `EventPoller` reads crossterm's global stdin. When the terminal is on the
+//! other end of a pty, socket, or SSH channel, bytes arrive from elsewhere:
+//! `InputParser` turns them into events as they are pushed in, and
+//! `EventReader` does the same for any blocking `Read`.
+//!
+//! Decodes UTF-8 text, control keys, CSI/SS3 key sequences (with xterm
+//! modifier parameters), kitty keyboard protocol `CSI u` reports (release
+//! and repeat states, super/hyper, and associated text when the peer's
+//! flags ask for it), SGR mouse reports, focus reports, and bracketed
+//! paste. Mouse and focus reporting and the kitty flags must be switched
+//! on through the output (e.g. `crossterm::event::EnableMouseCapture`).
+
+use crate::event::{Event, EventKind, EventSource, Key, KeyState, Modifiers, MouseButton, MouseEvent};
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::io::Read;
+
+const ESC: u8 = 0x1b;
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Outcome of decoding the front of the input buffer
+enum Parsed {
+    /// An event and the number of bytes it used
+    Event(EventKind, usize),
+    /// Bytes that carry no event (unknown or malformed sequences)
+    Skip(usize),
+    /// The sequence continues past the end of the buffer
+    Incomplete,
+}
+
+/// Incremental decoder from terminal input bytes to events
+#[derive(Debug, Default)]
+pub struct InputParser {
+    pending: Vec<u8>,
+    events: VecDeque<Event>,
+}
+
+impl InputParser {
+    /// Create an empty parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a chunk of input
+    ///
+    /// Sequences split across chunks are kept until the rest arrives. A lone
+    /// ESC at the end of a chunk is taken as the Escape key, since terminals
+    /// write a whole escape sequence at once.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+
+        let mut start = 0;
+        while start < self.pending.len() {
+            match parse(&self.pending[start..]) {
+                Parsed::Event(kind, used) => {
+                    self.events.push_back(Event::new(kind));
+                    start += used;
+                }
+                Parsed::Skip(used) => start += used,
+                Parsed::Incomplete => break,
+            }
+        }
+        self.pending.drain(..start);
+
+        if self.pending == [ESC] {
+            self.pending.clear();
+            self.events.push_back(Event::new(key_event(Key::Esc, Modifiers::none())));
+        }
+    }
+
+    /// Take the next decoded event
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+}
+
+/// Blocking event source over any byte stream
+///
+/// # Example
+/// ```no_run
+/// # fn main() -> anyhow::Result<()> {
+/// let pty = std::fs::File::open("/dev/pts/3")?;
+/// let mut events = mkui::tui::EventReader::new(pty);
+/// let event = events.read()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct EventReader<R> {
+    input: R,
+    parser: InputParser,
+}
+
+impl<R: Read> EventReader<R> {
+    /// Read events from `input`
+    pub fn new(input: R) -> Self {
+        EventReader {
+            input,
+            parser: InputParser::new(),
+        }
+    }
+
+    /// Block until the next event; fails once the input is closed
+    pub fn read(&mut self) -> Result<Event> {
+        let mut buf = [0u8; 1024];
+        loop {
+            if let Some(event) = self.parser.next_event() {
+                return Ok(event);
+            }
+            let n = self.input.read(&mut buf)?;
+            if n == 0 {
+                anyhow::bail!("Input closed");
+            }
+            self.parser.feed(&buf[..n]);
+        }
+    }
+
+    /// Get the underlying input back
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+}
+
+impl<R: Read> EventSource for EventReader<R> {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        self.read().map(Some)
+    }
+}
+
+/// Build a key press, deriving its text like the crossterm conversion does
+fn key_event(key: Key, modifiers: Modifiers) -> EventKind {
+    key_state_event(key, KeyState::Pressed, modifiers)
+}
+
+/// Build a key event in any state; releases produce no text
+fn key_state_event(key: Key, state: KeyState, modifiers: Modifiers) -> EventKind {
+    let text = match key {
+        Key::Char(c) if !modifiers.ctrl && !modifiers.alt => Some(c.to_string()),
+        Key::Space if !modifiers.ctrl && !modifiers.alt => Some(" ".to_string()),
+        _ => None,
+    };
+    EventKind::Key {
+        key,
+        state,
+        modifiers,
+        text: text.filter(|_| state != KeyState::Released),
+    }
+}
+
+fn ctrl() -> Modifiers {
+    Modifiers {
+        ctrl: true,
+        ..Modifiers::none()
+    }
+}
+
+fn parse(bytes: &[u8]) -> Parsed {
+    match bytes[0] {
+        ESC => parse_escape(bytes),
+        b'\r' | b'\n' => Parsed::Event(key_event(Key::Enter, Modifiers::none()), 1),
+        b'\t' => Parsed::Event(key_event(Key::Tab, Modifiers::none()), 1),
+        0x7f | 0x08 => Parsed::Event(key_event(Key::Backspace, Modifiers::none()), 1),
+        0x00 => Parsed::Event(key_event(Key::Space, ctrl()), 1),
+        b @ 0x01..=0x1a => Parsed::Event(key_event(Key::Char((b - 1 + b'a') as char), ctrl()), 1),
+        b @ 0x1c..=0x1f => Parsed::Event(key_event(Key::Char((b - 0x1c + b'4') as char), ctrl()), 1),
+        _ => parse_char(bytes),
+    }
+}
+
+/// Decode one UTF-8 character
+fn parse_char(bytes: &[u8]) -> Parsed {
+    let len = match bytes[0] {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => return Parsed::Skip(1),
+    };
+    if bytes.len() < len {
+        return Parsed::Incomplete;
+    }
+    let Some(c) = std::str::from_utf8(&bytes[..len]).ok().and_then(|s| s.chars().next()) else {
+        return Parsed::Skip(1);
+    };
+
+    let key = if c == ' ' { Key::Space } else { Key::Char(c) };
+    let modifiers = Modifiers {
+        shift: c.is_uppercase(),
+        ..Modifiers::none()
+    };
+    Parsed::Event(key_event(key, modifiers), len)
+}
+
+fn parse_escape(bytes: &[u8]) -> Parsed {
+    match bytes.get(1) {
+        None => Parsed::Incomplete,
+        Some(b'[') => parse_csi(bytes),
+        Some(b'O') => match bytes.get(2) {
+            None => Parsed::Incomplete,
+            Some(&b) => match final_key(b) {
+                Some(key) => Parsed::Event(key_event(key, Modifiers::none()), 3),
+                None => Parsed::Skip(3),
+            },
+        },
+        Some(&ESC) => Parsed::Event(key_event(Key::Esc, Modifiers::none()), 1),
+        // ESC before a key means Alt was held
+        Some(_) => match parse(&bytes[1..]) {
+            Parsed::Event(EventKind::Key { key, mut modifiers, .. }, used) => {
+                modifiers.alt = true;
+                Parsed::Event(key_event(key, modifiers), used + 1)
+            }
+            Parsed::Event(_, used) | Parsed::Skip(used) => Parsed::Skip(used + 1),
+            Parsed::Incomplete => Parsed::Incomplete,
+        },
+    }
+}
+
+/// Key named by the final byte of an SS3 or CSI sequence
+fn final_key(b: u8) -> Option<Key> {
+    Some(match b {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'P' => Key::F(1),
+        b'Q' => Key::F(2),
+        b'R' => Key::F(3),
+        b'S' => Key::F(4),
+        _ => return None,
+    })
+}
+
+/// Key named by the first parameter of a `CSI n ~` sequence
+fn tilde_key(n: u16) -> Option<Key> {
+    Some(match n {
+        1 | 7 => Key::Home,
+        2 => Key::Insert,
+        3 => Key::Delete,
+        4 | 8 => Key::End,
+        5 => Key::PageUp,
+        6 => Key::PageDown,
+        11..=15 => Key::F((n - 10) as u8),
+        17..=21 => Key::F((n - 11) as u8),
+        23..=26 => Key::F((n - 12) as u8),
+        28 | 29 => Key::F((n - 13) as u8),
+        31..=34 => Key::F((n - 14) as u8),
+        _ => return None,
+    })
+}
+
+/// Key named by a kitty `CSI u` code: a Unicode codepoint, or one of the
+/// functional keys kitty numbers from 57344 up
+fn kitty_key(code: u32) -> Option<Key> {
+    Some(match code {
+        9 => Key::Tab,
+        13 => Key::Enter,
+        27 => Key::Esc,
+        32 => Key::Space,
+        127 => Key::Backspace,
+        57376..=57387 => Key::F((code - 57376 + 13) as u8),
+        // Keypad keys report what they type or the key they stand in for
+        57399..=57408 => Key::Char(char::from_digit(code - 57399, 10)?),
+        57409 => Key::Char('.'),
+        57410 => Key::Char('/'),
+        57411 => Key::Char('*'),
+        57412 => Key::Char('-'),
+        57413 => Key::Char('+'),
+        57414 => Key::Enter,
+        57415 => Key::Char('='),
+        57417 => Key::Left,
+        57418 => Key::Right,
+        57419 => Key::Up,
+        57420 => Key::Down,
+        57421 => Key::PageUp,
+        57422 => Key::PageDown,
+        57423 => Key::Home,
+        57424 => Key::End,
+        57425 => Key::Insert,
+        57426 => Key::Delete,
+        // Lock, media, and modifier keys on their own
+        57344..=63743 => Key::Null,
+        _ => match char::from_u32(code)? {
+            c if c.is_control() => return None,
+            c => Key::Char(c),
+        },
+    })
+}
+
+/// Decode an xterm or kitty modifier parameter (1 + bitmask)
+///
+/// Kitty adds hyper, meta, and the lock keys above xterm's bits; meta and
+/// the locks have no `Modifiers` field and are dropped.
+fn modifier_param(param: u32) -> Modifiers {
+    let bits = param.saturating_sub(1);
+    Modifiers {
+        shift: bits & 1 != 0,
+        alt: bits & 2 != 0,
+        ctrl: bits & 4 != 0,
+        super_key: bits & 8 != 0,
+        hyper: bits & 16 != 0,
+    }
+}
+
+/// Decode a kitty event type subparameter
+fn event_type(code: u32) -> KeyState {
+    match code {
+        2 => KeyState::Repeat,
+        3 => KeyState::Released,
+        _ => KeyState::Pressed,
+    }
+}
+
+/// Decode a kitty keyboard report: `CSI code[:shifted] ; mods[:type] ; text u`
+fn parse_kitty_key(fields: &[Vec<u32>], modifiers: Modifiers, state: KeyState) -> Option<EventKind> {
+    let codes = fields.first()?;
+    let mut modifiers = modifiers;
+    // With alternate keys reported, prefer the shifted character
+    let shifted = codes
+        .get(1)
+        .filter(|_| modifiers.shift)
+        .and_then(|&code| char::from_u32(code))
+        .filter(|c| !c.is_control());
+    let key = match shifted {
+        Some(c) => Key::Char(c),
+        None => kitty_key(codes[0])?,
+    };
+    if let Key::Char(c) = key {
+        modifiers.shift |= c.is_uppercase();
+    }
+
+    let mut event = key_state_event(key, state, modifiers);
+    let associated: String = fields
+        .get(2)
+        .into_iter()
+        .flatten()
+        .filter_map(|&code| char::from_u32(code))
+        .collect();
+    if let EventKind::Key { text, .. } = &mut event {
+        if !associated.is_empty() {
+            *text = Some(associated);
+        }
+    }
+    Some(event)
+}
+
+fn parse_csi(bytes: &[u8]) -> Parsed {
+    // Parameter and intermediate bytes, then a final byte in 0x40..=0x7e
+    let Some(end) = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)) else {
+        return Parsed::Incomplete;
+    };
+    let len = end + 3;
+    let params = &bytes[2..len - 1];
+    let fin = bytes[len - 1];
+
+    if params.first() == Some(&b'<') {
+        return match parse_sgr_mouse(&params[1..], fin) {
+            Some(mouse) => Parsed::Event(EventKind::Mouse(mouse), len),
+            None => Parsed::Skip(len),
+        };
+    }
+
+    // `;`-separated fields, each with optional `:`-separated subparameters
+    let fields: Vec<Vec<u32>> = std::str::from_utf8(params)
+        .unwrap_or_default()
+        .split(';')
+        .map(|field| field.split(':').map(|n| n.parse().unwrap_or(0)).collect())
+        .collect();
+    let first = fields.first().map_or(0, |field| field[0]);
+    let (modifiers, state) = match fields.get(1) {
+        Some(field) => (
+            modifier_param(field[0]),
+            event_type(field.get(1).copied().unwrap_or(1)),
+        ),
+        None => (Modifiers::none(), KeyState::Pressed),
+    };
+
+    let kind = match fin {
+        b'I' => EventKind::FocusGained,
+        b'O' => EventKind::FocusLost,
+        b'Z' => key_event(
+            Key::Tab,
+            Modifiers {
+                shift: true,
+                ..Modifiers::none()
+            },
+        ),
+        b'~' if first == 200 => return parse_paste(bytes, len),
+        b'~' => match u16::try_from(first).ok().and_then(tilde_key) {
+            Some(key) => key_state_event(key, state, modifiers),
+            None => return Parsed::Skip(len),
+        },
+        b'u' => match parse_kitty_key(&fields, modifiers, state) {
+            Some(event) => event,
+            None => return Parsed::Skip(len),
+        },
+        _ => match final_key(fin) {
+            Some(key) => key_state_event(key, state, modifiers),
+            None => return Parsed::Skip(len),
+        },
+    };
+    Parsed::Event(kind, len)
+}
+
+/// Collect bracketed paste content up to the closing `ESC [ 201 ~`
+fn parse_paste(bytes: &[u8], start: usize) -> Parsed {
+    let body = &bytes[start..];
+    match body.windows(PASTE_END.len()).position(|w| w == PASTE_END) {
+        Some(end) => {
+            let text = String::from_utf8_lossy(&body[..end]).into_owned();
+            Parsed::Event(EventKind::Paste(text), start + end + PASTE_END.len())
+        }
+        None => Parsed::Incomplete,
+    }
+}
+
+/// Decode an SGR (mode 1006) mouse report: `ESC [ < b ; col ; row (M|m)`
+fn parse_sgr_mouse(params: &[u8], fin: u8) -> Option<MouseEvent> {
+    if fin != b'M' && fin != b'm' {
+        return None;
+    }
+    let mut fields = std::str::from_utf8(params).ok()?.split(';');
+    let code: u16 = fields.next()?.parse().ok()?;
+    let col = fields.next()?.parse::<u16>().ok()?.saturating_sub(1);
+    let row = fields.next()?.parse::<u16>().ok()?.saturating_sub(1);
+
+    let modifiers = Modifiers {
+        shift: code & 4 != 0,
+        alt: code & 8 != 0,
+        ctrl: code & 16 != 0,
+        ..Modifiers::none()
+    };
+
+    if code & 64 != 0 {
+        let (delta_x, delta_y) = match code & 3 {
+            0 => (0.0, -1.0),
+            1 => (0.0, 1.0),
+            2 => (-1.0, 0.0),
+            _ => (1.0, 0.0),
+        };
+        return Some(MouseEvent::Scroll {
+            delta_x,
+            delta_y,
+            col,
+            row,
+            modifiers,
+        });
+    }
+
+    if code & 32 != 0 {
+        return Some(MouseEvent::Moved { col, row });
+    }
+
+    let button = match code & 3 {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        2 => MouseButton::Right,
+        _ => return None,
+    };
+    let state = if fin == b'M' {
+        KeyState::Pressed
+    } else {
+        KeyState::Released
+    };
+    Some(MouseEvent::Button {
+        button,
+        state,
+        col,
+        row,
+        modifiers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<EventKind> {
+        let mut parser = InputParser::new();
+        parser.feed(bytes);
+        std::iter::from_fn(|| parser.next_event()).map(|e| e.kind).collect()
+    }
+
+    #[test]
+    fn test_text_and_control_keys() {
+        let events = decode("aé\r\x03".as_bytes());
+        assert_eq!(events.len(), 4);
+        assert!(events[0].is_key_press(Key::Char('a')));
+        assert!(events[1].is_key_press(Key::Char('é')));
+        assert!(events[2].is_key_press(Key::Enter));
+        assert!(events[3].is_ctrl('c'));
+    }
+
+    #[test]
+    fn test_csi_keys_with_modifiers() {
+        let events = decode(b"\x1b[A\x1b[1;5C\x1b[3~\x1b[15~\x1bOP");
+        assert!(events[0].is_key_press(Key::Up));
+        assert_eq!(events[1], key_event(Key::Right, ctrl()));
+        assert!(events[2].is_key_press(Key::Delete));
+        assert!(events[3].is_key_press(Key::F(5)));
+        assert!(events[4].is_key_press(Key::F(1)));
+    }
+
+    #[test]
+    fn test_alt_and_lone_escape() {
+        assert!(decode(b"\x1bx")[0].is_alt('x'));
+        assert!(decode(b"\x1b")[0].is_key_press(Key::Esc));
+    }
+
+    #[test]
+    fn test_split_sequence_waits_for_rest() {
+        let mut parser = InputParser::new();
+        parser.feed(b"\x1b[1;");
+        assert!(parser.next_event().is_none());
+        parser.feed(b"2B");
+        let event = parser.next_event().unwrap();
+        assert!(event.kind.is_key_press(Key::Down));
+    }
+
+    #[test]
+    fn test_kitty_keyboard_reports() {
+        let events = decode(b"\x1b[105;5u\x1b[9u\x1b[27u\x1b[97;1:3u\x1b[1;1:2A");
+        assert!(events[0].is_ctrl('i'));
+        assert!(events[1].is_key_press(Key::Tab));
+        assert!(events[2].is_key_press(Key::Esc));
+        assert_eq!(
+            events[3],
+            EventKind::Key {
+                key: Key::Char('a'),
+                state: KeyState::Released,
+                modifiers: Modifiers::none(),
+                text: None,
+            }
+        );
+        assert!(matches!(events[4], EventKind::Key { key: Key::Up, state: KeyState::Repeat, .. }));
+    }
+
+    #[test]
+    fn test_kitty_alternate_keys_and_text() {
+        let events = decode(b"\x1b[97:65;2;65u\x1b[115;9u\x1b[101;17;233u");
+        assert_eq!(
+            events[0],
+            key_event(Key::Char('A'), Modifiers { shift: true, ..Modifiers::none() })
+        );
+        assert_eq!(
+            events[1],
+            key_event(Key::Char('s'), Modifiers { super_key: true, ..Modifiers::none() })
+        );
+        let EventKind::Key { modifiers, text, .. } = &events[2] else {
+            panic!("expected a key event");
+        };
+        assert!(modifiers.hyper);
+        assert_eq!(text.as_deref(), Some("é"));
+    }
+
+    #[test]
+    fn test_sgr_mouse_and_focus() {
+        let events = decode(b"\x1b[<0;5;3M\x1b[<64;1;1M\x1b[I");
+        assert_eq!(
+            events[0],
+            EventKind::Mouse(MouseEvent::Button {
+                button: MouseButton::Left,
+                state: KeyState::Pressed,
+                col: 4,
+                row: 2,
+                modifiers: Modifiers::none(),
+            })
+        );
+        assert!(matches!(events[1], EventKind::Mouse(MouseEvent::Scroll { delta_y, .. }) if delta_y < 0.0));
+        assert_eq!(events[2], EventKind::FocusGained);
+    }
+
+    #[test]
+    fn test_bracketed_paste() {
+        let mut parser = InputParser::new();
+        parser.feed(b"\x1b[200~hello\x1b");
+        assert!(parser.next_event().is_none());
+        parser.feed(b"[201~");
+        assert_eq!(parser.next_event().unwrap().kind, EventKind::Paste("hello".into()));
+    }
+
+    #[test]
+    fn test_event_reader_over_bytes() {
+        let mut events = EventReader::new(&b"q"[..]);
+        assert!(events.read().unwrap().kind.is_key_press(Key::Char('q')));
+        assert!(events.read().is_err());
+    }
+}
@@ -3,7 +3,20 @@
 //! Provides `TerminalRenderer` which implements the `Renderer` trait for
 //! terminal applications using crossterm, with graphics support via
 //! Kitty, Sixel, Unicode blocks, and Linux framebuffer backends.
+//! `EventReader` and `InputParser` decode input from sources other than
+//! the process's own terminal.
 
+pub(crate) mod guard;
+mod input;
+mod osc;
+mod record;
 mod renderer;
+mod sgr;
+mod viewport;
+mod writer;
 
+pub use guard::{install_panic_hook, restore_terminal, TerminalGuard};
+pub use input::{EventReader, InputParser};
 pub use renderer::TerminalRenderer;
+pub use viewport::Viewport;
+pub use writer::FrameStats;
@@ -0,0 +1,119 @@
+//! Operating system commands - title, notification, and progress sequences
+//!
+//! Text is stripped of control characters before it is embedded, so a
+//! title or message can never terminate the sequence early or smuggle in
+//! its own escapes. Inside tmux, notifications and progress are wrapped in
+//! DCS passthrough because tmux swallows OSC strings it doesn't know.
+
+use crate::render::Progress;
+use crate::terminal::NotificationProtocol;
+use std::io::{self, Write};
+
+/// Save the current title on the terminal's title stack (XTWINOPS 22)
+pub(crate) const PUSH_TITLE: &[u8] = b"\x1b[22;0t";
+
+/// Restore the title saved by `PUSH_TITLE` (XTWINOPS 23)
+pub(crate) const POP_TITLE: &[u8] = b"\x1b[23;0t";
+
+/// Remove the progress indicator
+pub(crate) const CLEAR_PROGRESS: &[u8] = b"\x1b]9;4;0\x07";
+
+/// Set the window and icon title (OSC 0)
+pub(crate) fn title(out: &mut impl Write, title: &str) -> io::Result<()> {
+    write!(out, "\x1b]0;{}\x07", sanitize(title))
+}
+
+/// Show a desktop notification
+///
+/// `id` ties Kitty's title and body chunks to one notification.
+pub(crate) fn notification(
+    out: &mut impl Write,
+    protocol: NotificationProtocol,
+    id: u32,
+    title: &str,
+    body: &str,
+) -> io::Result<()> {
+    let (title, body) = (sanitize(title), sanitize(body));
+    match protocol {
+        NotificationProtocol::Kitty => {
+            write!(out, "\x1b]99;i={}:d=0:p=title;{}\x1b\\", id, title)?;
+            write!(out, "\x1b]99;i={}:d=1:p=body;{}\x1b\\", id, body)
+        }
+        // The title is a `;`-separated field, the body runs to the terminator
+        NotificationProtocol::Osc777 => {
+            write!(out, "\x1b]777;notify;{};{}\x1b\\", title.replace(';', ","), body)
+        }
+        NotificationProtocol::Osc9 => match (title.is_empty(), body.is_empty()) {
+            (true, _) => write!(out, "\x1b]9;{}\x07", body),
+            (false, true) => write!(out, "\x1b]9;{}\x07", title),
+            (false, false) => write!(out, "\x1b]9;{}: {}\x07", title, body),
+        },
+    }
+}
+
+/// Report progress (OSC 9;4)
+pub(crate) fn progress(out: &mut impl Write, progress: Progress) -> io::Result<()> {
+    let (state, percent) = match progress {
+        Progress::Hidden => (0, 0),
+        Progress::Normal(percent) => (1, percent),
+        Progress::Error(percent) => (2, percent),
+        Progress::Indeterminate => (3, 0),
+        Progress::Paused(percent) => (4, percent),
+    };
+    write!(out, "\x1b]9;4;{};{}\x07", state, percent.min(100))
+}
+
+/// Wrap a sequence in tmux DCS passthrough
+pub(crate) fn passthrough(sequence: &[u8]) -> Vec<u8> {
+    let mut out = b"\x1bPtmux;".to_vec();
+    for &byte in sequence {
+        if byte == 0x1b {
+            out.push(0x1b);
+        }
+        out.push(byte);
+    }
+    out.extend_from_slice(b"\x1b\\");
+    out
+}
+
+/// Drop control characters, which would end or corrupt the sequence
+fn sanitize(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(f: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut out = Vec::new();
+        f(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_title_is_sanitized() {
+        let out = encode(|out| title(out, "build\x07\x1b]0;pwned"));
+        assert_eq!(out, "\x1b]0;build]0;pwned\x07");
+    }
+
+    #[test]
+    fn test_notification_protocols() {
+        let kitty = encode(|out| notification(out, NotificationProtocol::Kitty, 3, "Done", "ok"));
+        assert_eq!(
+            kitty,
+            "\x1b]99;i=3:d=0:p=title;Done\x1b\\\x1b]99;i=3:d=1:p=body;ok\x1b\\"
+        );
+        let osc777 = encode(|out| notification(out, NotificationProtocol::Osc777, 0, "a;b", "c;d"));
+        assert_eq!(osc777, "\x1b]777;notify;a,b;c;d\x1b\\");
+        let osc9 = encode(|out| notification(out, NotificationProtocol::Osc9, 0, "Done", "ok"));
+        assert_eq!(osc9, "\x1b]9;Done: ok\x07");
+    }
+
+    #[test]
+    fn test_progress_and_passthrough() {
+        assert_eq!(encode(|out| progress(out, Progress::Normal(150))), "\x1b]9;4;1;100\x07");
+        assert_eq!(encode(|out| progress(out, Progress::Indeterminate)), "\x1b]9;4;3;0\x07");
+        assert_eq!(passthrough(b"\x1b]9;hi\x07"), b"\x1bPtmux;\x1b\x1b]9;hi\x07\x1b\\");
+    }
+}
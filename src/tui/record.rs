@@ -0,0 +1,201 @@
+//! Session recording - asciicast v2 output for bug reports and demos
+//!
+//! A recording is a JSON header line followed by one JSON array per event:
+//! `[seconds, "o", data]` for terminal output, `[seconds, "i", data]` for
+//! input, and `[seconds, "r", "COLSxROWS"]` for resizes. The writer thread
+//! records each frame right after writing it, so the cast sees exactly the
+//! bytes the terminal did.
+
+use crate::event::{Event, EventKind, Key};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Handle to an asciicast v2 stream, shared with the writer thread
+#[derive(Clone)]
+pub(crate) struct Recorder {
+    inner: Arc<Mutex<Cast>>,
+}
+
+struct Cast {
+    out: Box<dyn Write + Send>,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Start a recording by writing the header for a cols×rows terminal
+    pub fn start(mut out: impl Write + Send + 'static, cols: u16, rows: u16) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let term = std::env::var("TERM").unwrap_or_default();
+        writeln!(
+            out,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"env\": {{\"TERM\": {}}}}}",
+            cols,
+            rows,
+            timestamp,
+            json_string(&term)
+        )?;
+
+        Ok(Recorder {
+            inner: Arc::new(Mutex::new(Cast {
+                out: Box::new(out),
+                start: Instant::now(),
+            })),
+        })
+    }
+
+    /// Record bytes sent to the terminal
+    pub fn output(&self, bytes: &[u8]) {
+        self.event("o", &String::from_utf8_lossy(bytes));
+    }
+
+    /// Record an input event, if it maps to terminal input bytes
+    pub fn input(&self, event: &Event) {
+        if let Some(data) = encode_input(event) {
+            self.event("i", &data);
+        }
+    }
+
+    /// Record a terminal resize
+    pub fn resize(&self, cols: u16, rows: u16) {
+        self.event("r", &format!("{}x{}", cols, rows));
+    }
+
+    /// Flush buffered events to the output
+    pub fn finish(&self) {
+        let _ = self.lock().out.flush();
+    }
+
+    fn event(&self, code: &str, data: &str) {
+        let mut cast = self.lock();
+        let time = cast.start.elapsed().as_secs_f64();
+        // A failing recording must never take the UI down with it
+        let _ = writeln!(cast.out, "[{:.6}, \"{}\", {}]", time, code, json_string(data));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cast> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Quote a string as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Bytes a terminal would have sent for an event
+///
+/// Mouse, focus, and window events have no stable byte form and are skipped.
+fn encode_input(event: &Event) -> Option<String> {
+    let EventKind::Key {
+        key,
+        modifiers,
+        text,
+        ..
+    } = &event.kind
+    else {
+        return match &event.kind {
+            EventKind::Paste(data) => Some(data.clone()),
+            _ => None,
+        };
+    };
+
+    if let Some(text) = text {
+        return Some(text.clone());
+    }
+
+    let sequence = match key {
+        Key::Char(c) if modifiers.ctrl && c.is_ascii_lowercase() => {
+            ((*c as u8 - b'a' + 1) as char).to_string()
+        }
+        Key::Char(c) if modifiers.alt => format!("\x1b{}", c),
+        Key::Char(c) => c.to_string(),
+        Key::Space => " ".into(),
+        Key::Enter => "\r".into(),
+        Key::Tab if modifiers.shift => "\x1b[Z".into(),
+        Key::Tab => "\t".into(),
+        Key::Backspace => "\x7f".into(),
+        Key::Esc => "\x1b".into(),
+        Key::Up => "\x1b[A".into(),
+        Key::Down => "\x1b[B".into(),
+        Key::Right => "\x1b[C".into(),
+        Key::Left => "\x1b[D".into(),
+        Key::Home => "\x1b[H".into(),
+        Key::End => "\x1b[F".into(),
+        Key::Insert => "\x1b[2~".into(),
+        Key::Delete => "\x1b[3~".into(),
+        Key::PageUp => "\x1b[5~".into(),
+        Key::PageDown => "\x1b[6~".into(),
+        Key::F(n @ 1..=4) => format!("\x1bO{}", (b'P' + n - 1) as char),
+        Key::F(_) | Key::Null => return None,
+    };
+    Some(sequence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Modifiers;
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cast_lines() {
+        let capture = Capture::default();
+        let recorder = Recorder::start(capture.clone(), 80, 24).unwrap();
+        recorder.output(b"\x1b[1;1Hhi \"there\"");
+        recorder.input(&Event::key_with_mods(
+            Key::Char('c'),
+            Modifiers {
+                ctrl: true,
+                ..Modifiers::none()
+            },
+        ));
+        recorder.resize(100, 30);
+
+        let text = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24,"));
+        assert!(lines[1].ends_with(", \"o\", \"\\u001b[1;1Hhi \\\"there\\\"\"]"));
+        assert!(lines[2].ends_with(", \"i\", \"\\u0003\"]"));
+        assert!(lines[3].ends_with(", \"r\", \"100x30\"]"));
+    }
+
+    #[test]
+    fn test_encode_input() {
+        let event = Event::new(EventKind::FocusGained);
+        assert_eq!(encode_input(&event), None);
+        assert_eq!(encode_input(&Event::key(Key::Up)), Some("\x1b[A".into()));
+    }
+}
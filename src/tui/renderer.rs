@@ -2,31 +2,84 @@
 //!
 //! Output goes through /dev/tty via a background writer thread to prevent
 //! blocking the main thread on slow connections (SSH). This avoids stdout
-//! lock contention with crossterm's raw mode.
+//! lock contention with crossterm's raw mode. `with_writer` swaps /dev/tty
+//! for any output (a pty, socket, or SSH channel).
+//!
+//! Text never goes straight to the frame buffer: writes land in a back
+//! `CellGrid`, and `end_frame` diffs it against the front grid (what the
+//! terminal is known to show) so only changed cells are emitted.
 
+use crate::event::Event;
 use crate::graphics::{GraphicsBackend, ImageRenderer};
-use crate::render::{DirtyRegion, ImageParams, Renderer};
+use crate::layout::Rect;
+use crate::render::{
+    Cell, CellGrid, ClipStack, CursorStyle, DirtyRegion, ImageParams, LayerStack, Progress,
+    Renderer,
+};
 use crate::style::Style;
-use crate::terminal::TerminalContext;
+use crate::terminal::{TerminalContext, TerminalGeometry};
+use crate::tui::guard::{self, Mode};
+use crate::tui::osc;
+use crate::tui::record::Recorder;
+use crate::tui::sgr::Pen;
+use crate::tui::viewport::{InlineRegion, Viewport};
+use crate::tui::writer::{FrameStats, FrameWriter, SharedOutput};
 use anyhow::Result;
-use std::fs::{File, OpenOptions};
-use std::io::{self, Write};
-use std::sync::mpsc;
-use std::thread;
+use std::fs::OpenOptions;
+use std::io::Write;
 use std::time::{Duration, Instant};
 
+/// Placeholder for cells whose on-screen content is unknown
+///
+/// Never produced by writes, so it never compares equal to real content.
+fn stale_cell() -> Cell {
+    Cell::new('\0', Style::new())
+}
+
 /// Terminal rendering backend using ANSI escape sequences
 ///
-/// Renders to an in-memory buffer, then sends completed frames to a
-/// background writer thread that owns /dev/tty. The main thread never
-/// blocks on terminal I/O.
+/// Renders to an in-memory cell grid, then sends the minimal diff as a
+/// frame to a background writer thread that owns /dev/tty. The main
+/// thread never blocks on terminal I/O.
 pub struct TerminalRenderer {
-    /// Frame buffer — all writes go here during a frame
+    /// Frame buffer — escape sequences and the emitted cell diff
     buffer: Vec<u8>,
-    /// Channel to send completed frames to the writer thread
-    frame_tx: mpsc::Sender<Vec<u8>>,
-    /// Direct tty handle for immediate writes (alt screen, raw mode setup)
-    tty_direct: File,
+    /// Cells written during the current frame
+    back: CellGrid,
+    /// Cells the terminal is known to display
+    front: CellGrid,
+    /// Logical cursor position used by writes
+    cursor: (u16, u16),
+    /// Real terminal cursor position, if known
+    term_cursor: Option<(u16, u16)>,
+    /// Cursor style last sent, or `None` while the terminal default applies
+    cursor_style: Option<CursorStyle>,
+    /// Title last set; `Some` once the user's title is on the title stack
+    title: Option<String>,
+    /// Progress last reported
+    progress: Progress,
+    /// Id of the last Kitty notification
+    notification_id: u32,
+    /// Active clip rectangles; writes outside the innermost one are dropped
+    clips: ClipStack,
+    /// Overlay layers composited above `back`
+    layers: LayerStack,
+    /// Scratch row for compositing layers during the diff
+    composed: Vec<Cell>,
+    /// Terminal rendition, so only changed SGR parameters are emitted
+    pen: Pen,
+    /// Background writer that owns the terminal output
+    writer: FrameWriter,
+    /// The current frame carries output a full repaint can't reproduce
+    must_deliver: bool,
+    /// Reserved region when rendering inline instead of fullscreen
+    inline: Option<InlineRegion>,
+    /// Direct output handle for immediate writes (alt screen, raw mode setup)
+    tty_direct: Box<dyn Write + Send>,
+    /// Output is the process's own /dev/tty (not a caller-supplied writer)
+    owns_tty: bool,
+    /// Active asciicast recording, if any
+    recorder: Option<Recorder>,
     context: TerminalContext,
     image_renderer: ImageRenderer,
     in_alt_screen: bool,
@@ -34,116 +87,162 @@ pub struct TerminalRenderer {
     scratch: String,
     /// Last flush duration for adaptive framerate
     last_flush_duration: Duration,
+    /// Wrap frames in synchronized-update markers (mode 2026)
+    sync_output: bool,
 }
 
 impl TerminalRenderer {
     /// Create a new renderer with detected terminal context and graphics backend
     pub fn new() -> Result<Self> {
+        Self::with_backend(GraphicsBackend::detect())
+    }
+
+    /// Create a new renderer with a specific graphics backend
+    pub fn with_backend(backend: GraphicsBackend) -> Result<Self> {
         let context = TerminalContext::detect()?;
-        let backend = GraphicsBackend::detect();
-        let in_tmux = context.capabilities.in_multiplexer;
 
         let tty_direct = OpenOptions::new()
             .write(true)
             .open("/dev/tty")?;
 
-        let (frame_tx, frame_rx) = mpsc::channel::<Vec<u8>>();
-
         // Writer thread owns its own /dev/tty fd — no stdout contention
-        thread::spawn(move || {
-            let tty = match OpenOptions::new().write(true).open("/dev/tty") {
-                Ok(f) => f,
-                Err(_) => return,
-            };
-            let mut tty = tty;
-            while let Ok(frame) = frame_rx.recv() {
-                // Write entire frame directly — no intermediate buffering
-                // that could cause partial flushes mid-escape-sequence
-                let _ = tty.write_all(&frame);
-                let _ = tty.flush();
-            }
-        });
-
-        Ok(TerminalRenderer {
-            buffer: Vec::with_capacity(64 * 1024),
-            frame_tx,
-            tty_direct,
-            context,
-            image_renderer: ImageRenderer::new(backend, in_tmux),
-            in_alt_screen: false,
-            dirty: DirtyRegion::new(),
-            scratch: String::with_capacity(256),
-            last_flush_duration: Duration::ZERO,
-        })
+        let writer = FrameWriter::spawn(|| OpenOptions::new().write(true).open("/dev/tty"));
+        let mut renderer = Self::from_parts(context, backend, writer, Box::new(tty_direct));
+        renderer.owns_tty = true;
+        Ok(renderer)
     }
 
-    /// Create a new renderer with a specific graphics backend
-    pub fn with_backend(backend: GraphicsBackend) -> Result<Self> {
-        let context = TerminalContext::detect()?;
-        let in_tmux = context.capabilities.in_multiplexer;
+    /// Create a renderer that draws to `output` instead of /dev/tty
+    ///
+    /// For serving the UI over a pty, socket, or SSH channel. The local
+    /// terminal is never queried: geometry comes from the caller (see
+    /// `set_geometry`) and images fall back to Unicode blocks.
+    pub fn with_writer<W: Write + Send + 'static>(output: W, geometry: TerminalGeometry) -> Self {
+        let context = TerminalContext::with_geometry(geometry);
+        let output = SharedOutput::new(output);
+        let thread_output = output.clone();
+        let writer = FrameWriter::spawn(move || Ok(thread_output));
 
-        let tty_direct = OpenOptions::new()
-            .write(true)
-            .open("/dev/tty")?;
+        let mut renderer =
+            Self::from_parts(context, GraphicsBackend::Blocks, writer, Box::new(output));
+        renderer.image_renderer = ImageRenderer::new(GraphicsBackend::Blocks, false);
+        renderer
+    }
 
-        let (frame_tx, frame_rx) = mpsc::channel::<Vec<u8>>();
+    /// Create a renderer that draws into `height` rows below the cursor
+    ///
+    /// The rest of the screen and the scrollback are left alone; see
+    /// `print_above` for emitting output above the live region.
+    pub fn inline(height: u16) -> Result<Self> {
+        let mut renderer = Self::new()?;
+        renderer.set_viewport(Viewport::Inline(height))?;
+        Ok(renderer)
+    }
 
-        thread::spawn(move || {
-            let tty = match OpenOptions::new().write(true).open("/dev/tty") {
-                Ok(f) => f,
-                Err(_) => return,
-            };
-            let mut tty = tty;
-            while let Ok(frame) = frame_rx.recv() {
-                // Write entire frame directly — no intermediate buffering
-                // that could cause partial flushes mid-escape-sequence
-                let _ = tty.write_all(&frame);
-                let _ = tty.flush();
+    /// Switch between fullscreen and inline rendering
+    ///
+    /// Reserving an inline region queries the cursor position from the terminal.
+    /// Renderers created with `with_writer` can't query it and place the
+    /// region at the bottom of the screen instead.
+    pub fn set_viewport(&mut self, viewport: Viewport) -> Result<()> {
+        let (cols, rows) = (self.context.geometry.cols, self.context.geometry.rows);
+        self.inline = match viewport {
+            Viewport::Fullscreen => None,
+            Viewport::Inline(height) => {
+                let cursor_row = if self.owns_tty {
+                    crossterm::cursor::position()?.1
+                } else {
+                    rows
+                };
+                Some(InlineRegion::reserve(cursor_row, rows, height, &mut self.buffer)?)
             }
-        });
+        };
+        self.must_deliver = true;
+        let (_, height) = self.dimensions();
+        self.back.resize(cols, height);
+        self.front.resize(cols, height);
+        self.layers.resize(cols, height);
+        self.invalidate();
+        Ok(())
+    }
 
-        Ok(TerminalRenderer {
-            buffer: Vec::with_capacity(64 * 1024),
-            frame_tx,
-            tty_direct,
-            context,
-            image_renderer: ImageRenderer::new(backend, in_tmux),
-            in_alt_screen: false,
-            dirty: DirtyRegion::new(),
-            scratch: String::with_capacity(256),
-            last_flush_duration: Duration::ZERO,
-        })
+    /// Current viewport mode
+    pub fn viewport(&self) -> Viewport {
+        match self.inline {
+            Some(region) => Viewport::Inline(region.height),
+            None => Viewport::Fullscreen,
+        }
+    }
+
+    /// Print text above the inline region so it scrolls into the scrollback
+    ///
+    /// Lines wider than the screen wrap. The region moves down (or the screen
+    /// scrolls) to make room and is repainted on the next frame. In
+    /// fullscreen mode this does nothing.
+    pub fn print_above(&mut self, text: &str) -> Result<()> {
+        let (cols, rows) = (self.context.geometry.cols, self.context.geometry.rows);
+        if let Some(region) = &mut self.inline {
+            let lines: Vec<&str> = text.lines().collect();
+            region.print_above(&lines, cols, rows, &mut self.buffer)?;
+            self.must_deliver = true;
+            self.invalidate();
+        }
+        Ok(())
     }
 
     /// Create a renderer for headless/test environments.
     pub fn headless() -> Self {
-        use crate::terminal::{TerminalContext, TerminalGeometry};
-
         let context =
             TerminalContext::with_geometry(TerminalGeometry::with_char_size(80, 24, 10, 20));
         let backend = GraphicsBackend::detect();
 
-        let (frame_tx, frame_rx) = mpsc::channel::<Vec<u8>>();
-        thread::spawn(move || {
-            while frame_rx.recv().is_ok() {}
-        });
+        let writer = FrameWriter::spawn(|| Ok(std::io::sink()));
 
-        // Headless uses /dev/null for direct writes
-        let tty_direct = OpenOptions::new()
-            .write(true)
-            .open("/dev/null")
-            .expect("/dev/null");
+        // Headless discards direct writes
+        let mut renderer = Self::from_parts(context, backend, writer, Box::new(std::io::sink()));
+        renderer.image_renderer = ImageRenderer::new(backend, false);
+        renderer
+    }
+
+    fn from_parts(
+        context: TerminalContext,
+        backend: GraphicsBackend,
+        writer: FrameWriter,
+        tty_direct: Box<dyn Write + Send>,
+    ) -> Self {
+        let in_tmux = context.capabilities.in_multiplexer;
+        let sync_output = context.capabilities.synchronized_output;
+        let (cols, rows) = (context.geometry.cols, context.geometry.rows);
+        let mut front = CellGrid::new(cols, rows);
+        front.fill(stale_cell());
 
         TerminalRenderer {
             buffer: Vec::with_capacity(64 * 1024),
-            frame_tx,
+            back: CellGrid::new(cols, rows),
+            front,
+            cursor: (0, 0),
+            term_cursor: None,
+            cursor_style: None,
+            title: None,
+            progress: Progress::Hidden,
+            notification_id: 0,
+            clips: ClipStack::new(),
+            layers: LayerStack::new(),
+            composed: Vec::new(),
+            pen: Pen::new(context.capabilities),
+            writer,
+            must_deliver: false,
+            inline: None,
             tty_direct,
+            owns_tty: false,
+            recorder: None,
             context,
-            image_renderer: ImageRenderer::new(backend, false),
+            image_renderer: ImageRenderer::new(backend, in_tmux),
             in_alt_screen: false,
             dirty: DirtyRegion::new(),
             scratch: String::with_capacity(256),
             last_flush_duration: Duration::ZERO,
+            sync_output,
         }
     }
 
@@ -153,11 +252,18 @@ impl TerminalRenderer {
     }
 
     /// Enter alternative screen buffer (immediate write)
+    ///
+    /// Does nothing in inline mode, which always draws on the normal screen.
     pub fn enter_alt_screen(&mut self) -> Result<()> {
-        if !self.in_alt_screen {
-            self.tty_direct.write_all(b"\x1b[?1049h")?;
-            self.tty_direct.flush()?;
+        if !self.in_alt_screen && self.inline.is_none() {
+            self.write_direct(b"\x1b[?1049h")?;
             self.in_alt_screen = true;
+            if self.owns_tty {
+                guard::set_mode(Mode::AltScreen, true);
+                // The alternate screen has its own keyboard flag stack
+                self.write_direct(guard::sync_keyboard_flags())?;
+            }
+            self.invalidate();
             let (cols, rows) = (self.context.geometry.cols, self.context.geometry.rows);
             self.dirty.mark_all(cols, rows);
         }
@@ -167,9 +273,18 @@ impl TerminalRenderer {
     /// Exit alternative screen buffer (immediate write)
     pub fn exit_alt_screen(&mut self) -> Result<()> {
         if self.in_alt_screen {
-            self.tty_direct.write_all(b"\x1b[?1049l\x1b[?25h")?;
-            self.tty_direct.flush()?;
+            if self.owns_tty {
+                self.write_direct(guard::release_alt_keyboard_flags())?;
+            }
+            self.write_direct(b"\x1b[?1049l\x1b[?25h")?;
             self.in_alt_screen = false;
+            if self.owns_tty {
+                guard::set_mode(Mode::AltScreen, false);
+                // Catch the normal screen up with an `EventPoller` created
+                // or dropped while the alternate screen showed
+                self.write_direct(guard::sync_keyboard_flags())?;
+            }
+            self.invalidate();
             self.dirty.clear();
         }
         Ok(())
@@ -181,12 +296,100 @@ impl TerminalRenderer {
     }
 
     /// Refresh terminal geometry (call after resize)
+    ///
+    /// Re-reads the size of /dev/tty; renderers created with `with_writer`
+    /// keep their current geometry and only re-layout.
     pub fn refresh_geometry(&mut self) -> Result<()> {
-        self.context.refresh_geometry()?;
+        if self.owns_tty {
+            self.context.refresh_geometry()?;
+        }
+        self.apply_geometry()
+    }
+
+    /// Replace the terminal geometry, e.g. when a remote client resizes
+    pub fn set_geometry(&mut self, geometry: TerminalGeometry) -> Result<()> {
+        self.context.geometry = geometry;
+        self.apply_geometry()
+    }
+
+    fn apply_geometry(&mut self) -> Result<()> {
+        let (cols, rows) = (self.context.geometry.cols, self.context.geometry.rows);
+        if let Some(recorder) = &self.recorder {
+            recorder.resize(cols, rows);
+        }
+        if let Some(region) = &mut self.inline {
+            region.resize(rows, &mut self.buffer)?;
+            self.must_deliver = true;
+        }
+        let (_, height) = self.dimensions();
+        self.back.resize(cols, height);
+        self.front.resize(cols, height);
+        self.layers.resize(cols, height);
+        self.invalidate();
         self.image_renderer.refresh_pane_info();
         Ok(())
     }
 
+    /// Stop the process for shell job control (Ctrl-Z) and pick up on resume
+    ///
+    /// Waits for queued frames, restores the terminal, and raises SIGTSTP.
+    /// Once the shell continues the process (SIGCONT), the modes that were
+    /// active are re-enabled, geometry is re-read, and the next frame repaints
+    /// every cell. An inline region is re-reserved at the new cursor line.
+    ///
+    /// Fails for renderers created with `with_writer`, which don't own the
+    /// process's terminal.
+    pub fn suspend(&mut self) -> Result<()> {
+        if !self.owns_tty {
+            anyhow::bail!("Suspend needs the controlling terminal");
+        }
+        self.writer.wait_idle();
+        if let Some(region) = &self.inline {
+            let mut parked = Vec::new();
+            region.finish(&mut parked)?;
+            self.write_direct(&parked)?;
+        }
+
+        let modes = guard::enabled_modes();
+        guard::restore_terminal()?;
+
+        // Execution stops here until SIGCONT
+        // SAFETY: raise has no preconditions; SIGTSTP's default action stops the process
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
+
+        guard::enable_modes(modes)?;
+        if let Some(style) = self.cursor_style {
+            write_cursor_style(&mut self.buffer, style, None)?;
+            self.must_deliver = true;
+        }
+        if let Some(title) = self.title.take() {
+            self.set_title(&title)?;
+        }
+        let progress = std::mem::replace(&mut self.progress, Progress::Hidden);
+        self.set_progress(progress)?;
+        self.pen.invalidate();
+        self.refresh_geometry()?;
+        if let Some(region) = self.inline {
+            self.set_viewport(Viewport::Inline(region.height))?;
+        }
+        let (cols, rows) = self.dimensions();
+        self.dirty.mark_all(cols, rows);
+        Ok(())
+    }
+
+    /// Forget what the terminal shows so the next frame repaints every cell
+    pub fn invalidate(&mut self) {
+        self.front.fill(stale_cell());
+        self.term_cursor = None;
+    }
+
+    /// Get the cells written so far (the pending frame)
+    pub fn cells(&self) -> &CellGrid {
+        &self.back
+    }
+
     /// Force refresh tmux pane position
     pub fn refresh_pane_info(&mut self) {
         self.image_renderer.refresh_pane_info();
@@ -197,6 +400,19 @@ impl TerminalRenderer {
         self.image_renderer.set_unicode_placeholders(enabled);
     }
 
+    /// Enable or disable synchronized output (defaults to the detected capability)
+    ///
+    /// Turn this off for terminals that misreport support or render
+    /// synchronized frames with visible delay.
+    pub fn set_synchronized_output(&mut self, enabled: bool) {
+        self.sync_output = enabled;
+    }
+
+    /// Whether frames are wrapped in synchronized-update markers
+    pub fn synchronized_output(&self) -> bool {
+        self.sync_output
+    }
+
     /// Check if running inside a terminal multiplexer
     pub fn in_multiplexer(&self) -> bool {
         self.context.capabilities.in_multiplexer
@@ -224,6 +440,9 @@ impl TerminalRenderer {
     /// Discard the current frame without sending
     pub fn discard_frame(&mut self) {
         self.buffer.clear();
+        self.must_deliver = false;
+        self.back.clone_from(&self.front);
+        self.pen.invalidate();
     }
 
     /// Get the duration of the last flush (for adaptive framerate)
@@ -231,6 +450,88 @@ impl TerminalRenderer {
         self.last_flush_duration
     }
 
+    /// Writer thread counters: queued, dropped, and written frames plus write time
+    ///
+    /// When the terminal can't keep up, waiting frames are dropped in favour
+    /// of a full repaint rather than replayed late.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.writer.stats()
+    }
+
+    /// Start recording the session to an asciicast v2 stream
+    ///
+    /// Every flushed frame is copied into the recording by the writer thread,
+    /// and the next frame repaints the whole screen so the cast starts from
+    /// a complete picture. Input is recorded through `record_input`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> anyhow::Result<()> {
+    /// let mut renderer = mkui::TerminalRenderer::new()?;
+    /// renderer.start_recording(std::fs::File::create("session.cast")?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_recording(&mut self, output: impl Write + Send + 'static) -> Result<()> {
+        self.stop_recording();
+        let (cols, rows) = (self.context.geometry.cols, self.context.geometry.rows);
+        let recorder = Recorder::start(output, cols, rows)?;
+        self.writer.set_recorder(Some(recorder.clone()));
+        self.recorder = Some(recorder);
+
+        self.invalidate();
+        self.pen.invalidate();
+        let (cols, rows) = self.dimensions();
+        self.dirty.mark_all(cols, rows);
+        Ok(())
+    }
+
+    /// Stop recording once the frames already flushed have been captured
+    pub fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            self.writer.wait_idle();
+            self.writer.set_recorder(None);
+            recorder.finish();
+        }
+    }
+
+    /// Whether a recording is active
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Add an input event to the active recording
+    pub fn record_input(&self, event: &Event) {
+        if let Some(recorder) = &self.recorder {
+            recorder.input(event);
+        }
+    }
+
+    /// Write to the terminal immediately, bypassing the frame writer
+    fn write_direct(&mut self, bytes: &[u8]) -> Result<()> {
+        self.tty_direct.write_all(bytes)?;
+        self.tty_direct.flush()?;
+        if let Some(recorder) = &self.recorder {
+            recorder.output(bytes);
+        }
+        Ok(())
+    }
+
+    /// Send a control sequence immediately, after any frame in flight
+    fn write_control(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.wait_idle();
+        self.write_direct(bytes)
+    }
+
+    /// Wrap a sequence for tmux, which drops OSC strings it doesn't know
+    fn passthrough(&self, sequence: Vec<u8>) -> Vec<u8> {
+        if self.context.capabilities.in_multiplexer {
+            osc::passthrough(&sequence)
+        } else {
+            sequence
+        }
+    }
+
     /// Get mutable access to the scratch buffer
     pub fn scratch_buffer(&mut self) -> &mut String {
         self.scratch.clear();
@@ -267,9 +568,86 @@ impl TerminalRenderer {
         let w = params.width_cells.unwrap_or((params.width / 10) as u16);
         let h = params.height_cells.unwrap_or((params.height / 20) as u16);
         self.dirty.mark_region(params.col, params.row, w, h);
+
+        // The image owns these cells now; keep the diff from painting over it
+        let area = Rect::new(params.col, params.row, w, h);
+        self.back.fill_rect(area, stale_cell());
+        self.front.fill_rect(area, stale_cell());
+        self.term_cursor = None;
+        self.must_deliver = true;
+    }
+
+    /// Write text into the back grid at the logical cursor
+    fn put_text(&mut self, text: &str, style: Style) {
+        self.cursor.0 = self
+            .layers
+            .target(&mut self.back)
+            .put_str_clipped(self.cursor, text, style, self.clips.current());
+    }
+
+    /// Emit the cells that differ between the back and front grids
+    fn emit_diff(&mut self) -> Result<()> {
+        // A waiting frame this diff builds on was dropped; repaint everything
+        if self.writer.reclaim() {
+            self.invalidate();
+        }
+
+        let mut cells = std::mem::take(&mut self.composed);
+        for row in 0..self.back.rows() {
+            self.layers.compose_row(&self.back, row, &mut cells);
+            for col in 0..cells.len() {
+                let cell = cells[col];
+                // Continuation cells are painted along with the wide grapheme before them
+                if cell == stale_cell()
+                    || cell.width == 0
+                    || !cell_changed(&cells, self.front.row(row), col)
+                {
+                    continue;
+                }
+                let col = col as u16;
+                self.emit_move(col, row)?;
+                self.pen.set(&cell.style, &mut self.buffer)?;
+                write!(self.buffer, "{}", cell.symbol)?;
+                let next = col + cell.width as u16;
+                for c in col..next {
+                    if let Some(&covered) = cells.get(c as usize) {
+                        self.front.set(c, row, covered);
+                    }
+                }
+                // Writing the last column leaves the cursor in a pending-wrap state
+                self.term_cursor = (next < self.back.cols()).then_some((next, row));
+            }
+        }
+        self.composed = cells;
+
+        // Leave the default rendition for anything written outside the grid
+        self.pen.reset(&mut self.buffer)?;
+        let (col, row) = self.cursor;
+        self.emit_move(col.min(self.back.cols().saturating_sub(1)), row)
+    }
+
+    /// Move the terminal cursor using the shortest sequence available
+    fn emit_move(&mut self, col: u16, row: u16) -> Result<()> {
+        match self.term_cursor {
+            Some(pos) if pos == (col, row) => return Ok(()),
+            Some((c, r)) if r == row && col == c + 1 => write!(self.buffer, "\x1b[C")?,
+            Some((c, r)) if r == row && col > c => write!(self.buffer, "\x1b[{}C", col - c)?,
+            _ => {
+                let origin = self.inline.map_or(0, |region| region.origin);
+                write!(self.buffer, "\x1b[{};{}H", origin + row + 1, col + 1)?
+            }
+        }
+        self.term_cursor = Some((col, row));
+        Ok(())
     }
 }
 
+/// Whether a cell (and any continuation it covers) differs from the screen
+fn cell_changed(cells: &[Cell], front: &[Cell], col: usize) -> bool {
+    let width = cells[col].width.max(1) as usize;
+    (col..(col + width).min(cells.len())).any(|c| cells.get(c) != front.get(c))
+}
+
 impl Renderer for TerminalRenderer {
     fn as_any(&self) -> &dyn std::any::Any {
         self
@@ -281,32 +659,26 @@ impl Renderer for TerminalRenderer {
 
     #[inline]
     fn write_text(&mut self, text: &str) -> Result<()> {
-        write!(self.buffer, "{}", text)?;
+        self.put_text(text, Style::new());
         Ok(())
     }
 
     #[inline]
     fn write_styled(&mut self, text: &str, style: &Style) -> Result<()> {
-        let ansi = style.to_ansi();
-        if ansi.is_empty() {
-            write!(self.buffer, "{}", text)?;
-        } else {
-            write!(self.buffer, "{}{}\x1b[0m", ansi, text)?;
-        }
+        self.put_text(text, *style);
         Ok(())
     }
 
     #[inline]
     fn write_repeated(&mut self, ch: char, count: usize) -> Result<()> {
-        for _ in 0..count {
-            write!(self.buffer, "{}", ch)?;
-        }
+        let text: String = std::iter::repeat_n(ch, count.min(u16::MAX as usize)).collect();
+        self.put_text(&text, Style::new());
         Ok(())
     }
 
     #[inline]
     fn move_cursor(&mut self, col: u16, row: u16) -> Result<()> {
-        write!(self.buffer, "\x1b[{};{}H", row + 1, col + 1)?;
+        self.cursor = (col, row);
         Ok(())
     }
 
@@ -320,33 +692,117 @@ impl Renderer for TerminalRenderer {
         Ok(())
     }
 
+    fn set_title(&mut self, title: &str) -> Result<()> {
+        if !self.context.capabilities.title {
+            return Ok(());
+        }
+        let mut sequence = Vec::new();
+        if self.title.is_none() {
+            sequence.extend_from_slice(osc::PUSH_TITLE);
+            if self.owns_tty {
+                guard::set_mode(Mode::Title, true);
+            }
+        }
+        osc::title(&mut sequence, title)?;
+        self.title = Some(title.to_string());
+        self.write_control(&sequence)
+    }
+
+    fn notify(&mut self, title: &str, body: &str) -> Result<()> {
+        let Some(protocol) = self.context.capabilities.notifications else {
+            return Ok(());
+        };
+        self.notification_id = self.notification_id.wrapping_add(1);
+        let mut sequence = Vec::new();
+        osc::notification(&mut sequence, protocol, self.notification_id, title, body)?;
+        let sequence = self.passthrough(sequence);
+        self.write_control(&sequence)
+    }
+
+    fn set_progress(&mut self, progress: Progress) -> Result<()> {
+        if !self.context.capabilities.progress || self.progress == progress {
+            return Ok(());
+        }
+        let mut sequence = Vec::new();
+        osc::progress(&mut sequence, progress)?;
+        self.progress = progress;
+        if self.owns_tty {
+            guard::set_mode(Mode::Progress, progress != Progress::Hidden);
+        }
+        let sequence = self.passthrough(sequence);
+        self.write_control(&sequence)
+    }
+
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        if self.cursor_style == Some(style) {
+            return Ok(());
+        }
+        let previous_color = self.cursor_style.and_then(|current| current.color);
+        write_cursor_style(&mut self.buffer, style, previous_color)?;
+        // A dropped frame would leave the terminal with the old cursor
+        self.must_deliver = true;
+        self.cursor_style = Some(style);
+        if self.owns_tty {
+            guard::set_mode(Mode::CursorStyle, true);
+        }
+        Ok(())
+    }
+
     fn clear(&mut self) -> Result<()> {
-        write!(self.buffer, "\x1b[2J")?;
-        let (cols, rows) = (self.context.geometry.cols, self.context.geometry.rows);
+        match self.inline {
+            // Only erase the region; everything above belongs to the shell
+            Some(region) => write!(self.buffer, "\x1b[{};1H\x1b[J", region.origin + 1)?,
+            None => write!(self.buffer, "\x1b[2J")?,
+        }
+        self.must_deliver = true;
+        self.term_cursor = None;
+        self.back.clear();
+        self.front.clear();
+        self.layers.clear_all();
+        let (cols, rows) = self.dimensions();
         self.dirty.mark_all(cols, rows);
         Ok(())
     }
 
     fn flush(&mut self) -> Result<()> {
+        self.emit_diff()?;
         if !self.buffer.is_empty() {
             let frame = std::mem::replace(&mut self.buffer, Vec::with_capacity(64 * 1024));
             let start = Instant::now();
-            let _ = self.frame_tx.send(frame);
+            self.writer.submit(frame, !self.must_deliver);
+            self.must_deliver = false;
             self.last_flush_duration = start.elapsed();
         }
         Ok(())
     }
 
-    fn fill_rect(&mut self, bounds: crate::layout::Rect, color: crate::theme::Color) -> Result<()> {
-        let style = Style::new().bg(color);
-        let ansi = style.to_ansi();
-        let spaces: String = std::iter::repeat_n(' ', bounds.width as usize).collect();
+    fn push_clip(&mut self, rect: Rect) {
+        self.clips.push(rect);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, z: u16) {
+        self.layers.push(z, self.back.cols(), self.back.rows());
+    }
+
+    fn pop_layer(&mut self) {
+        self.layers.pop();
+    }
 
-        for row in 0..bounds.height {
-            write!(self.buffer, "\x1b[{};{}H", bounds.y + row + 1, bounds.x + 1)?;
-            write!(self.buffer, "{}{}\x1b[0m", ansi, spaces)?;
+    fn clear_layer(&mut self, z: u16) {
+        if let Some(area) = self.layers.clear(z) {
+            self.dirty.mark_region(area.x, area.y, area.width, area.height);
         }
+    }
 
+    fn fill_rect(&mut self, bounds: Rect, color: crate::theme::Color) -> Result<()> {
+        let bounds = self.clips.apply(bounds);
+        self.layers
+            .target(&mut self.back)
+            .fill_rect(bounds, Cell::new(' ', Style::new().bg(color)));
         Ok(())
     }
 
@@ -362,12 +818,16 @@ impl Renderer for TerminalRenderer {
     }
 
     fn clear_images(&mut self) -> Result<()> {
+        self.must_deliver = true;
         self.image_renderer.delete_all_images(&mut self.buffer)?;
         Ok(())
     }
 
     fn dimensions(&self) -> (u16, u16) {
-        self.context.char_dimensions()
+        match self.inline {
+            Some(region) => (self.context.geometry.cols, region.height),
+            None => self.context.char_dimensions(),
+        }
     }
 
     fn cell_aspect(&self) -> f32 {
@@ -393,24 +853,78 @@ impl Renderer for TerminalRenderer {
 
     fn begin_frame(&mut self) -> Result<()> {
         // Begin synchronized update — terminal buffers until end_frame
-        write!(self.buffer, "\x1b[?2026h")?;
+        if self.sync_output {
+            write!(self.buffer, "\x1b[?2026h")?;
+        }
         self.hide_cursor()?;
         Ok(())
     }
 
     fn end_frame(&mut self) -> Result<()> {
+        self.emit_diff()?;
         self.show_cursor()?;
         // End synchronized update — terminal renders everything at once
-        write!(self.buffer, "\x1b[?2026l")?;
+        if self.sync_output {
+            write!(self.buffer, "\x1b[?2026l")?;
+        }
         self.flush()?;
         self.clear_dirty();
         Ok(())
     }
 }
 
+/// Write DECSCUSR for the shape and blink, then OSC 12 or 112 for the color
+fn write_cursor_style(
+    out: &mut Vec<u8>,
+    style: CursorStyle,
+    previous_color: Option<crate::theme::Color>,
+) -> Result<()> {
+    write!(out, "\x1b[{} q", style.decscusr())?;
+    match style.color {
+        Some(color) => {
+            let (r, g, b) = color.to_rgb();
+            write!(out, "\x1b]12;#{:02x}{:02x}{:02x}\x07", r, g, b)?;
+        }
+        None if previous_color.is_some() => out.extend_from_slice(b"\x1b]112\x07"),
+        None => {}
+    }
+    Ok(())
+}
+
 impl Drop for TerminalRenderer {
     fn drop(&mut self) {
+        if let Some(region) = self.inline {
+            let _ = self.emit_diff();
+            let _ = region.finish(&mut self.buffer);
+            let _ = self.flush();
+        }
+
+        // Let the writer finish the waiting frame first
+        self.writer.close();
+
         let _ = self.exit_alt_screen();
+        if let Some(style) = self.cursor_style.take() {
+            let reset: &[u8] = match style.color {
+                Some(_) => b"\x1b[0 q\x1b]112\x07",
+                None => b"\x1b[0 q",
+            };
+            let _ = self.write_direct(reset);
+            if self.owns_tty {
+                guard::set_mode(Mode::CursorStyle, false);
+            }
+        }
+        if self.progress != Progress::Hidden {
+            let _ = self.set_progress(Progress::Hidden);
+        }
+        if self.title.take().is_some() {
+            let _ = self.write_direct(osc::POP_TITLE);
+            if self.owns_tty {
+                guard::set_mode(Mode::Title, false);
+            }
+        }
+        if let Some(recorder) = self.recorder.take() {
+            recorder.finish();
+        }
     }
 }
 
@@ -424,4 +938,229 @@ mod tests {
         assert_eq!(renderer.context.geometry.cols, 80);
         assert_eq!(renderer.context.geometry.rows, 24);
     }
+
+    fn emitted(renderer: &mut TerminalRenderer) -> String {
+        renderer.emit_diff().unwrap();
+        String::from_utf8(std::mem::take(&mut renderer.buffer)).unwrap()
+    }
+
+    #[test]
+    fn test_unchanged_frame_emits_nothing() {
+        let mut renderer = TerminalRenderer::headless();
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("hello").unwrap();
+        assert!(emitted(&mut renderer).contains("hello"));
+
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("hello").unwrap();
+        assert_eq!(emitted(&mut renderer), "");
+    }
+
+    #[test]
+    fn test_only_changed_cells_emitted() {
+        let mut renderer = TerminalRenderer::headless();
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("hello").unwrap();
+        emitted(&mut renderer);
+
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("help!").unwrap();
+        assert_eq!(emitted(&mut renderer), "\x1b[1;4Hp!");
+    }
+
+    #[test]
+    fn test_wide_grapheme_emitted_once() {
+        let mut renderer = TerminalRenderer::headless();
+        emitted(&mut renderer);
+
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("日x").unwrap();
+        assert_eq!(emitted(&mut renderer), "日x");
+        assert_eq!(renderer.cursor, (3, 0));
+    }
+
+    #[test]
+    fn test_style_change_emits_sgr() {
+        let mut renderer = TerminalRenderer::headless();
+        renderer.move_cursor(2, 1).unwrap();
+        renderer.write_text("ab").unwrap();
+        emitted(&mut renderer);
+
+        renderer.move_cursor(2, 1).unwrap();
+        renderer.write_styled("ab", &Style::new().bold(true)).unwrap();
+        assert_eq!(emitted(&mut renderer), "\x1b[2;3H\x1b[1mab\x1b[0m");
+    }
+
+    #[test]
+    fn test_synchronized_output_opt_out() {
+        let mut renderer = TerminalRenderer::headless();
+        renderer.set_synchronized_output(true);
+        renderer.begin_frame().unwrap();
+        assert!(renderer.buffer.starts_with(b"\x1b[?2026h"));
+
+        renderer.discard_frame();
+        renderer.set_synchronized_output(false);
+        renderer.begin_frame().unwrap();
+        assert!(!renderer.buffer.windows(8).any(|w| w == b"\x1b[?2026"));
+    }
+
+    #[test]
+    fn test_frame_stats_count_flushed_frames() {
+        let mut renderer = TerminalRenderer::headless();
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("hello").unwrap();
+        renderer.flush().unwrap();
+        renderer.writer.close();
+        let stats = renderer.frame_stats();
+        assert_eq!((stats.written, stats.dropped, stats.queued), (1, 0, 0));
+    }
+
+    /// Output that keeps everything written to it
+    #[derive(Clone, Default)]
+    struct Capture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Capture {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_with_writer_sends_frames_to_output() {
+        let output = Capture::default();
+        let geometry = TerminalGeometry::with_char_size(20, 5, 10, 20);
+        let mut renderer = TerminalRenderer::with_writer(output.clone(), geometry);
+        assert_eq!(renderer.dimensions(), (20, 5));
+
+        renderer.enter_alt_screen().unwrap();
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("remote").unwrap();
+        renderer.flush().unwrap();
+        renderer.set_geometry(TerminalGeometry::with_char_size(30, 6, 10, 20)).unwrap();
+        assert_eq!(renderer.dimensions(), (30, 6));
+        drop(renderer);
+
+        let written = output.text();
+        let alt = written.find("\x1b[?1049h").unwrap();
+        assert!(alt < written.find("remote").unwrap());
+        assert!(written.ends_with("\x1b[?1049l\x1b[?25h"));
+    }
+
+    #[test]
+    fn test_cursor_style_sent_once_and_reset_on_drop() {
+        use crate::render::CursorShape;
+        use crate::theme::Color;
+
+        let output = Capture::default();
+        let geometry = TerminalGeometry::with_char_size(20, 5, 10, 20);
+        let mut renderer = TerminalRenderer::with_writer(output.clone(), geometry);
+
+        let bar = CursorStyle::new(CursorShape::Bar).blinking(true).color(Color::rgb(255, 0, 0));
+        renderer.set_cursor_style(bar).unwrap();
+        renderer.set_cursor_style(bar).unwrap();
+        renderer.flush().unwrap();
+        renderer.set_cursor_style(CursorStyle::new(CursorShape::Block)).unwrap();
+        renderer.flush().unwrap();
+        drop(renderer);
+
+        let written = output.text();
+        assert_eq!(written.matches("\x1b[5 q\x1b]12;#ff0000\x07").count(), 1);
+        assert!(written.contains("\x1b[2 q\x1b]112\x07"));
+        assert!(written.ends_with("\x1b[0 q"));
+    }
+
+    #[test]
+    fn test_title_restored_and_unsupported_osc_skipped() {
+        let output = Capture::default();
+        let geometry = TerminalGeometry::with_char_size(20, 5, 10, 20);
+        let mut renderer = TerminalRenderer::with_writer(output.clone(), geometry);
+        renderer.context.capabilities.title = true;
+        renderer.context.capabilities.notifications = None;
+        renderer.context.capabilities.progress = false;
+
+        renderer.set_title("one").unwrap();
+        renderer.set_title("two").unwrap();
+        renderer.notify("Export", "done").unwrap();
+        renderer.set_progress(Progress::Normal(50)).unwrap();
+        drop(renderer);
+
+        assert_eq!(
+            output.text(),
+            "\x1b[22;0t\x1b]0;one\x07\x1b]0;two\x07\x1b[23;0t"
+        );
+    }
+
+    #[test]
+    fn test_recording_tees_frames() {
+        let geometry = TerminalGeometry::with_char_size(20, 5, 10, 20);
+        let mut renderer = TerminalRenderer::with_writer(std::io::sink(), geometry);
+        let cast = Capture::default();
+        renderer.start_recording(cast.clone()).unwrap();
+        assert!(renderer.is_recording());
+
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("cast").unwrap();
+        renderer.flush().unwrap();
+        renderer.writer.wait_idle();
+        renderer.record_input(&Event::key(crate::event::Key::Enter));
+        renderer.stop_recording();
+
+        let text = cast.text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].contains("\"width\": 20, \"height\": 5"));
+        assert!(lines[1].contains("\"o\"") && lines[1].contains("cast"));
+        assert!(lines[2].ends_with("\"i\", \"\\r\"]"));
+    }
+
+    #[test]
+    fn test_inline_region_offsets_output() {
+        let mut renderer = TerminalRenderer::headless();
+        renderer.inline = Some(InlineRegion { origin: 10, height: 3 });
+        renderer.back.resize(80, 3);
+        renderer.front.resize(80, 3);
+        assert_eq!(renderer.dimensions(), (80, 3));
+
+        renderer.move_cursor(0, 1).unwrap();
+        renderer.write_text("x").unwrap();
+        renderer.write_text("y").unwrap();
+        let out = emitted(&mut renderer);
+        assert!(out.contains("\x1b[12;1H"));
+        assert!(!out.contains("\x1b[2;1H"));
+    }
+
+    #[test]
+    fn test_fill_rect_writes_cells() {
+        let mut renderer = TerminalRenderer::headless();
+        let color = crate::theme::Color::Rgb(10, 20, 30);
+        renderer.fill_rect(Rect::new(1, 1, 2, 2), color).unwrap();
+        assert_eq!(renderer.cells().get(2, 2).and_then(|c| c.style.bg), Some(color));
+        assert_eq!(renderer.cells().get(3, 2).and_then(|c| c.style.bg), None);
+    }
+
+    #[test]
+    fn test_clip_truncates_text_and_fills() {
+        let mut renderer = TerminalRenderer::headless();
+        let color = crate::theme::Color::Rgb(10, 20, 30);
+        renderer.push_clip(Rect::new(2, 0, 3, 1));
+        renderer.fill_rect(Rect::new(0, 0, 10, 2), color).unwrap();
+        renderer.move_cursor(0, 0).unwrap();
+        renderer.write_text("abcdefg").unwrap();
+        renderer.pop_clip();
+
+        let row: String = renderer.cells().row(0)[..8].iter().map(|c| c.symbol()).collect();
+        assert_eq!(row, "  cde   ");
+        assert_eq!(renderer.cells().get(1, 0).and_then(|c| c.style.bg), None);
+        assert_eq!(renderer.cells().get(2, 1).and_then(|c| c.style.bg), None);
+    }
 }
@@ -0,0 +1,322 @@
+//! SGR pen - tracks the terminal's graphic rendition between writes
+//!
+//! Consecutive cells usually share most of their style, so instead of
+//! resetting and re-sending every attribute the pen emits only the
+//! parameters that differ from what the terminal is already using. Links
+//! are tracked the same way: OSC 8 is only sent when the target changes.
+//! Underline shapes and colors fall back to a plain underline on terminals
+//! that don't understand SGR 4:x and 58.
+
+use crate::style::{Hyperlink, Style, UnderlineStyle};
+use crate::terminal::TerminalCapabilities;
+use crate::theme::color::rgb_to_256;
+use crate::theme::Color;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Current rendition of the terminal plus cached color codes
+pub(crate) struct Pen {
+    /// Style the terminal is drawing with, or `None` when unknown
+    current: Option<Style>,
+    /// Open hyperlink, or `None` when unknown
+    link: Option<Option<Hyperlink>>,
+    caps: TerminalCapabilities,
+    /// Degraded foreground parameters per color
+    fg_codes: HashMap<Color, String>,
+    /// Degraded background parameters per color
+    bg_codes: HashMap<Color, String>,
+    /// OSC 8 sequence opening each link, so the intern table is only
+    /// consulted once per link
+    link_codes: HashMap<Hyperlink, Vec<u8>>,
+}
+
+impl Pen {
+    /// Create a pen in the unknown state
+    pub fn new(caps: TerminalCapabilities) -> Self {
+        Pen {
+            current: None,
+            link: None,
+            caps,
+            fg_codes: HashMap::new(),
+            bg_codes: HashMap::new(),
+            link_codes: HashMap::new(),
+        }
+    }
+
+    /// Forget the terminal's rendition so the next `set` starts from a reset
+    pub fn invalidate(&mut self) {
+        self.current = None;
+        self.link = None;
+    }
+
+    /// Switch to `style`, emitting only the parameters that changed
+    pub fn set(&mut self, style: &Style, out: &mut Vec<u8>) -> std::io::Result<()> {
+        self.set_link(style.link, out)?;
+        let target = normalize(style, self.caps.styled_underlines);
+        if self.current == Some(target) {
+            return Ok(());
+        }
+
+        let mut params = String::new();
+        match self.current {
+            Some(current) if !target.is_empty() => self.push_delta(&current, &target, &mut params),
+            _ => {
+                params.push('0');
+                self.push_delta(&Style::new(), &target, &mut params);
+            }
+        }
+        self.current = Some(target);
+        write!(out, "\x1b[{}m", params)
+    }
+
+    /// Return to the default rendition and close any link
+    pub fn reset(&mut self, out: &mut Vec<u8>) -> std::io::Result<()> {
+        self.set(&Style::new(), out)
+    }
+
+    /// Open `link`, or close the open link for `None`
+    fn set_link(&mut self, link: Option<Hyperlink>, out: &mut Vec<u8>) -> std::io::Result<()> {
+        if !self.caps.hyperlinks || self.link == Some(link) {
+            return Ok(());
+        }
+        self.link = Some(link);
+        let Some(link) = link else {
+            return out.write_all(b"\x1b]8;;\x1b\\");
+        };
+        // Bounded like the intern table it caches
+        if self.link_codes.len() >= crate::style::MAX_LINKS {
+            self.link_codes.clear();
+        }
+        let code = self.link_codes.entry(link).or_insert_with(|| {
+            let (uri, id) = link.target();
+            match id {
+                Some(id) => format!("\x1b]8;id={};{}\x1b\\", id, uri).into_bytes(),
+                None => format!("\x1b]8;;{}\x1b\\", uri).into_bytes(),
+            }
+        });
+        out.write_all(code)
+    }
+
+    /// SGR 58 parameters for an underline color, in the colon form
+    fn underline_color_params(&self, color: Color) -> String {
+        let (r, g, b) = color.to_rgb();
+        if self.caps.truecolor {
+            format!("58:2::{}:{}:{}", r, g, b)
+        } else {
+            format!("58:5:{}", rgb_to_256(r, g, b))
+        }
+    }
+
+    fn push_delta(&mut self, from: &Style, to: &Style, params: &mut String) {
+        // Bold and dim share one "normal intensity" reset
+        let intensity_off = (from.bold.is_some() && to.bold.is_none())
+            || (from.dim.is_some() && to.dim.is_none());
+        if intensity_off {
+            push(params, "22");
+        }
+        if to.bold.is_some() && (intensity_off || from.bold.is_none()) {
+            push(params, "1");
+        }
+        if to.dim.is_some() && (intensity_off || from.dim.is_none()) {
+            push(params, "2");
+        }
+
+        for (from_on, to_on, on, off) in [
+            (from.italic, to.italic, "3", "23"),
+            (from.blink, to.blink, "5", "25"),
+            (from.reverse, to.reverse, "7", "27"),
+            (from.strikethrough, to.strikethrough, "9", "29"),
+            (from.overline, to.overline, "53", "55"),
+        ] {
+            if from_on != to_on {
+                push(params, if to_on.is_some() { on } else { off });
+            }
+        }
+
+        if (from.underline, from.underline_style) != (to.underline, to.underline_style) {
+            match to.underline {
+                Some(_) => push(params, to.underline_style.unwrap_or_default().sgr()),
+                None => push(params, "24"),
+            }
+        }
+        if from.underline_color != to.underline_color {
+            match to.underline_color {
+                Some(color) => push(params, &self.underline_color_params(color)),
+                None => push(params, "59"),
+            }
+        }
+
+        if from.fg != to.fg {
+            match to.fg {
+                Some(color) => {
+                    let caps = &self.caps;
+                    let code = self
+                        .fg_codes
+                        .entry(color)
+                        .or_insert_with(|| sgr_params(&color.degrade(caps)));
+                    push(params, code);
+                }
+                None => push(params, "39"),
+            }
+        }
+        if from.bg != to.bg {
+            match to.bg {
+                Some(color) => {
+                    let caps = &self.caps;
+                    let code = self
+                        .bg_codes
+                        .entry(color)
+                        .or_insert_with(|| sgr_params(&color.bg_ansi(caps)));
+                    push(params, code);
+                }
+                None => push(params, "49"),
+            }
+        }
+    }
+}
+
+/// Keep only attributes that are switched on, so `Some(false)` equals unset
+///
+/// Underline shape and color only count while underlined, and only on
+/// terminals with `styled_underlines`; a single underline equals unset.
+fn normalize(style: &Style, styled_underlines: bool) -> Style {
+    let on = |flag: Option<bool>| flag.filter(|&on| on);
+    let underline = on(style.underline);
+    let styled = underline.is_some() && styled_underlines;
+    Style {
+        fg: style.fg,
+        bg: style.bg,
+        bold: on(style.bold),
+        dim: on(style.dim),
+        italic: on(style.italic),
+        underline,
+        underline_style: style
+            .underline_style
+            .filter(|&shape| styled && shape != UnderlineStyle::Single),
+        underline_color: style.underline_color.filter(|_| styled),
+        strikethrough: on(style.strikethrough),
+        overline: on(style.overline),
+        blink: on(style.blink),
+        reverse: on(style.reverse),
+        link: None,
+    }
+}
+
+/// Strip the CSI introducer and final `m` from a complete SGR sequence
+fn sgr_params(sequence: &str) -> String {
+    sequence
+        .trim_start_matches("\x1b[")
+        .trim_end_matches('m')
+        .to_string()
+}
+
+fn push(params: &mut String, code: &str) {
+    if !params.is_empty() {
+        params.push(';');
+    }
+    params.push_str(code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pen() -> Pen {
+        Pen::new(TerminalCapabilities {
+            kitty_graphics: false,
+            sixel: false,
+            truecolor: true,
+            colors_256: true,
+            in_multiplexer: false,
+            mouse: false,
+            synchronized_output: false,
+            title: false,
+            notifications: None,
+            progress: false,
+            hyperlinks: false,
+            styled_underlines: false,
+        })
+    }
+
+    fn emit(pen: &mut Pen, style: Style) -> String {
+        let mut out = Vec::new();
+        pen.set(&style, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_unknown_state_starts_with_reset() {
+        let mut pen = pen();
+        assert_eq!(emit(&mut pen, Style::new().bold(true)), "\x1b[0;1m");
+        assert_eq!(emit(&mut pen, Style::new().bold(true)), "");
+    }
+
+    #[test]
+    fn test_only_changed_attributes_emitted() {
+        let mut pen = pen();
+        let red = Color::Rgb(255, 0, 0);
+        emit(&mut pen, Style::new().fg(red));
+        assert_eq!(emit(&mut pen, Style::new().fg(red).underline(true)), "\x1b[4m");
+        assert_eq!(emit(&mut pen, Style::new().underline(true)), "\x1b[39m");
+        assert_eq!(emit(&mut pen, Style::new()), "\x1b[0m");
+    }
+
+    #[test]
+    fn test_intensity_reset_keeps_remaining_flag() {
+        let mut pen = pen();
+        emit(&mut pen, Style::new().bold(true).dim(true));
+        assert_eq!(emit(&mut pen, Style::new().dim(true)), "\x1b[22;2m");
+    }
+
+    #[test]
+    fn test_colors_degraded_to_capabilities() {
+        let mut pen = pen();
+        pen.caps.truecolor = false;
+        let style = Style::new().fg(Color::Rgb(255, 255, 255)).bg(Color::Rgb(0, 0, 0));
+        assert_eq!(emit(&mut pen, style), "\x1b[0;38;5;231;48;5;16m");
+        assert_eq!(pen.fg_codes.len(), 1);
+    }
+
+    #[test]
+    fn test_reset_is_skipped_when_already_default() {
+        let mut pen = pen();
+        let mut out = Vec::new();
+        pen.set(&Style::new(), &mut out).unwrap();
+        out.clear();
+        pen.reset(&mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_styled_underline_degrades_to_plain() {
+        let error = Style::new()
+            .underline_style(UnderlineStyle::Curly)
+            .underline_color(Color::Rgb(255, 0, 0))
+            .strikethrough(true);
+
+        let mut plain = pen();
+        assert_eq!(emit(&mut plain, error), "\x1b[0;9;4m");
+
+        let mut styled = pen();
+        styled.caps.styled_underlines = true;
+        assert_eq!(emit(&mut styled, error), "\x1b[0;9;4:3;58:2::255:0:0m");
+        assert_eq!(emit(&mut styled, Style::new().underline(true)), "\x1b[29;4;59m");
+        assert_eq!(emit(&mut styled, Style::new().overline(true)), "\x1b[53;24m");
+    }
+
+    #[test]
+    fn test_links_open_once_and_close_on_reset() {
+        let mut pen = pen();
+        pen.caps.hyperlinks = true;
+        let link = Style::new().link(Hyperlink::with_id("https://example.com", "1"));
+        assert_eq!(
+            emit(&mut pen, link),
+            "\x1b]8;id=1;https://example.com\x1b\\\x1b[0m"
+        );
+        assert_eq!(emit(&mut pen, link), "");
+
+        let mut out = Vec::new();
+        pen.reset(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\x1b]8;;\x1b\\");
+    }
+}
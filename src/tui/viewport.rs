@@ -0,0 +1,174 @@
+//! Inline viewport - a fixed-height live region below the shell prompt
+//!
+//! Instead of taking over the alternate screen, an inline viewport reserves
+//! `height` rows starting at the cursor line. Everything above it stays in
+//! the normal scrollback, and lines printed "above" the region push the
+//! region down (scrolling the screen once it reaches the bottom).
+
+use crate::unicode;
+use std::io::Write;
+
+/// How a `TerminalRenderer` occupies the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Viewport {
+    /// The whole (alternate) screen
+    Fullscreen,
+    /// A fixed number of rows drawn inline in the normal screen
+    Inline(u16),
+}
+
+/// Screen position of a reserved inline region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct InlineRegion {
+    /// Screen row of the region's first line
+    pub origin: u16,
+    /// Number of rows in the region
+    pub height: u16,
+}
+
+impl InlineRegion {
+    /// Reserve `height` rows starting at `cursor_row`, scrolling if the region
+    /// would run past the bottom of the screen
+    pub fn reserve(
+        cursor_row: u16,
+        screen_rows: u16,
+        height: u16,
+        out: &mut Vec<u8>,
+    ) -> std::io::Result<Self> {
+        let height = height.clamp(1, screen_rows.max(1));
+        // Newlines scroll the screen once the cursor sits on the last row
+        out.write_all(b"\r")?;
+        for _ in 1..height {
+            out.write_all(b"\n")?;
+        }
+        let origin = cursor_row.min(screen_rows.saturating_sub(height));
+        Ok(InlineRegion { origin, height })
+    }
+
+    /// Print lines into the scrollback above the region, moving the region down
+    ///
+    /// Lines wider than `screen_cols` are wrapped here so the region moves by
+    /// the rows they really take. The region's rows are erased; callers must
+    /// repaint it afterwards.
+    pub fn print_above(
+        &mut self,
+        lines: &[&str],
+        screen_cols: u16,
+        screen_rows: u16,
+        out: &mut Vec<u8>,
+    ) -> std::io::Result<()> {
+        let rows: Vec<&str> = lines
+            .iter()
+            .flat_map(|line| wrap_rows(line, screen_cols as usize))
+            .collect();
+        let room = screen_rows.saturating_sub(self.height).max(1) as usize;
+        for chunk in rows.chunks(room) {
+            let needed = self.origin as usize + self.height as usize + chunk.len();
+            let overflow = needed.saturating_sub(screen_rows as usize) as u16;
+            if overflow > 0 {
+                write!(out, "\x1b[{};1H", screen_rows)?;
+                for _ in 0..overflow {
+                    out.write_all(b"\n")?;
+                }
+                self.origin = self.origin.saturating_sub(overflow);
+            }
+
+            write!(out, "\x1b[{};1H\x1b[J", self.origin + 1)?;
+            for line in chunk {
+                write!(out, "{}\r\n", line)?;
+            }
+            self.origin += chunk.len() as u16;
+        }
+        Ok(())
+    }
+
+    /// Keep the region on screen after a resize, erasing reflowed leftovers
+    pub fn resize(&mut self, screen_rows: u16, out: &mut Vec<u8>) -> std::io::Result<()> {
+        self.height = self.height.min(screen_rows.max(1));
+        self.origin = self.origin.min(screen_rows.saturating_sub(self.height));
+        write!(out, "\x1b[{};1H\x1b[J", self.origin + 1)
+    }
+
+    /// Park the cursor on the line below the region, leaving the last frame intact
+    pub fn finish(&self, out: &mut Vec<u8>) -> std::io::Result<()> {
+        write!(out, "\x1b[0m\x1b[{};1H\r\n\x1b[?25h", self.origin + self.height)
+    }
+}
+
+/// Split a line into pieces that each fit in one screen row
+fn wrap_rows(line: &str, cols: usize) -> Vec<&str> {
+    let mut rows = Vec::new();
+    let mut rest = line;
+    loop {
+        let mut row = unicode::truncate(rest, cols.max(1));
+        if row.is_empty() && !rest.is_empty() {
+            // A wide grapheme on a one-column screen still takes a row
+            row = unicode::graphemes(rest).next().unwrap_or(rest);
+        }
+        rows.push(row);
+        rest = &rest[row.len()..];
+        if rest.is_empty() {
+            return rows;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_at_cursor() {
+        let mut out = Vec::new();
+        let region = InlineRegion::reserve(3, 24, 5, &mut out).unwrap();
+        assert_eq!(region, InlineRegion { origin: 3, height: 5 });
+        assert_eq!(out, b"\r\n\n\n\n");
+    }
+
+    #[test]
+    fn test_reserve_near_bottom_scrolls() {
+        let mut out = Vec::new();
+        let region = InlineRegion::reserve(22, 24, 5, &mut out).unwrap();
+        assert_eq!(region.origin, 19);
+    }
+
+    #[test]
+    fn test_print_above_moves_region_down() {
+        let mut region = InlineRegion { origin: 2, height: 3 };
+        let mut out = Vec::new();
+        region.print_above(&["one", "two"], 80, 24, &mut out).unwrap();
+        assert_eq!(region.origin, 4);
+        assert_eq!(String::from_utf8(out).unwrap(), "\x1b[3;1H\x1b[Jone\r\ntwo\r\n");
+    }
+
+    #[test]
+    fn test_print_above_at_bottom_scrolls() {
+        let mut region = InlineRegion { origin: 7, height: 3 };
+        let mut out = Vec::new();
+        region.print_above(&["a", "b"], 80, 10, &mut out).unwrap();
+        // Region stays pinned to the bottom of the screen
+        assert_eq!(region.origin, 7);
+        assert!(String::from_utf8(out).unwrap().starts_with("\x1b[10;1H\n\n\x1b[6;1H"));
+    }
+
+    #[test]
+    fn test_print_above_wraps_wide_lines() {
+        let mut region = InlineRegion { origin: 2, height: 3 };
+        let mut out = Vec::new();
+        region.print_above(&["abcdefghij", "日本語"], 4, 24, &mut out).unwrap();
+        // "abcdefghij" takes three rows and "日本語" two
+        assert_eq!(region.origin, 7);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b[3;1H\x1b[Jabcd\r\nefgh\r\nij\r\n日本\r\n語\r\n"
+        );
+    }
+
+    #[test]
+    fn test_resize_clamps_region() {
+        let mut region = InlineRegion { origin: 20, height: 6 };
+        let mut out = Vec::new();
+        region.resize(10, &mut out).unwrap();
+        assert_eq!(region, InlineRegion { origin: 4, height: 6 });
+    }
+}
@@ -0,0 +1,412 @@
+//! Frame writer - background thread that owns the terminal output
+//!
+//! Frames are handed over through a single pending slot instead of an
+//! unbounded queue. While the terminal is still busy writing, a new frame is
+//! merged into the one waiting in the slot. Frames that only repaint grid
+//! cells are kept apart, after the ones that must be delivered, so they can
+//! be reclaimed and dropped; the renderer then sends a full repaint in their
+//! place. Frames that must be delivered only pile up to
+//! `MAX_PENDING_BYTES`, after which `submit` waits for the terminal.
+
+use crate::tui::record::Recorder;
+use std::io::{self, Write};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Bytes of must-deliver frames that can wait before `submit` blocks
+pub(crate) const MAX_PENDING_BYTES: usize = 1 << 20;
+
+/// Writer thread counters, surfaced by `TerminalRenderer::frame_stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Frames waiting for the terminal (merged into one pending write)
+    pub queued: usize,
+    /// Bytes waiting for the terminal
+    pub queued_bytes: usize,
+    /// Frames dropped because a newer frame superseded them
+    pub dropped: u64,
+    /// Frames written to the terminal
+    pub written: u64,
+    /// Time the last terminal write took
+    pub last_write: Duration,
+}
+
+/// Caller-supplied output shared by the writer thread and direct writes
+///
+/// Every `write` delivers the whole buffer under the lock, so a frame and
+/// an immediate write (e.g. leaving the alternate screen) never interleave.
+#[derive(Clone)]
+pub(crate) struct SharedOutput {
+    inner: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl SharedOutput {
+    pub fn new(output: impl Write + Send + 'static) -> Self {
+        SharedOutput {
+            inner: Arc::new(Mutex::new(Box::new(output))),
+        }
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut out = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        out.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner).flush()
+    }
+}
+
+/// Bytes waiting for the writer thread
+#[derive(Default)]
+struct Pending {
+    /// Frames that have to reach the terminal, in order
+    deliver: Vec<u8>,
+    /// Frames after them that a full repaint can stand in for
+    droppable: Vec<u8>,
+    /// Number of frames merged into `deliver`
+    deliver_frames: usize,
+    /// Number of frames merged into `droppable`
+    droppable_frames: usize,
+}
+
+impl Pending {
+    fn frames(&self) -> usize {
+        self.deliver_frames + self.droppable_frames
+    }
+
+    fn len(&self) -> usize {
+        self.deliver.len() + self.droppable.len()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    pending: Option<Pending>,
+    /// The writer thread is in the middle of a terminal write
+    writing: bool,
+    /// Session recording that gets a copy of every written frame
+    recorder: Option<Recorder>,
+    closed: bool,
+    stats: FrameStats,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    ready: Condvar,
+    idle: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Handle to the background writer thread
+pub(crate) struct FrameWriter {
+    shared: Arc<Shared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl FrameWriter {
+    /// Spawn the writer thread; `open` runs on that thread to create the output
+    ///
+    /// If the output can't be opened, frames are discarded.
+    pub fn spawn<W, F>(open: F) -> Self
+    where
+        W: Write + 'static,
+        F: FnOnce() -> io::Result<W> + Send + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+        let thread_shared = Arc::clone(&shared);
+        let thread = thread::spawn(move || {
+            let out: Box<dyn Write> = match open() {
+                Ok(out) => Box::new(out),
+                Err(_) => Box::new(io::sink()),
+            };
+            write_frames(&thread_shared, out);
+        });
+
+        FrameWriter {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Hand a frame to the writer, merging it into any frame still waiting
+    ///
+    /// A frame that isn't droppable waits while `MAX_PENDING_BYTES` of
+    /// must-deliver frames are already queued for a stalled terminal.
+    pub fn submit(&self, frame: Vec<u8>, droppable: bool) {
+        let mut state = self.shared.lock();
+        if !droppable {
+            while state
+                .pending
+                .as_ref()
+                .is_some_and(|pending| pending.deliver.len() >= MAX_PENDING_BYTES)
+                && !state.closed
+            {
+                state = self.shared.idle.wait(state).unwrap_or_else(PoisonError::into_inner);
+            }
+        }
+
+        let pending = state.pending.get_or_insert_with(Pending::default);
+        if droppable {
+            pending.droppable.extend_from_slice(&frame);
+            pending.droppable_frames += 1;
+        } else {
+            // Keep the order: droppable frames before this one go out first
+            let earlier = std::mem::take(&mut pending.droppable);
+            pending.deliver.extend_from_slice(&earlier);
+            pending.deliver.extend_from_slice(&frame);
+            pending.deliver_frames += std::mem::take(&mut pending.droppable_frames) + 1;
+        }
+        self.shared.ready.notify_one();
+    }
+
+    /// Drop waiting frames that a full repaint can replace
+    ///
+    /// Frames that must be delivered stay queued. Returns true when frames
+    /// were dropped; the caller must then repaint everything in the next
+    /// frame it submits.
+    pub fn reclaim(&self) -> bool {
+        let mut state = self.shared.lock();
+        let Some(pending) = &mut state.pending else {
+            return false;
+        };
+        if pending.droppable_frames == 0 {
+            return false;
+        }
+        let dropped = std::mem::take(&mut pending.droppable_frames);
+        pending.droppable = Vec::new();
+        if pending.deliver_frames == 0 {
+            state.pending = None;
+        }
+        state.stats.dropped += dropped as u64;
+        true
+    }
+
+    /// Tee written frames into a recording, or stop teeing with `None`
+    pub fn set_recorder(&self, recorder: Option<Recorder>) {
+        self.shared.lock().recorder = recorder;
+    }
+
+    /// Block until every submitted frame has reached the terminal
+    pub fn wait_idle(&self) {
+        let mut state = self.shared.lock();
+        while (state.pending.is_some() || state.writing) && !state.closed {
+            state = self.shared.idle.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Snapshot of the writer counters
+    pub fn stats(&self) -> FrameStats {
+        let state = self.shared.lock();
+        FrameStats {
+            queued: state.pending.as_ref().map_or(0, Pending::frames),
+            queued_bytes: state.pending.as_ref().map_or(0, Pending::len),
+            ..state.stats
+        }
+    }
+
+    /// Stop accepting frames and wait for the waiting one to be written
+    pub fn close(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.ready.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for FrameWriter {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Writer thread body: write pending frames until closed
+fn write_frames(shared: &Shared, mut out: Box<dyn Write>) {
+    loop {
+        let (pending, recorder) = {
+            let mut state = shared.lock();
+            while state.pending.is_none() && !state.closed {
+                state = shared.ready.wait(state).unwrap_or_else(PoisonError::into_inner);
+            }
+            match state.pending.take() {
+                Some(pending) => {
+                    state.writing = true;
+                    (pending, state.recorder.clone())
+                }
+                None => return,
+            }
+        };
+
+        // Write entire frame directly — no intermediate buffering
+        // that could cause partial flushes mid-escape-sequence
+        let start = Instant::now();
+        let mut bytes = pending.deliver;
+        bytes.extend_from_slice(&pending.droppable);
+        let _ = out.write_all(&bytes);
+        let _ = out.flush();
+        if let Some(recorder) = recorder {
+            recorder.output(&bytes);
+        }
+
+        let mut state = shared.lock();
+        state.stats.written += (pending.deliver_frames + pending.droppable_frames) as u64;
+        state.stats.last_write = start.elapsed();
+        state.writing = false;
+        shared.idle.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// Output that blocks each write until the test releases it
+    struct Gate {
+        release: mpsc::Receiver<()>,
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Write for Gate {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let _ = self.release.recv();
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn gated() -> (FrameWriter, mpsc::Sender<()>, Arc<Mutex<Vec<u8>>>) {
+        let (release_tx, release) = mpsc::channel();
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let gate = Gate {
+            release,
+            written: Arc::clone(&written),
+        };
+        (FrameWriter::spawn(move || Ok(gate)), release_tx, written)
+    }
+
+    /// Wait until the writer thread has taken the pending frame
+    fn wait_until_taken(writer: &FrameWriter) {
+        while writer.stats().queued > 0 {
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn test_superseded_frame_is_reclaimed() {
+        let (mut writer, release, written) = gated();
+        writer.submit(b"one".to_vec(), true);
+        wait_until_taken(&writer);
+
+        writer.submit(b"two".to_vec(), true);
+        assert_eq!(writer.stats().queued, 1);
+        assert!(writer.reclaim());
+        writer.submit(b"three".to_vec(), true);
+
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        writer.close();
+        assert_eq!(written.lock().unwrap().as_slice(), b"onethree");
+        let stats = writer.stats();
+        assert_eq!((stats.dropped, stats.written, stats.queued), (1, 2, 0));
+    }
+
+    #[test]
+    fn test_undroppable_frames_are_merged() {
+        let (mut writer, release, written) = gated();
+        writer.submit(b"a".to_vec(), true);
+        wait_until_taken(&writer);
+
+        writer.submit(b"b".to_vec(), false);
+        writer.submit(b"c".to_vec(), false);
+        assert!(!writer.reclaim());
+        assert_eq!(writer.stats().queued, 2);
+
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        writer.close();
+        assert_eq!(written.lock().unwrap().as_slice(), b"abc");
+        assert_eq!(writer.stats().written, 3);
+    }
+
+    #[test]
+    fn test_droppable_frames_after_undroppable_are_reclaimed() {
+        let (mut writer, release, written) = gated();
+        writer.submit(b"a".to_vec(), true);
+        wait_until_taken(&writer);
+
+        writer.submit(b"b".to_vec(), false);
+        writer.submit(b"c".to_vec(), true);
+        assert!(writer.reclaim());
+        writer.submit(b"d".to_vec(), true);
+
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        writer.close();
+        assert_eq!(written.lock().unwrap().as_slice(), b"abd");
+        let stats = writer.stats();
+        assert_eq!((stats.dropped, stats.written), (1, 3));
+    }
+
+    #[test]
+    fn test_pending_bytes_stay_bounded_while_blocked() {
+        let (writer, release, _) = gated();
+        writer.submit(b"first".to_vec(), false);
+        wait_until_taken(&writer);
+
+        // The terminal is stuck on the first write. Keep rendering the way
+        // the renderer does, with the odd frame that has to be delivered.
+        let frame = vec![b'x'; 4096];
+        for i in 0..1000 {
+            // The renderer would repaint everything after a reclaim
+            writer.reclaim();
+            writer.submit(frame.clone(), i % 100 != 0);
+            assert!(writer.stats().queued_bytes <= 11 * frame.len());
+        }
+
+        // Must-deliver frames alone block the caller once over the limit
+        let writer = Arc::new(writer);
+        let submitter = {
+            let writer = Arc::clone(&writer);
+            thread::spawn(move || {
+                let frame = vec![b'y'; MAX_PENDING_BYTES / 4];
+                for _ in 0..16 {
+                    writer.submit(frame.clone(), false);
+                    assert!(writer.stats().queued_bytes <= MAX_PENDING_BYTES + frame.len());
+                }
+            })
+        };
+        while !submitter.is_finished() {
+            let _ = release.send(());
+            thread::yield_now();
+        }
+        submitter.join().unwrap();
+        // Let the writer drain on drop
+        drop(release);
+    }
+
+    #[test]
+    fn test_wait_idle_returns_after_write() {
+        let (writer, release, written) = gated();
+        writer.submit(b"frame".to_vec(), true);
+        release.send(()).unwrap();
+        writer.wait_idle();
+        assert_eq!(written.lock().unwrap().as_slice(), b"frame");
+        assert_eq!(writer.stats().written, 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! Text measurement - display width, grapheme segmentation, and truncation
+//!
+//! Terminal cells hold grapheme clusters, not chars: CJK ideographs and most
+//! emoji occupy two columns, combining marks and ZWJ sequences fold into the
+//! cluster they extend. Anything that positions or truncates text should
+//! measure with these helpers instead of `len()` or `chars().count()`.
+
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Ellipsis appended by `truncate_with_ellipsis`
+pub const ELLIPSIS: &str = "…";
+
+/// Iterate over the extended grapheme clusters of a string
+pub fn graphemes(text: &str) -> impl Iterator<Item = &str> {
+    text.graphemes(true)
+}
+
+/// Number of terminal columns a single grapheme cluster occupies (0, 1, or 2)
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.chars().all(char::is_control) {
+        return 0;
+    }
+    grapheme.width().clamp(1, 2)
+}
+
+/// Number of terminal columns a string occupies
+pub fn width(text: &str) -> usize {
+    graphemes(text).map(grapheme_width).sum()
+}
+
+/// Longest prefix of whole graphemes that fits in `max_width` columns
+pub fn truncate(text: &str, max_width: usize) -> &str {
+    let mut used = 0;
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        used += grapheme_width(grapheme);
+        if used > max_width {
+            return &text[..offset];
+        }
+    }
+    text
+}
+
+/// Truncate to `max_width` columns, ending in an ellipsis when text was cut
+pub fn truncate_with_ellipsis(text: &str, max_width: usize) -> Cow<'_, str> {
+    if width(text) <= max_width {
+        return Cow::Borrowed(text);
+    }
+    if max_width == 0 {
+        return Cow::Borrowed("");
+    }
+    let head = truncate(text, max_width - width(ELLIPSIS));
+    Cow::Owned(format!("{}{}", head, ELLIPSIS))
+}
+
+/// Drop whole graphemes from the front until at least `columns` are skipped
+pub fn skip_width(text: &str, columns: usize) -> &str {
+    let mut skipped = 0;
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        if skipped >= columns {
+            return &text[offset..];
+        }
+        skipped += grapheme_width(grapheme);
+    }
+    ""
+}
+
+/// Byte offset of the grapheme boundary before `offset` (0 at the start)
+pub fn prev_boundary(text: &str, offset: usize) -> usize {
+    text[..offset]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(i, _)| i)
+}
+
+/// Byte offset of the grapheme boundary after `offset` (`text.len()` at the end)
+pub fn next_boundary(text: &str, offset: usize) -> usize {
+    text[offset..]
+        .graphemes(true)
+        .next()
+        .map_or(text.len(), |g| offset + g.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("日本語"), 6);
+        assert_eq!(width("e\u{301}"), 1);
+        assert_eq!(width("👨‍👩‍👧"), 2);
+        assert_eq!(width("a\tb"), 2);
+    }
+
+    #[test]
+    fn test_truncate_keeps_whole_graphemes() {
+        assert_eq!(truncate("日本語", 5), "日本");
+        assert_eq!(truncate("e\u{301}x", 1), "e\u{301}");
+        assert_eq!(truncate("abc", 10), "abc");
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate_with_ellipsis("hello", 5), "hello");
+        assert_eq!(truncate_with_ellipsis("hello world", 6), "hello…");
+        assert_eq!(truncate_with_ellipsis("日本語", 4), "日…");
+        assert_eq!(truncate_with_ellipsis("abc", 0), "");
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let text = "ae\u{301}b";
+        assert_eq!(next_boundary(text, 1), 4);
+        assert_eq!(prev_boundary(text, 4), 1);
+        assert_eq!(prev_boundary(text, 0), 0);
+        assert_eq!(next_boundary(text, text.len()), text.len());
+    }
+
+    #[test]
+    fn test_skip_width() {
+        assert_eq!(skip_width("abcdef", 2), "cdef");
+        assert_eq!(skip_width("日本語", 3), "語");
+        assert_eq!(skip_width("ab", 5), "");
+    }
+}